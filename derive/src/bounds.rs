@@ -0,0 +1,136 @@
+// Rust language amplification derive library providing multiple generic trait
+// implementations, type wrappers, derive macros and other language enhancements
+//
+// Written in 2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the MIT License
+// along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+use proc_macro2::{TokenStream as TokenStream2, TokenTree};
+use quote::ToTokens;
+use syn::punctuated::Punctuated;
+use syn::{
+    GenericParam, Generics, Ident, Lit, Meta, MetaNameValue, NestedMeta, Result, Type, TypePath,
+    WherePredicate,
+};
+
+/// Trait bounds on the generic type parameters required by a generated
+/// implementation
+#[derive(Clone)]
+pub(crate) struct Bounds {
+    /// Names of the generic type parameters
+    params: Vec<Ident>,
+    /// Bounds inferred from the types used by the implementation
+    inferred: Vec<WherePredicate>,
+    /// Bounds given with `bound = "..."` argument; they replace the inferred
+    /// ones
+    custom: Option<Vec<WherePredicate>>,
+}
+
+impl Bounds {
+    pub fn with(generics: &Generics, custom: Option<Vec<WherePredicate>>) -> Self {
+        Bounds {
+            params: generics
+                .params
+                .iter()
+                .filter_map(|param| match param {
+                    GenericParam::Type(param) => Some(param.ident.clone()),
+                    _ => None,
+                })
+                .collect(),
+            inferred: vec![],
+            custom,
+        }
+    }
+
+    /// Checks whether the type depends on any of the generic type parameters
+    pub fn is_generic(&self, ty: &impl ToTokens) -> bool {
+        fn mentions(tokens: TokenStream2, params: &[Ident]) -> bool {
+            tokens.into_iter().any(|token| match token {
+                TokenTree::Ident(ident) => params.contains(&ident),
+                TokenTree::Group(group) => mentions(group.stream(), params),
+                _ => false,
+            })
+        }
+        mentions(ty.to_token_stream(), &self.params)
+    }
+
+    /// Checks whether the type is one of the generic type parameters
+    pub fn is_param(&self, ty: &Type) -> bool {
+        match ty {
+            Type::Path(TypePath { qself: None, path }) => {
+                matches!(path.get_ident(), Some(ident) if self.params.contains(ident))
+            }
+            _ => false,
+        }
+    }
+
+    /// Requires the type to implement the trait if either of them depends on
+    /// the generic type parameters
+    pub fn add(&mut self, ty: &impl ToTokens, bound: TokenStream2) {
+        if self.is_generic(ty) || self.is_generic(&bound) {
+            self.push(parse_quote! { #ty: #bound });
+        }
+    }
+
+    /// Requires the type for which the trait is implemented to implement
+    /// another trait, if the type has generic type parameters
+    pub fn add_self(&mut self, bound: TokenStream2) {
+        if !self.params.is_empty() {
+            self.push(parse_quote! { Self: #bound });
+        }
+    }
+
+    fn push(&mut self, predicate: WherePredicate) {
+        let repr = predicate.to_token_stream().to_string();
+        if !self
+            .inferred
+            .iter()
+            .any(|p| p.to_token_stream().to_string() == repr)
+        {
+            self.inferred.push(predicate);
+        }
+    }
+
+    /// Returns generics of the type extended with the bounds, which should be
+    /// used for the implementation
+    pub fn apply(&self, generics: &Generics) -> Generics {
+        let mut generics = generics.clone();
+        let predicates = self.custom.as_ref().unwrap_or(&self.inferred);
+        if !predicates.is_empty() {
+            generics
+                .make_where_clause()
+                .predicates
+                .extend(predicates.iter().cloned());
+        }
+        generics
+    }
+}
+
+/// Parses `bound = "T: Trait, ..."` argument of a derive attribute, returning
+/// `None` if the argument has other form
+pub(crate) fn parse_bound(nested: &NestedMeta) -> Option<Result<Vec<WherePredicate>>> {
+    match nested {
+        NestedMeta::Meta(Meta::NameValue(MetaNameValue { path, lit, .. }))
+            if path.is_ident("bound") =>
+        {
+            Some(match lit {
+                Lit::Str(bound) => bound
+                    .parse_with(Punctuated::<WherePredicate, Token![,]>::parse_terminated)
+                    .map(|predicates| predicates.into_iter().collect()),
+                _ => Err(syn::Error::new_spanned(
+                    lit,
+                    "bounds must be given as a string literal, like `bound = \"T: Display\"`",
+                )),
+            })
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,207 @@
+// Rust language amplification derive library providing multiple generic trait
+// implementations, type wrappers, derive macros and other language enhancements
+//
+// Written in 2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the MIT License
+// along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+use std::collections::HashMap;
+use std::convert::TryInto;
+use std::iter::FromIterator;
+
+use amplify_syn::{ArgValueReq, AttrReq, ParametrizedAttr, ValueClass};
+use proc_macro2::{Ident, Span, TokenStream as TokenStream2};
+use syn::spanned::Spanned;
+use syn::{Data, DeriveInput, Error, Expr, Fields, LitStr, Result};
+
+use crate::util::get_amplify_crate;
+
+pub(crate) fn derive(input: DeriveInput) -> Result<TokenStream2> {
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let struct_name = &input.ident;
+    let vis = &input.vis;
+    let amplify_crate = get_amplify_crate(&input);
+
+    let mut global_param = ParametrizedAttr::with("builder", &input.attrs)?;
+    let global = BuilderDerive::try_from(&mut global_param, true)?;
+
+    let fields = match input.data {
+        Data::Struct(ref data) => match data.fields {
+            Fields::Named(ref fields) => &fields.named,
+            _ => {
+                return Err(Error::new_spanned(
+                    &input,
+                    "Deriving builder is supported only for structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(Error::new_spanned(
+                &input,
+                "Deriving builder is supported only for structs with named fields",
+            ))
+        }
+    };
+
+    let builder_name = global
+        .name
+        .as_ref()
+        .map(|name| Ident::new(&name.value(), name.span()))
+        .unwrap_or_else(|| Ident::new(&format!("{}Builder", struct_name), Span::call_site()));
+    let builder_doc = format!(
+        "Builder for [`{}`], which checks that all required fields are set.",
+        struct_name
+    );
+    let ctor_doc = format!(
+        "Constructs [`{}`] for creating [`{}`] instances.",
+        builder_name, struct_name
+    );
+    let build_doc = format!(
+        "Constructs [`{}`] from the values provided to the builder.\n\n# Errors\n\n\
+         Returns `MissingFieldError` if a field without a default value was not set.",
+        struct_name
+    );
+    let struct_str = struct_name.to_string();
+
+    let mut field_defs = Vec::with_capacity(fields.len());
+    let mut field_inits = Vec::with_capacity(fields.len());
+    let mut methods = Vec::with_capacity(fields.len());
+    let mut values = Vec::with_capacity(fields.len());
+    for field in fields {
+        let mut local_param = ParametrizedAttr::with("builder", &field.attrs)?;
+        let local = BuilderDerive::try_from(&mut local_param, false)?;
+
+        let name = field
+            .ident
+            .as_ref()
+            .expect("named fields always have ident");
+        let ty = &field.ty;
+        let name_str = name.to_string();
+        let (arg_ty, value) = if global.into || local.into {
+            (
+                quote! { impl ::core::convert::Into<#ty> },
+                quote! { ::core::convert::Into::into(value) },
+            )
+        } else {
+            (quote! { #ty }, quote! { value })
+        };
+        let fn_doc = format!("Sets value of [`{}::{}`] field.", struct_name, name);
+
+        field_defs.push(quote_spanned! { field.span() => #name: ::core::option::Option<#ty> });
+        field_inits.push(quote! { #name: ::core::option::Option::None });
+        methods.push(quote_spanned! { field.span() =>
+            #[doc = #fn_doc]
+            #[inline]
+            pub fn #name(mut self, value: #arg_ty) -> Self {
+                self.#name = ::core::option::Option::Some(#value);
+                self
+            }
+        });
+        values.push(match local.default {
+            None => quote_spanned! { field.span() =>
+                #name: self.#name.ok_or(#amplify_crate::MissingFieldError {
+                    ty: #struct_str,
+                    field: #name_str,
+                })?
+            },
+            Some(None) => quote_spanned! { field.span() =>
+                #name: self.#name.unwrap_or_default()
+            },
+            Some(Some(expr)) => quote_spanned! { field.span() =>
+                #name: self.#name.unwrap_or_else(|| #expr)
+            },
+        });
+    }
+
+    Ok(quote! {
+        #[doc = #builder_doc]
+        #vis struct #builder_name #impl_generics #where_clause {
+            #( #field_defs ),*
+        }
+
+        impl #impl_generics ::core::default::Default for #builder_name #ty_generics #where_clause {
+            fn default() -> Self {
+                #builder_name {
+                    #( #field_inits ),*
+                }
+            }
+        }
+
+        impl #impl_generics #struct_name #ty_generics #where_clause {
+            #[doc = #ctor_doc]
+            #[inline]
+            pub fn builder() -> #builder_name #ty_generics {
+                <#builder_name #ty_generics as ::core::default::Default>::default()
+            }
+        }
+
+        impl #impl_generics #builder_name #ty_generics #where_clause {
+            #( #methods )*
+
+            #[doc = #build_doc]
+            pub fn build(
+                self,
+            ) -> ::core::result::Result<#struct_name #ty_generics, #amplify_crate::MissingFieldError> {
+                ::core::result::Result::Ok(#struct_name {
+                    #( #values ),*
+                })
+            }
+        }
+    })
+}
+
+struct BuilderDerive {
+    /// Name of the builder type
+    pub name: Option<LitStr>,
+    pub into: bool,
+    /// Default value for the field: `Some(None)` stands for [`Default`]
+    pub default: Option<Option<Expr>>,
+}
+
+impl BuilderDerive {
+    fn try_from(attr: &mut ParametrizedAttr, global: bool) -> Result<BuilderDerive> {
+        let mut map = HashMap::from_iter(vec![("into", ArgValueReq::Prohibited)]);
+
+        if global {
+            map.insert("name", ArgValueReq::Optional(ValueClass::str()));
+        } else {
+            map.insert("default", ArgValueReq::with_default(""));
+        }
+
+        attr.check(AttrReq::with(map))?;
+
+        let default = attr
+            .args
+            .get("default")
+            .map(|a| -> Result<_> {
+                let lit: LitStr = a.clone().try_into()?;
+                if lit.value().is_empty() {
+                    Ok(None)
+                } else {
+                    lit.parse().map(Some)
+                }
+            })
+            .transpose()?;
+
+        Ok(BuilderDerive {
+            name: if global {
+                attr.args
+                    .get("name")
+                    .map(|a| a.clone().try_into())
+                    .transpose()?
+            } else {
+                None
+            },
+            into: attr.args.contains_key("into"),
+            default,
+        })
+    }
+}
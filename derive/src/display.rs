@@ -14,18 +14,26 @@
 // along with this software.
 // If not, see <https://opensource.org/licenses/MIT>.
 
+use std::collections::BTreeSet;
+
 use proc_macro2::{Span, TokenStream as TokenStream2};
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
 use syn::spanned::Spanned;
 use syn::{
-    Attribute, Data, DataEnum, DataStruct, DataUnion, DeriveInput, Error, Fields, Ident, Lit,
-    LitStr, Meta, MetaNameValue, NestedMeta, Path, Result, Index,
+    Attribute, Data, DataEnum, DataStruct, DataUnion, DeriveInput, Error, Expr, Fields, Ident,
+    Index, Lit, LitStr, Member, Meta, MetaList, MetaNameValue, NestedMeta, Path, Result, Type,
+    WherePredicate,
 };
 
+use crate::bounds::{parse_bound, Bounds};
+use crate::format::{self, escape, parse_format, Arg, Location, Piece, Placeholder};
+
 const NAME: &str = "display";
 const EXAMPLE: &str = r#"#[display("format {} string" | Trait | Type::function)]"#;
 
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
-enum FormattingTrait {
+pub(crate) enum FormattingTrait {
     Debug,
     Octal,
     Binary,
@@ -79,6 +87,51 @@ impl FormattingTrait {
         quote! { #fmt }
     }
 
+    /// Returns trait selected by the formatting type of a format string
+    /// placeholder, or `None` for `Display`
+    pub fn from_spec_type(ty: &str) -> Option<Self> {
+        match ty {
+            "?" | "x?" | "X?" => Some(FormattingTrait::Debug),
+            "o" => Some(FormattingTrait::Octal),
+            "b" => Some(FormattingTrait::Binary),
+            "p" => Some(FormattingTrait::Pointer),
+            "x" => Some(FormattingTrait::LowerHex),
+            "X" => Some(FormattingTrait::UpperHex),
+            "e" => Some(FormattingTrait::LowerExp),
+            "E" => Some(FormattingTrait::UpperExp),
+            _ => None,
+        }
+    }
+
+    /// Returns path to the trait, or to `Display` if no trait is given
+    pub fn path_or_display(fmt_trait: Option<Self>) -> TokenStream2 {
+        match fmt_trait {
+            None => quote! { ::core::fmt::Display },
+            Some(FormattingTrait::Debug) => quote! { ::core::fmt::Debug },
+            Some(FormattingTrait::Octal) => quote! { ::core::fmt::Octal },
+            Some(FormattingTrait::Binary) => quote! { ::core::fmt::Binary },
+            Some(FormattingTrait::Pointer) => quote! { ::core::fmt::Pointer },
+            Some(FormattingTrait::LowerHex) => quote! { ::core::fmt::LowerHex },
+            Some(FormattingTrait::UpperHex) => quote! { ::core::fmt::UpperHex },
+            Some(FormattingTrait::LowerExp) => quote! { ::core::fmt::LowerExp },
+            Some(FormattingTrait::UpperExp) => quote! { ::core::fmt::UpperExp },
+        }
+    }
+
+    /// Returns formatting type used in format strings for the trait
+    pub fn spec_type(self) -> &'static str {
+        match self {
+            FormattingTrait::Debug => "?",
+            FormattingTrait::Octal => "o",
+            FormattingTrait::Binary => "b",
+            FormattingTrait::Pointer => "p",
+            FormattingTrait::LowerHex => "x",
+            FormattingTrait::UpperHex => "X",
+            FormattingTrait::LowerExp => "e",
+            FormattingTrait::UpperExp => "E",
+        }
+    }
+
     pub fn into_token_stream2(self, span: Span) -> TokenStream2 {
         match self {
             FormattingTrait::Debug => quote_spanned! { span =>
@@ -109,15 +162,174 @@ impl FormattingTrait {
     }
 }
 
+/// Case conversion style for the type and enum variant names
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub(crate) enum CaseStyle {
+    /// `lowercase`
+    Lower,
+    /// `UPPERCASE`
+    Upper,
+    /// `snake_case`
+    Snake,
+    /// `kebab-case`
+    Kebab,
+    /// `camelCase`
+    Camel,
+    /// `PascalCase`
+    Pascal,
+    /// `SCREAMING_SNAKE_CASE`
+    ScreamingSnake,
+    /// `Title Case`
+    Title,
+}
+
+impl CaseStyle {
+    /// Detects style from the attribute argument. Matching is
+    /// case-insensitive and ignores underscores, so `snake_case`,
+    /// `SnakeCase` and `SNAKE_CASE` are all the same.
+    pub fn from_ident(ident: &Ident) -> Option<Self> {
+        let name = ident.to_string().to_lowercase().replace('_', "");
+        Some(match name.as_str() {
+            "lowercase" => CaseStyle::Lower,
+            "uppercase" => CaseStyle::Upper,
+            "snakecase" => CaseStyle::Snake,
+            "kebabcase" => CaseStyle::Kebab,
+            "camelcase" => CaseStyle::Camel,
+            "pascalcase" => CaseStyle::Pascal,
+            "screamingsnakecase" => CaseStyle::ScreamingSnake,
+            "titlecase" => CaseStyle::Title,
+            _ => return None,
+        })
+    }
+
+    /// Converts identifier into the given case style
+    pub fn apply(self, ident: &str) -> String {
+        fn capitalize(word: &str) -> String {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| {
+                    first
+                        .to_uppercase()
+                        .chain(chars.flat_map(char::to_lowercase))
+                })
+                .into_iter()
+                .flatten()
+                .collect()
+        }
+
+        let words = split_words(ident);
+        match self {
+            CaseStyle::Lower => ident.to_lowercase(),
+            CaseStyle::Upper => ident.to_uppercase(),
+            CaseStyle::Snake => words.join("_").to_lowercase(),
+            CaseStyle::Kebab => words.join("-").to_lowercase(),
+            CaseStyle::ScreamingSnake => words.join("_").to_uppercase(),
+            CaseStyle::Pascal => words.iter().map(|w| capitalize(w)).collect(),
+            CaseStyle::Camel => words
+                .iter()
+                .enumerate()
+                .map(|(no, w)| {
+                    if no == 0 {
+                        w.to_lowercase()
+                    } else {
+                        capitalize(w)
+                    }
+                })
+                .collect(),
+            CaseStyle::Title => words
+                .iter()
+                .map(|w| capitalize(w))
+                .collect::<Vec<_>>()
+                .join(" "),
+        }
+    }
+}
+
+/// Splits identifier into words on underscores and case boundaries; keeps
+/// acronyms together, so `HTTPServer` gives `HTTP` and `Server`
+fn split_words(ident: &str) -> Vec<String> {
+    let chars = ident.chars().collect::<Vec<_>>();
+    let mut words = vec![];
+    let mut word = String::new();
+    for (pos, c) in chars.iter().enumerate() {
+        if *c == '_' {
+            if !word.is_empty() {
+                words.push(word);
+                word = String::new();
+            }
+            continue;
+        }
+        if c.is_uppercase() && !word.is_empty() {
+            let prev = chars[pos - 1];
+            let next_lower = chars
+                .get(pos + 1)
+                .map(|c| c.is_lowercase())
+                .unwrap_or_default();
+            if prev.is_lowercase() || prev.is_numeric() || (prev.is_uppercase() && next_lower) {
+                words.push(word);
+                word = String::new();
+            }
+        }
+        word.push(*c);
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    words
+}
+
 #[derive(Clone)]
-enum Technique {
+pub(crate) enum Technique {
     FromTrait(FormattingTrait),
     FromMethod(Path),
-    WithFormat(LitStr, Option<LitStr>),
+    FromWriteMethod(Path),
+    WithFormat(LitStr),
     DocComments(String),
     Inner,
-    Lowercase(String),
-    Uppercase(String),
+    Case(CaseStyle, String),
+}
+
+/// Parses `#[display(...)]` attribute. Unlike [`Attribute::parse_meta`]
+/// allows paths to be used as values of named arguments (like
+/// `fmt = Self::write_human`); such paths are converted into string literals.
+fn parse_meta(attr: &Attribute) -> Result<Meta> {
+    struct Arg(NestedMeta);
+
+    impl Parse for Arg {
+        fn parse(input: ParseStream) -> Result<Self> {
+            if input.peek(Lit) {
+                return input.parse().map(NestedMeta::Lit).map(Arg);
+            }
+            let path: Path = input.parse()?;
+            if !input.peek(Token![=]) {
+                return Ok(Arg(NestedMeta::Meta(Meta::Path(path))));
+            }
+            let eq_token = input.parse()?;
+            let lit = if input.peek(Lit) {
+                input.parse()?
+            } else {
+                let value: Path = input.parse()?;
+                Lit::Str(LitStr::new(&quote! { #value }.to_string(), value.span()))
+            };
+            Ok(Arg(NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                path,
+                eq_token,
+                lit,
+            }))))
+        }
+    }
+
+    attr.parse_meta().or_else(|err| {
+        let args = attr
+            .parse_args_with(Punctuated::<Arg, Token![,]>::parse_terminated)
+            .map_err(|_| err)?;
+        Ok(Meta::List(MetaList {
+            path: attr.path.clone(),
+            paren_token: Default::default(),
+            nested: args.into_iter().map(|arg| arg.0).collect(),
+        }))
+    })
 }
 
 impl Technique {
@@ -129,70 +341,68 @@ impl Technique {
             .clone()
             .into_iter()
             .find(|attr| attr.path.is_ident(NAME))
-            .map(|attr| attr.parse_meta())
+            .map(parse_meta)
             .map_or(Ok(None), |r| r.map(Some))?
         {
             Some(Meta::List(list)) => {
-                if list.nested.len() > 2 {
-                    return Err(attr_err!(span, "too many arguments"));
-                }
-                let mut iter = list.nested.iter();
-                let mut res = match iter.next() {
+                let mut iter = list.nested.iter().filter(|nested| {
+                    !Alternates::is_alternate(nested) && parse_bound(nested).is_none()
+                });
+                let res = match iter.next() {
                     Some(NestedMeta::Lit(Lit::Str(format))) => {
-                        Some(Technique::WithFormat(format.clone(), None))
+                        Some(Technique::WithFormat(format.clone()))
                     }
+                    Some(NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                        path,
+                        lit: Lit::Str(format),
+                        ..
+                    }))) if path.is_ident("short") => Some(Technique::WithFormat(format.clone())),
                     Some(NestedMeta::Meta(Meta::Path(path)))
                         if path.is_ident("doc_comments") || path.is_ident("docs") =>
                     {
                         Some(Technique::DocComments(String::new()))
                     }
+                    Some(NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                        path,
+                        lit: Lit::Str(method),
+                        ..
+                    }))) if path.is_ident("fmt") => {
+                        Some(Technique::FromWriteMethod(method.parse()?))
+                    }
                     Some(NestedMeta::Meta(Meta::Path(path))) if path.is_ident("inner") => {
                         Some(Technique::Inner)
                     }
-                    Some(NestedMeta::Meta(Meta::Path(path))) if path.is_ident("lowercase") => {
-                        Some(Technique::Lowercase(String::new()))
-                    }
-                    Some(NestedMeta::Meta(Meta::Path(path))) if path.is_ident("uppercase") => {
-                        Some(Technique::Uppercase(String::new()))
+                    Some(NestedMeta::Meta(Meta::Path(path)))
+                        if path.get_ident().and_then(CaseStyle::from_ident).is_some() =>
+                    {
+                        let style = path
+                            .get_ident()
+                            .and_then(CaseStyle::from_ident)
+                            .expect("we just checked that the style is known");
+                        Some(Technique::Case(style, String::new()))
                     }
                     Some(NestedMeta::Meta(Meta::Path(path))) => Some(
                         FormattingTrait::from_path(path, list.span())?
                             .map_or(Technique::FromMethod(path.clone()), Technique::FromTrait),
                     ),
                     Some(_) => return Err(attr_err!(span, "argument must be a string literal")),
-                    None => return Err(attr_err!(span, "argument is required")),
-                };
-                res = match iter.next() {
-                    Some(NestedMeta::Meta(Meta::NameValue(MetaNameValue {
-                        path,
-                        lit: Lit::Str(alt),
-                        ..
-                    }))) if Some("alt".to_string()) == path.get_ident().map(Ident::to_string) => {
-                        if iter.count() > 0 {
-                            return Err(attr_err!(span, "excessive arguments"));
-                        }
-                        match res {
-                            Some(Technique::WithFormat(fmt, _)) => {
-                                Some(Technique::WithFormat(fmt, Some(alt.clone())))
-                            }
-                            _ => {
-                                return Err(attr_err!(
-                                    span,
-                                    "alternative formatting can be given only if \
-                                 the first argument is a format string"
-                                ))
-                            }
-                        }
+                    None => {
+                        return Err(attr_err!(
+                            span,
+                            "default representation must be given in addition to the \
+                             alternative formats"
+                        ))
                     }
-                    None => res,
-                    _ => return Err(attr_err!(span, "unrecognizable second argument")),
                 };
+                if iter.next().is_some() {
+                    return Err(attr_err!(span, "too many arguments"));
+                }
                 res
             }
             Some(Meta::NameValue(MetaNameValue {
                 lit: Lit::Str(format),
                 ..
-            })) => Some(Technique::WithFormat(format, None)),
+            })) => Some(Technique::WithFormat(format)),
             Some(_) => return Err(attr_err!(span, "argument must be a string literal")),
             None => None,
         };
@@ -200,9 +410,6 @@ impl Technique {
         if let Some(r) = res.as_mut() {
             r.apply_docs(attrs)
         }
-        if let Some(r) = res.as_mut() {
-            r.fix_fmt()
-        };
 
         Ok(res)
     }
@@ -210,101 +417,46 @@ impl Technique {
     pub fn to_fmt(&self, alt: bool) -> TokenStream2 {
         match self {
             Technique::FromTrait(fmt) => fmt.to_fmt(alt),
-            Technique::FromMethod(_) => quote! { "{}" },
-            Technique::WithFormat(fmt, fmt_alt) => {
-                if alt && fmt_alt.is_some() {
-                    let alt = fmt_alt
-                        .as_ref()
-                        .expect("we just checked that there are data");
-                    quote! {#alt}
-                } else {
-                    quote! {#fmt}
-                }
-            }
+            Technique::FromMethod(_) | Technique::FromWriteMethod(_) => quote! { "{}" },
+            Technique::WithFormat(fmt) => quote! { #fmt },
             Technique::DocComments(doc) => quote! { #doc },
             Technique::Inner => {
                 if alt {
-                    quote! { "{_0:#}" }
+                    quote! { "{0:#}" }
                 } else {
-                    quote! { "{_0}" }
+                    quote! { "{0}" }
                 }
             }
-            Technique::Lowercase(fields_fmt) => quote! { #fields_fmt },
-            Technique::Uppercase(fields_fmt) => quote! { #fields_fmt },
+            Technique::Case(_, fields_fmt) => quote! { #fields_fmt },
         }
     }
 
-    #[allow(clippy::unnecessary_unwrap)]
-    pub fn into_token_stream2(self, fields: &Fields, span: Span, alt: bool) -> TokenStream2 {
+    /// Returns format string used by the technique, if any. Format strings
+    /// which are not given by the user get the provided span.
+    pub fn format(&self, span: Span) -> Option<LitStr> {
         match self {
-            Technique::FromTrait(fmt) => fmt.into_token_stream2(span),
-            Technique::FromMethod(path) => quote_spanned! { span =>
-                f.write_str(& #path (self))
-            },
-            Technique::WithFormat(fmt, fmt_alt) => {
-                let format = if alt && fmt_alt.is_some() {
-                    let alt = fmt_alt.expect("we just checked that there are data");
-                    quote_spanned! { span => #alt }
-                } else {
-                    quote_spanned! { span => #fmt }
-                };
-                Self::impl_format(fields, &format, span)
-            }
-            Technique::DocComments(doc) => {
-                let format = quote_spanned! { span => #doc };
-                Self::impl_format(fields, &format, span)
-            }
-            Technique::Inner => {
-                let format = if alt {
-                    quote_spanned! { span => "{_0:#}" }
-                } else {
-                    quote_spanned! { span => "{_0}" }
-                };
-                Self::impl_format(fields, &format, span)
-            }
-            Technique::Lowercase(fields_fmt) => {
-                let format = quote_spanned! { span => #fields_fmt };
-                Self::impl_format(fields, &format, span)
-            }
-            Technique::Uppercase(fields_fmt) => {
-                let format = quote_spanned! { span => #fields_fmt };
-                Self::impl_format(fields, &format, span)
+            Technique::FromTrait(_) | Technique::FromMethod(_) | Technique::FromWriteMethod(_) => {
+                None
             }
+            Technique::WithFormat(fmt) => Some(fmt.clone()),
+            Technique::DocComments(doc) => Some(LitStr::new(doc, span)),
+            Technique::Inner => Some(LitStr::new("{0}", span)),
+            Technique::Case(_, fields_fmt) => Some(LitStr::new(fields_fmt, span)),
         }
     }
 
-    fn impl_format(fields: &Fields, format: &TokenStream2, span: Span) -> TokenStream2 {
-        match fields {
-            // Format string
-            Fields::Named(fields) => {
-                let idents = fields
-                    .named
-                    .iter()
-                    .map(|f| f.ident.as_ref().unwrap())
-                    .collect::<Vec<_>>();
-                quote_spanned! { span =>
-                    write!(f, #format, #( #idents = self.#idents, )* )
-                }
-            }
-            Fields::Unnamed(fields) => {
-                let idents = (0..fields.unnamed.len())
-                    .map(|i| Ident::new(&format!("_{}", i), span))
-                    .collect::<Vec<_>>();
-                let selves = (0..fields.unnamed.len())
-                    .map(|i| {
-                        let index = Index::from(i);
-                        quote_spanned! { span => self.#index }
-                    })
-                    .collect::<Vec<_>>();
-                quote_spanned! { span =>
-                    write!(f, #format, #( #idents = #selves, )* )
-                }
-            }
-            Fields::Unit => {
-                quote_spanned! { span =>
-                    f.write_str(#format)
-                }
-            }
+    /// Returns code writing to the formatter for the techniques which do not
+    /// use format strings
+    pub fn to_code(&self, span: Span) -> Option<TokenStream2> {
+        match self {
+            Technique::FromTrait(fmt) => Some(fmt.into_token_stream2(span)),
+            Technique::FromMethod(path) => Some(quote_spanned! { span =>
+                ::core::fmt::Display::fmt(&#path(self), f)
+            }),
+            Technique::FromWriteMethod(path) => Some(quote_spanned! { span =>
+                #path(self, f)
+            }),
+            _ => None,
         }
     }
 
@@ -328,73 +480,581 @@ impl Technique {
         }
     }
 
-    fn apply_case(&mut self, type_str: &str, fields: &Fields) {
+    fn apply_case(&mut self, type_str: &str, fields: &Fields) -> Result<()> {
         let (type_str_cased, fields_fmt) = match self {
-            Technique::Lowercase(ref mut f) => (type_str.to_lowercase(), f),
-            Technique::Uppercase(ref mut f) => (type_str.to_uppercase(), f),
+            Technique::Case(style, ref mut f) => (style.apply(type_str), f),
             _ => unreachable!(),
         };
+        let shown = FieldFmt::with_fields(fields)?
+            .into_iter()
+            .filter(|field| !field.attr.skip)
+            .map(|field| field.name);
         *fields_fmt = match fields {
-            Fields::Named(f) => {
-                let idents = f
-                    .named
-                    .iter()
-                    .map(|f| f.ident.as_ref().unwrap())
-                    .collect::<Vec<_>>();
-                let inner = idents
-                    .iter()
-                    .map(|ident| format!("{}: {{{0}}}", ident.to_string()))
+            Fields::Named(_) => {
+                let inner = shown
+                    .map(|ident| format!("{}: {{{0}}}", ident))
                     .collect::<Vec<_>>()
                     .join(", ");
                 format!("{} {{{{ {} }}}}", type_str_cased, inner)
             }
-            Fields::Unnamed(f) => {
-                let inner = (0..f.unnamed.len())
-                    .map(|i| format!("{{_{}}}", i))
+            Fields::Unnamed(_) => {
+                let inner = shown
+                    .map(|ident| format!("{{{}}}", ident))
                     .collect::<Vec<_>>()
                     .join(", ");
                 format!("{}({})", type_str_cased, inner)
             }
             Fields::Unit => type_str_cased,
         };
+        Ok(())
+    }
+}
+
+/// Alternative representations selected by the formatter flags
+#[derive(Clone, Default)]
+pub(crate) struct Alternates {
+    /// Format used when precision is given, like in `{:.1}`
+    pub verbose: Option<LitStr>,
+    /// Format used in the alternate mode, `{:#}`
+    pub alt: Option<LitStr>,
+    /// Format used with `+` flag, `{:+}`
+    pub plus: Option<LitStr>,
+}
+
+impl Alternates {
+    /// Argument names for the alternative formats; `long` is synonym for
+    /// `alt`
+    const NAMES: [&'static str; 4] = ["verbose", "alt", "long", "plus"];
+
+    fn is_alternate(nested: &NestedMeta) -> bool {
+        match nested {
+            NestedMeta::Meta(Meta::NameValue(MetaNameValue { path, .. })) => {
+                Self::NAMES.iter().any(|name| path.is_ident(name))
+            }
+            _ => false,
+        }
+    }
+
+    pub fn from_attrs<'a>(attrs: impl IntoIterator<Item = &'a Attribute>) -> Result<Self> {
+        let mut res = Alternates::default();
+        let list = match attrs
+            .into_iter()
+            .find(|attr| attr.path.is_ident(NAME))
+            .map(parse_meta)
+            .transpose()?
+        {
+            Some(Meta::List(list)) => list,
+            _ => return Ok(res),
+        };
+        for nested in list
+            .nested
+            .iter()
+            .filter(|nested| Self::is_alternate(nested))
+        {
+            if let NestedMeta::Meta(Meta::NameValue(MetaNameValue { path, lit, .. })) = nested {
+                let fmt = match lit {
+                    Lit::Str(fmt) => fmt.clone(),
+                    _ => {
+                        return Err(attr_err!(
+                            lit,
+                            "alternative format must be a string literal"
+                        ))
+                    }
+                };
+                let slot = if path.is_ident("verbose") {
+                    &mut res.verbose
+                } else if path.is_ident("plus") {
+                    &mut res.plus
+                } else {
+                    &mut res.alt
+                };
+                if slot.is_some() {
+                    return Err(attr_err!(path, "repeated alternative format"));
+                }
+                *slot = Some(fmt);
+            }
+        }
+        Ok(res)
+    }
+
+    /// Adds alternative formats implied by the technique: `inner` uses
+    /// alternate formatting of the inner type
+    pub fn with_technique(mut self, technique: Option<&Technique>) -> Self {
+        if let (Some(Technique::Inner), None) = (technique, &self.alt) {
+            self.alt = Some(LitStr::new("{0:#}", Span::call_site()));
+        }
+        self
+    }
+
+    /// Returns alternative formats in the order of their priority together
+    /// with the conditions selecting them
+    pub fn selectors(&self) -> Vec<(TokenStream2, &LitStr)> {
+        vec![
+            (quote! { f.precision().is_some() }, &self.verbose),
+            (quote! { f.alternate() }, &self.alt),
+            (quote! { f.sign_plus() }, &self.plus),
+        ]
+        .into_iter()
+        .filter_map(|(cond, fmt)| fmt.as_ref().map(|fmt| (cond, fmt)))
+        .collect()
+    }
+}
+
+/// Field-level `#[display(...)]` parameters
+#[derive(Clone, Default)]
+pub(crate) struct FieldAttr {
+    /// Field must not be displayed
+    pub skip: bool,
+    /// Function converting a reference to the field value into a type which
+    /// is used for formatting
+    pub with: Option<Path>,
+    /// Formatting trait used for the field when the format string does not
+    /// specify one
+    pub fmt_trait: Option<FormattingTrait>,
+}
+
+enum FieldArg {
+    Skip,
+    With(Path),
+    Trait(FormattingTrait),
+}
+
+impl Parse for FieldArg {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let path: Path = input.parse()?;
+        if path.is_ident("skip") {
+            return Ok(FieldArg::Skip);
+        }
+        if path.is_ident("with") {
+            input.parse::<Token![=]>()?;
+            return if input.peek(LitStr) {
+                input.parse::<LitStr>()?.parse().map(FieldArg::With)
+            } else {
+                input.parse().map(FieldArg::With)
+            };
+        }
+        FormattingTrait::from_path(&path, path.span())?
+            .map(FieldArg::Trait)
+            .ok_or_else(|| attr_err!(path, "unrecognized field-level argument"))
+    }
+}
+
+impl FieldAttr {
+    pub fn from_attrs(attrs: &[Attribute]) -> Result<Self> {
+        let mut res = FieldAttr::default();
+        let mut span = None;
+        for attr in attrs.iter().filter(|attr| attr.path.is_ident(NAME)) {
+            span = Some(attr.span());
+            let args = attr.parse_args_with(Punctuated::<FieldArg, Token![,]>::parse_terminated)?;
+            for arg in args {
+                match arg {
+                    FieldArg::Skip => res.skip = true,
+                    FieldArg::With(path) if res.with.is_none() => res.with = Some(path),
+                    FieldArg::Trait(fmt_trait) if res.fmt_trait.is_none() => {
+                        res.fmt_trait = Some(fmt_trait)
+                    }
+                    _ => return Err(attr_err!(attr, "repeated field-level argument")),
+                }
+            }
+        }
+        if res.skip && (res.with.is_some() || res.fmt_trait.is_some()) {
+            return Err(attr_err!(
+                span.unwrap_or_else(Span::call_site),
+                NAME,
+                "skipped field can't have other display arguments",
+                EXAMPLE
+            ));
+        }
+        Ok(res)
     }
+}
+
+/// Struct or enum variant field which may be referenced from a format string
+pub(crate) struct FieldFmt {
+    /// Name used for the field inside format strings: either field identifier
+    /// or `_N` for the tuple fields
+    pub name: Ident,
+    pub member: Member,
+    pub ty: Type,
+    pub attr: FieldAttr,
+}
 
-    fn fix_fmt(&mut self) {
-        fn fix(s: &str) -> String {
-            s.replace("{0", "{_0")
-                .replace("{1", "{_1")
-                .replace("{2", "{_2")
-                .replace("{3", "{_3")
-                .replace("{4", "{_4")
-                .replace("{5", "{_5")
-                .replace("{6", "{_6")
-                .replace("{7", "{_7")
-                .replace("{8", "{_8")
-                .replace("{9", "{_9")
+impl FieldFmt {
+    pub fn with_fields(fields: &Fields) -> Result<Vec<Self>> {
+        fields
+            .iter()
+            .enumerate()
+            .map(|(index, field)| {
+                Ok(FieldFmt {
+                    name: field
+                        .ident
+                        .clone()
+                        .unwrap_or_else(|| Ident::new(&format!("_{}", index), field.span())),
+                    member: field.ident.clone().map(Member::Named).unwrap_or_else(|| {
+                        Member::Unnamed(Index {
+                            index: index as u32,
+                            span: field.span(),
+                        })
+                    }),
+                    ty: field.ty.clone(),
+                    attr: FieldAttr::from_attrs(&field.attrs)?,
+                })
+            })
+            .collect()
+    }
+
+    /// Finds field referenced by the format argument: either by position, by
+    /// name or by `_N` alias of the field name
+    pub fn find(fields: &[Self], arg: &Arg) -> Option<usize> {
+        match arg {
+            Arg::Index(index) => Some(*index).filter(|index| *index < fields.len()),
+            Arg::Name(name) => fields
+                .iter()
+                .position(|field| field.name == name)
+                .or_else(|| {
+                    name.strip_prefix('_')
+                        .and_then(|index| index.parse::<usize>().ok())
+                        .filter(|index| *index < fields.len())
+                }),
+            Arg::Expr(_) => None,
         }
+    }
+
+    fn value(&self, bindings: bool) -> TokenStream2 {
+        let name = &self.name;
+        let member = &self.member;
+        let value = if bindings {
+            quote! { #name }
+        } else {
+            quote! { self.#member }
+        };
+        match self.attr.with {
+            Some(ref path) => quote! { #path(&#value) },
+            None => value,
+        }
+    }
+}
+
+/// Value of a named argument passed to `write!` macro
+enum FormatArg {
+    /// Field with the given index
+    Field(usize),
+    /// Expression given inside the format string
+    Expr(Box<Expr>),
+}
+
+/// Format string prepared for `write!` macro: all field references are
+/// converted into named arguments, expressions are moved out of the string
+struct Format {
+    string: String,
+    args: Vec<(Ident, FormatArg)>,
+    /// Fields formatted by the placeholders together with the formatting
+    /// traits they use; `None` stands for `Display`
+    traits: Vec<(usize, Option<FormattingTrait>)>,
+}
+
+impl Format {
+    pub fn with(fmt: &LitStr, fields: &[FieldFmt]) -> Result<Self> {
+        let mut res = Format {
+            string: String::new(),
+            args: vec![],
+            traits: vec![],
+        };
+        for piece in parse_format(fmt)? {
+            let Placeholder {
+                arg,
+                mut spec,
+                range,
+            } = match piece {
+                Piece::Literal(literal) => {
+                    res.string.push_str(&escape(&literal));
+                    continue;
+                }
+                Piece::Placeholder(placeholder) => placeholder,
+            };
+            let location = Location::with(fmt, range);
 
-        if let Technique::WithFormat(fmt, x) = self {
-            *self = Technique::WithFormat(
-                LitStr::new(&fix(&fmt.value()), Span::call_site()),
-                x.clone(),
-            );
+            let name = res.arg_name(&arg, fields, &location)?;
+            if let Some(index) = FieldFmt::find(fields, &arg) {
+                let field = &fields[index];
+                if let (Some(fmt_trait), true) = (field.attr.fmt_trait, spec.ty.is_empty()) {
+                    spec.ty = fmt_trait.spec_type().to_owned();
+                }
+                // Values converted with `with` function have their own types
+                if field.attr.with.is_none() {
+                    res.traits
+                        .push((index, FormattingTrait::from_spec_type(&spec.ty)));
+                }
+            }
+            let counts = spec
+                .args()
+                .map(|arg| Ok((arg.clone(), res.arg_name(arg, fields, &location)?)))
+                .collect::<Result<Vec<_>>>()?;
+            let spec = spec.to_string_with(|arg| {
+                counts
+                    .iter()
+                    .find(|(count, _)| count == arg)
+                    .map(|(_, name)| name.clone())
+                    .expect("all spec arguments are resolved")
+            });
+
+            res.string.push('{');
+            res.string.push_str(&name);
+            if !spec.is_empty() {
+                res.string.push(':');
+                res.string.push_str(&spec);
+            }
+            res.string.push('}');
         }
-        if let Technique::WithFormat(x, Some(fmt)) = self {
-            *self = Technique::WithFormat(
-                x.clone(),
-                Some(LitStr::new(&fix(&fmt.value()), Span::call_site())),
-            );
+        Ok(res)
+    }
+
+    /// Returns name under which the argument is passed to the `write!`
+    /// macro, adding it to the list of the named arguments if required
+    fn arg_name(&mut self, arg: &Arg, fields: &[FieldFmt], location: &Location) -> Result<String> {
+        let name = match (arg, FieldFmt::find(fields, arg)) {
+            (_, Some(index)) => {
+                let field = &fields[index];
+                if field.attr.skip {
+                    return Err(location.error(format!(
+                        "field `{}` is marked with `#[display(skip)]` and can't be used \
+                             in format string",
+                        field.name
+                    )));
+                }
+                let name = match arg {
+                    Arg::Name(name) if field.name != name => Ident::new(name, location.span),
+                    _ => field.name.clone(),
+                };
+                if !self.args.iter().any(|(ident, _)| *ident == name) {
+                    self.args.push((name.clone(), FormatArg::Field(index)));
+                }
+                name
+            }
+            (Arg::Index(index), None) => {
+                return Err(location.error(format!(
+                    "format string references field at position {}, but there are only \
+                         {} fields",
+                    index,
+                    fields.len()
+                )))
+            }
+            // Other identifiers may refer to the constants in scope
+            (Arg::Name(name), None) => return Ok(name.clone()),
+            (Arg::Expr(expr), None) => {
+                let expr = syn::parse_str::<Expr>(expr).map_err(|err| {
+                    location.error(format!(
+                        "invalid expression `{}` in format string: {}",
+                        expr, err
+                    ))
+                })?;
+                let name = Ident::new(&format!("__display_expr{}", self.args.len()), location.span);
+                self.args
+                    .push((name.clone(), FormatArg::Expr(Box::new(expr))));
+                name
+            }
+        };
+        Ok(name.to_string())
+    }
+
+    pub fn has_exprs(&self) -> bool {
+        self.args
+            .iter()
+            .any(|(_, arg)| matches!(arg, FormatArg::Expr(_)))
+    }
+
+    pub fn fields(&self) -> impl Iterator<Item = usize> + '_ {
+        self.args.iter().filter_map(|(_, arg)| match arg {
+            FormatArg::Field(index) => Some(*index),
+            FormatArg::Expr(_) => None,
+        })
+    }
+
+    /// Generates `write!` macro call. If `bindings` is set, fields are
+    /// accessed via local variables named after the fields; otherwise via
+    /// `self`.
+    pub fn to_write(&self, fields: &[FieldFmt], bindings: bool, span: Span) -> TokenStream2 {
+        let string = &self.string;
+        let args = self.args.iter().map(|(name, arg)| match arg {
+            FormatArg::Field(index) => {
+                let value = fields[*index].value(bindings);
+                quote! { #name = #value }
+            }
+            FormatArg::Expr(expr) => quote! { #name = #expr },
+        });
+        quote_spanned! { span =>
+            write!(f, #string, #( #args, )*)
         }
-        if let Technique::DocComments(fmt) = self {
-            *self = Technique::DocComments(fix(fmt))
+    }
+}
+
+/// Representation of a struct or an enum variant
+enum Body {
+    /// Code writing to the formatter
+    Code(TokenStream2),
+    /// String written to the formatter as is
+    Literal(String),
+    /// Format string for the fields
+    Format(Format),
+}
+
+impl Body {
+    fn to_code(&self, fields: &[FieldFmt], bindings: bool, span: Span) -> TokenStream2 {
+        match self {
+            Body::Code(code) => code.clone(),
+            Body::Literal(lit) => quote_spanned! { span => f.write_str(#lit) },
+            Body::Format(format) => format.to_write(fields, bindings, span),
         }
     }
 }
 
-fn has_formatters(ident: impl ToString, s: &str) -> bool {
-    let m1 = format!("{}{}:", '{', ident.to_string());
-    let m2 = format!("{}{}{}", '{', ident.to_string(), '}');
-    s.contains(&m1) || s.contains(&m2)
+/// Display of the struct or enum variant fields with the default technique
+/// and alternative formats selected by the formatter flags
+struct FieldsDisplay {
+    fields: Vec<FieldFmt>,
+    default: Body,
+    alternates: Vec<(TokenStream2, Body)>,
+    /// Formatting trait of the whole type used by the default technique
+    self_trait: Option<FormattingTrait>,
+}
+
+impl FieldsDisplay {
+    pub fn with(
+        technique: &Technique,
+        alternates: &Alternates,
+        fields: &Fields,
+        span: Span,
+    ) -> Result<Self> {
+        let field_fmts = FieldFmt::with_fields(fields)?;
+        let body = |fmt: &LitStr, raw: bool| -> Result<Body> {
+            // Unit types and variants write doc comments and strings without
+            // placeholders as is
+            if let Fields::Unit = fields {
+                if raw {
+                    return Ok(Body::Literal(fmt.value()));
+                }
+                if let Some(literal) = format::literal(fmt)? {
+                    return Ok(Body::Literal(literal));
+                }
+            }
+            Format::with(fmt, &field_fmts).map(Body::Format)
+        };
+        let default = match technique.to_code(span) {
+            Some(code) => Body::Code(code),
+            None => body(
+                &technique
+                    .format(span)
+                    .expect("all techniques either write code or use format string"),
+                matches!(technique, Technique::DocComments(_)),
+            )?,
+        };
+        let alternates = alternates
+            .selectors()
+            .into_iter()
+            .map(|(cond, fmt)| Ok((cond, body(fmt, false)?)))
+            .collect::<Result<_>>()?;
+        let self_trait = match technique {
+            Technique::FromTrait(fmt_trait) => Some(*fmt_trait),
+            _ => None,
+        };
+        Ok(FieldsDisplay {
+            fields: field_fmts,
+            default,
+            alternates,
+            self_trait,
+        })
+    }
+
+    /// Adds bounds for the generic types of the fields which are formatted
+    pub fn add_bounds(&self, bounds: &mut Bounds) {
+        if let Some(fmt_trait) = self.self_trait {
+            bounds.add_self(FormattingTrait::path_or_display(Some(fmt_trait)));
+        }
+        for body in self.bodies() {
+            if let Body::Format(format) = body {
+                for (index, fmt_trait) in &format.traits {
+                    bounds.add(
+                        &self.fields[*index].ty,
+                        FormattingTrait::path_or_display(*fmt_trait),
+                    );
+                }
+            }
+        }
+    }
+
+    fn bodies(&self) -> impl Iterator<Item = &Body> {
+        self.alternates
+            .iter()
+            .map(|(_, body)| body)
+            .chain(Some(&self.default))
+    }
+
+    pub fn has_exprs(&self) -> bool {
+        self.bodies()
+            .any(|body| matches!(body, Body::Format(format) if format.has_exprs()))
+    }
+
+    /// Indexes of the fields which has to be accessible to the `write!`
+    /// macro: all fields if the format strings use expressions, or only the
+    /// fields referenced from the format strings otherwise
+    pub fn used_fields(&self) -> BTreeSet<usize> {
+        if self.has_exprs() {
+            return (0..self.fields.len()).collect();
+        }
+        self.bodies()
+            .filter_map(|body| match body {
+                Body::Format(format) => Some(format.fields()),
+                _ => None,
+            })
+            .flatten()
+            .collect()
+    }
+
+    /// Generates pattern binding fields used by the formats to local
+    /// variables named after the fields
+    pub fn bind_pattern(&self, fields: &Fields) -> TokenStream2 {
+        let used = self.used_fields();
+        let names = self
+            .fields
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| used.contains(index))
+            .map(|(_, field)| &field.name);
+        match fields {
+            Fields::Named(_) => quote! { { #( #names, )* .. } },
+            Fields::Unnamed(_) => {
+                let names = self.fields.iter().enumerate().map(|(index, field)| {
+                    if used.contains(&index) {
+                        let name = &field.name;
+                        quote! { #name }
+                    } else {
+                        quote! { _ }
+                    }
+                });
+                quote! { ( #( #names, )* ) }
+            }
+            Fields::Unit => quote! {},
+        }
+    }
+
+    pub fn to_body(&self, bindings: bool, span: Span) -> TokenStream2 {
+        let default = self.default.to_code(&self.fields, bindings, span);
+        if self.alternates.is_empty() {
+            return default;
+        }
+        let conds = self.alternates.iter().map(|(cond, _)| cond);
+        let alternates = self
+            .alternates
+            .iter()
+            .map(|(_, body)| body.to_code(&self.fields, bindings, span));
+        quote_spanned! { span =>
+            #( if #conds {
+                #alternates
+            } else )* {
+                #default
+            }
+        }
+    }
 }
 
 pub(crate) fn inner(input: DeriveInput) -> Result<TokenStream2> {
@@ -405,10 +1065,8 @@ pub(crate) fn inner(input: DeriveInput) -> Result<TokenStream2> {
     }
 }
 
-fn inner_struct(input: &DeriveInput, data: &DataStruct) -> Result<TokenStream2> {
-    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
-    let ident_name = &input.ident;
-
+/// Resolves technique used for displaying the structure
+pub(crate) fn struct_technique(input: &DeriveInput, data: &DataStruct) -> Result<Technique> {
     let mut technique = Technique::from_attrs(&input.attrs, input.span())?.ok_or_else(|| {
         Error::new(
             input.span(),
@@ -419,111 +1077,152 @@ fn inner_struct(input: &DeriveInput, data: &DataStruct) -> Result<TokenStream2>
         )
     })?;
     technique.apply_docs(&input.attrs);
+    if let Technique::Case(..) = technique {
+        technique.apply_case(&input.ident.to_string(), &data.fields)?;
+    }
+    Ok(technique)
+}
 
-    let tokens_fmt = technique.to_fmt(false);
-    let tokens_alt = technique.to_fmt(true);
-    let str_fmt = tokens_fmt.to_string();
-    let str_alt = tokens_alt.to_string();
+/// Techniques used for displaying the enum
+pub(crate) struct EnumTechniques {
+    /// Technique given for the whole enum
+    pub global: Option<Technique>,
+    /// Alternative formats given for the whole enum
+    pub global_alternates: Alternates,
+    /// Whether the global technique is applied to the enum as a whole,
+    /// without matching on its variants
+    pub use_global: bool,
+    /// Techniques used for each of the enum variants
+    pub variants: Vec<Option<Technique>>,
+    /// Alternative formats used for each of the enum variants
+    pub variant_alternates: Vec<Alternates>,
+}
 
-    let display = match (&data.fields, &technique) {
-        (_, Technique::FromTrait(_)) | (_, Technique::FromMethod(_)) => technique
-            .clone()
-            .into_token_stream2(&data.fields, input.span(), false),
-        (Fields::Named(fields), Technique::Inner) => {
-            if fields.named.len() != 1 {
+impl EnumTechniques {
+    pub fn with(input: &DeriveInput, data: &DataEnum) -> Result<Self> {
+        let global = Technique::from_attrs(&input.attrs, input.span())?;
+        let global_alternates = Alternates::from_attrs(&input.attrs)?;
+        // Ancient rust versions do not known about `matches!` macro
+        #[allow(clippy::match_like_matches_macro)]
+        let mut use_global = match global {
+            Some(Technique::Inner) | Some(Technique::Case(..)) => false,
+            _ => true,
+        };
+
+        let mut variants = Vec::with_capacity(data.variants.len());
+        let mut variant_alternates = Vec::with_capacity(data.variants.len());
+        for v in &data.variants {
+            let type_str = format!("{}", v.ident);
+
+            if custom_bounds(&v.attrs)?.is_some() {
                 return Err(attr_err!(
-                    fields.span(),
-                    "display(inner) requires only single field in the structure"
+                    v,
+                    "bounds can be given only for the whole type, not for a variant"
                 ));
             }
-            let field = fields
-                .named
-                .first()
-                .expect("we just checked that there is a single field")
-                .ident
-                .as_ref()
-                .expect("named fields always have ident with the name");
-            quote_spanned! { field.span() =>
-                write!(f, #tokens_fmt, _0 = self.#field)
-            }
-        }
-        (Fields::Named(fields), _) => {
-            let f = fields.named.iter().map(|f| f.ident.as_ref().unwrap());
-            let idents = f
-                .clone()
-                .filter(|ident| has_formatters(ident, &str_fmt))
-                .collect::<Vec<_>>();
-            if str_fmt == str_alt {
-                quote_spanned! { fields.span() =>
-                    write!(f, #tokens_fmt, #( #idents = self.#idents, )*)
-                }
+            let mut local = Technique::from_attrs(&v.attrs, v.span())?;
+            let mut parent = global.clone();
+            let current = local.as_mut().or(parent.as_mut());
+            let mut current = current
+                .map(|r| {
+                    r.apply_docs(&v.attrs);
+                    r
+                })
+                .cloned();
+
+            let alternates = if local.is_some() {
+                use_global = false;
+                Alternates::from_attrs(&v.attrs)?
             } else {
-                let idents_alt = f
-                    .filter(|ident| has_formatters(ident, &str_alt))
-                    .collect::<Vec<_>>();
-                if str_fmt != str_alt {
-                    quote_spanned! { fields.span() =>
-                        if !f.alternate() {
-                            write!(f, #tokens_fmt, #( #idents = self.#idents, )*)
-                        } else {
-                            write!(f, #tokens_alt, #( #idents_alt = self.#idents_alt, )*)
+                global_alternates.clone()
+            };
+
+            if let Some(Technique::DocComments(_)) | Some(Technique::Case(..)) = current {
+                use_global = false;
+                if let Some(t) = current.as_mut() {
+                    match t {
+                        Technique::DocComments(_) => {
+                            *t = Technique::DocComments(String::new());
+                            t.apply_docs(&v.attrs);
                         }
-                    }
-                } else {
-                    quote_spanned! { fields.span() =>
-                        write!(f, #tokens_fmt, #( #idents = self.#idents, )*)
-                    }
-                }
-            }
-        }
-        (Fields::Unnamed(fields), _) => {
-            let f = (0..fields.unnamed.len()).map(Index::from);
-            let idents = f
-                .clone()
-                .filter(|ident| has_formatters(format!("_{}", ident.index), &str_fmt));
-            let nums = idents
-                .clone()
-                .map(|ident| Ident::new(&format!("_{}", ident.index), fields.span()))
-                .collect::<Vec<_>>();
-            let idents = idents.collect::<Vec<_>>();
-            if str_fmt == str_alt {
-                quote_spanned! { fields.span() =>
-                    write!(f, #tokens_fmt, #( #nums = self.#idents, )*)
-                }
-            } else {
-                let idents_alt =
-                    f.filter(|ident| has_formatters(format!("_{}", ident.index), &str_alt));
-                let nums_alt = idents_alt
-                    .clone()
-                    .map(|ident| Ident::new(&format!("_{}", ident.index), fields.span()))
-                    .collect::<Vec<_>>();
-                let idents_alt = idents_alt.collect::<Vec<_>>();
-                if str_fmt != str_alt {
-                    quote_spanned! { fields.span() =>
-                        if !f.alternate() {
-                            write!(f, #tokens_fmt, #( #nums = self.#idents, )*)
-                        } else {
-                            write!(f, #tokens_alt, #( #nums_alt = self.#idents_alt, )*)
+                        Technique::Case(style, _) => {
+                            *t = Technique::Case(*style, String::new());
+                            t.apply_case(&type_str, &v.fields)?;
                         }
-                    }
-                } else {
-                    quote_spanned! { fields.span() =>
-                        write!(f, #tokens_fmt, #( #nums = self.#idents, )*)
+                        _ => unreachable!(),
                     }
                 }
             }
+            variant_alternates.push(alternates.with_technique(current.as_ref()));
+            variants.push(current);
         }
-        (Fields::Unit, _) => {
-            if str_fmt == str_alt {
-                quote_spanned! { data.fields.span() =>
-                    f.write_str(#tokens_fmt)
-                }
-            } else {
-                quote_spanned! { data.fields.span() =>
-                    f.write_str(if !f.alternate() { #tokens_fmt } else { #tokens_alt })
-                }
-            }
+
+        Ok(EnumTechniques {
+            global,
+            global_alternates,
+            use_global,
+            variants,
+            variant_alternates,
+        })
+    }
+}
+
+/// Parses bounds for the generic parameters given with
+/// `#[display(bound = "...")]`
+fn custom_bounds(attrs: &[Attribute]) -> Result<Option<Vec<WherePredicate>>> {
+    let list = match attrs
+        .iter()
+        .find(|attr| attr.path.is_ident(NAME))
+        .map(parse_meta)
+        .transpose()?
+    {
+        Some(Meta::List(list)) => list,
+        _ => return Ok(None),
+    };
+    let mut res = None;
+    for bound in list.nested.iter().filter_map(parse_bound) {
+        if res.is_some() {
+            return Err(attr_err!(list, "repeated `bound` argument"));
         }
+        res = Some(bound?);
+    }
+    Ok(res)
+}
+
+fn inner_struct(input: &DeriveInput, data: &DataStruct) -> Result<TokenStream2> {
+    let ident_name = &input.ident;
+
+    let technique = struct_technique(input, data)?;
+    let alternates = Alternates::from_attrs(&input.attrs)?.with_technique(Some(&technique));
+
+    if let (Fields::Named(fields), Technique::Inner) = (&data.fields, &technique) {
+        if fields.named.len() != 1 {
+            return Err(attr_err!(
+                fields.span(),
+                "display(inner) requires only single field in the structure"
+            ));
+        }
+    }
+
+    let span = data.fields.span();
+    let display = FieldsDisplay::with(&technique, &alternates, &data.fields, span)?;
+    let mut bounds = Bounds::with(&input.generics, custom_bounds(&input.attrs)?);
+    display.add_bounds(&mut bounds);
+    let generics = bounds.apply(&input.generics);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let display = if display.has_exprs() {
+        // Expressions may reference fields by their names, so we bind them
+        // all to the local variables
+        let pattern = display.bind_pattern(&data.fields);
+        let body = display.to_body(true, span);
+        quote_spanned! { span =>
+            #[allow(unused_variables)]
+            let Self #pattern = self;
+            #body
+        }
+    } else {
+        display.to_body(false, span)
     };
 
     Ok(quote! {
@@ -536,188 +1235,61 @@ fn inner_struct(input: &DeriveInput, data: &DataStruct) -> Result<TokenStream2>
 }
 
 fn inner_enum(input: &DeriveInput, data: &DataEnum) -> Result<TokenStream2> {
-    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
     let ident_name = &input.ident;
     let mut display = TokenStream2::new();
+    let mut bounds = Bounds::with(&input.generics, custom_bounds(&input.attrs)?);
 
-    let global = Technique::from_attrs(&input.attrs, input.span())?;
-    // Ancient rust versions do not known about `matches!` macro
-    #[allow(clippy::match_like_matches_macro)]
-    let mut use_global = match global {
-        Some(Technique::Inner) | Some(Technique::Lowercase(_)) | Some(Technique::Uppercase(_)) => {
-            false
-        }
-        _ => true,
-    };
+    let EnumTechniques {
+        global,
+        global_alternates,
+        use_global,
+        variants,
+        variant_alternates,
+    } = EnumTechniques::with(input, data)?;
 
-    for v in &data.variants {
+    for ((v, current), alternates) in data.variants.iter().zip(variants).zip(variant_alternates) {
         let type_name = &v.ident;
         let type_str = format!("{}", type_name);
 
-        let mut local = Technique::from_attrs(&v.attrs, v.span())?;
-        let mut parent = global.clone();
-        let current = local.as_mut().or(parent.as_mut());
-        let mut current = current
-            .map(|r| {
-                r.apply_docs(&v.attrs);
-                r
-            })
-            .cloned();
-
-        if local.is_some() {
-            use_global = false;
-        }
-
-        if let Some(Technique::DocComments(_))
-        | Some(Technique::Lowercase(_))
-        | Some(Technique::Uppercase(_)) = current
-        {
-            use_global = false;
-            if let Some(t) = current.as_mut() {
-                match t {
-                    Technique::DocComments(_) => {
-                        *t = Technique::DocComments(String::new());
-                        t.apply_docs(&v.attrs);
-                        t.fix_fmt();
-                    }
-                    Technique::Lowercase(_) => {
-                        *t = Technique::Lowercase(String::new());
-                        t.apply_case(&type_str, &v.fields);
-                        t.fix_fmt();
-                    }
-                    Technique::Uppercase(_) => {
-                        *t = Technique::Uppercase(String::new());
-                        t.apply_case(&type_str, &v.fields);
-                        t.fix_fmt();
-                    }
-                    _ => unreachable!(),
-                }
-            }
-        }
-
-        let tokens_fmt = current.as_ref().map(|t| t.to_fmt(false));
-        let tokens_alt = current.as_ref().map(|t| t.to_fmt(true));
-
-        match (&v.fields, &tokens_fmt, &tokens_alt) {
-            (Fields::Named(_), None, _) => {
+        match (&v.fields, current) {
+            (Fields::Named(_), None) => {
                 display.extend(quote_spanned! { v.span() =>
                     Self::#type_name { .. } => f.write_str(concat!(#type_str, " { .. }")),
                 });
             }
-            (Fields::Unnamed(_), None, _) => {
+            (Fields::Unnamed(_), None) => {
                 display.extend(quote_spanned! { v.span() =>
                     Self::#type_name(..) => f.write_str(concat!(#type_str, "(..)")),
                 });
             }
-            (Fields::Unit, None, _) => {
+            (Fields::Unit, None) | (Fields::Unit, Some(Technique::Inner)) => {
                 display.extend(quote_spanned! { v.span() =>
                     Self::#type_name => f.write_str(#type_str),
                 });
             }
-            (Fields::Named(fields), Some(tokens_fmt), Some(tokens_alt)) => {
-                if let Some(Technique::Inner) = current {
-                    if fields.named.len() != 1 {
-                        return Err(attr_err!(
-                            fields.span(),
-                            "display(inner) requires only single field in the structure"
-                        ));
-                    }
-                    let field = fields
-                        .named
-                        .first()
-                        .expect("we just checked that there is a single field")
-                        .ident
-                        .as_ref()
-                        .expect("named fields always have ident with the name");
-                    display.extend(quote_spanned! { v.span() =>
-                        Self::#type_name { #field, .. } => {
-                            write!(f, #tokens_fmt, _0 = #field)
-                        }
-                    });
-                } else if let Some(Technique::FromTrait(tr)) = current {
-                    let stream =
-                        Technique::FromTrait(tr).into_token_stream2(&v.fields, v.span(), false);
-                    display.extend(quote_spanned! { v.span() =>
-                        Self::#type_name { .. } => {
-                            #stream
-                        }
-                    })
-                } else {
-                    let f = fields.named.iter().map(|f| f.ident.as_ref().unwrap());
-                    let idents = f
-                        .clone()
-                        .filter(|ident| has_formatters(ident, &tokens_fmt.to_string()))
-                        .collect::<Vec<_>>();
-                    let idents_alt = f
-                        .filter(|ident| has_formatters(ident, &tokens_alt.to_string()))
-                        .collect::<Vec<_>>();
-                    if tokens_fmt.to_string() != tokens_alt.to_string() {
-                        display.extend(quote_spanned! { v.span() =>
-                            Self::#type_name { #( #idents, )* .. } if !f.alternate() => {
-                                write!(f, #tokens_fmt, #( #idents = #idents, )*)
-                            },
-                            Self::#type_name { #( #idents, )* .. } => {
-                                write!(f, #tokens_alt, #( #idents_alt = #idents_alt, )*)
-                            },
-                        });
-                    } else {
-                        display.extend(quote_spanned! { v.span() =>
-                            Self::#type_name { #( #idents, )* .. } => {
-                                write!(f, #tokens_fmt, #( #idents = #idents, )*)
-                            },
-                        });
-                    }
-                }
+            (Fields::Named(fields), Some(Technique::Inner)) if fields.named.len() != 1 => {
+                return Err(attr_err!(
+                    fields.span(),
+                    "display(inner) requires only single field in the structure"
+                ));
             }
-            (Fields::Unnamed(fields), Some(tokens_fmt), Some(tokens_alt)) => {
-                if let Some(Technique::FromTrait(tr)) = current {
-                    let stream =
-                        Technique::FromTrait(tr).into_token_stream2(&v.fields, v.span(), false);
-                    display.extend(quote_spanned! { v.span() =>
-                        Self::#type_name(..) => {
-                            #stream
-                        }
-                    })
+            (fields, Some(technique)) => {
+                let variant = FieldsDisplay::with(&technique, &alternates, fields, v.span())?;
+                variant.add_bounds(&mut bounds);
+                let pattern = variant.bind_pattern(fields);
+                let body = variant.to_body(true, v.span());
+                let allow = if variant.has_exprs() {
+                    quote! { #[allow(unused_variables)] }
                 } else {
-                    let f =
-                        (0..fields.unnamed.len()).map(|i| Ident::new(&format!("_{}", i), v.span()));
-                    let idents = f
-                        .clone()
-                        .filter(|ident| has_formatters(ident, &tokens_fmt.to_string()))
-                        .collect::<Vec<_>>();
-                    let idents_alt = f
-                        .filter(|ident| has_formatters(ident, &tokens_alt.to_string()))
-                        .collect::<Vec<_>>();
-                    if tokens_fmt.to_string() != tokens_alt.to_string() {
-                        display.extend(quote_spanned! { v.span() =>
-                            Self::#type_name ( #( #idents, )* .. ) if !f.alternate() => {
-                                write!(f, #tokens_fmt, #( #idents = #idents, )*)
-                            },
-                            Self::#type_name ( #( #idents, )* .. ) => {
-                                write!(f, #tokens_alt, #( #idents_alt = #idents_alt, )*)
-                            },
-                        });
-                    } else {
-                        display.extend(quote_spanned! { v.span() =>
-                            Self::#type_name ( #( #idents, )* .. ) => {
-                                write!(f, #tokens_fmt, #( #idents = #idents, )*)
-                            },
-                        });
+                    quote! {}
+                };
+                display.extend(quote_spanned! { v.span() =>
+                    #allow
+                    Self::#type_name #pattern => {
+                        #body
                     }
-                }
-            }
-            (Fields::Unit, Some(tokens_fmt), Some(tokens_alt)) => {
-                if let Some(Technique::Inner) = current {
-                    display.extend(quote_spanned! { v.span() =>
-                        Self::#type_name => f.write_str(#type_str),
-                    });
-                } else {
-                    display.extend(quote_spanned! { v.span() =>
-                        Self::#type_name => f.write_str(if !f.alternate() { #tokens_fmt } else { #tokens_alt }),
-                    });
-                }
+                });
             }
-            _ => unreachable!(),
         }
     }
 
@@ -728,25 +1300,15 @@ fn inner_enum(input: &DeriveInput, data: &DataEnum) -> Result<TokenStream2> {
             }
         },
         (true, Some(tenchique)) => {
-            let format_str =
-                tenchique
-                    .clone()
-                    .into_token_stream2(&Fields::Unit, input.span(), false);
-            let format_alt = tenchique.into_token_stream2(&Fields::Unit, input.span(), true);
-            if format_str.to_string() != format_alt.to_string() {
-                quote! {
-                    if f.alternate() {
-                        #format_alt
-                    } else {
-                        #format_str
-                    }
-                }
-            } else {
-                quote! { #format_str }
-            }
+            let global =
+                FieldsDisplay::with(&tenchique, &global_alternates, &Fields::Unit, input.span())?;
+            global.add_bounds(&mut bounds);
+            global.to_body(false, input.span())
         }
         _ => unreachable!(),
     };
+    let generics = bounds.apply(&input.generics);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
 
     Ok(quote! {
         impl #impl_generics ::core::fmt::Display for #ident_name #ty_generics #where_clause {
@@ -759,11 +1321,12 @@ fn inner_enum(input: &DeriveInput, data: &DataEnum) -> Result<TokenStream2> {
 }
 
 fn inner_union(input: &DeriveInput, data: &DataUnion) -> Result<TokenStream2> {
-    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
     let ident_name = &input.ident;
     let mut display = vec![];
+    let mut bounds = Bounds::with(&input.generics, custom_bounds(&input.attrs)?);
 
     let global = Technique::from_attrs(&input.attrs, input.span())?;
+    let global_alternates = Alternates::from_attrs(&input.attrs)?;
 
     for field in &data.fields.named {
         let type_name = field
@@ -793,22 +1356,10 @@ fn inner_union(input: &DeriveInput, data: &DataUnion) -> Result<TokenStream2> {
 
     let content = match global {
         Some(tenchique) => {
-            let format_str =
-                tenchique
-                    .clone()
-                    .into_token_stream2(&Fields::Unit, input.span(), false);
-            let format_alt = tenchique.into_token_stream2(&Fields::Unit, input.span(), true);
-            if format_str.to_string() != format_alt.to_string() {
-                quote! {
-                    if f.alternate() {
-                        #format_alt
-                    } else {
-                        #format_str
-                    }
-                }
-            } else {
-                quote! { #format_str }
-            }
+            let global =
+                FieldsDisplay::with(&tenchique, &global_alternates, &Fields::Unit, input.span())?;
+            global.add_bounds(&mut bounds);
+            global.to_body(false, input.span())
         }
         None => quote! {
             let s = match self {
@@ -817,6 +1368,8 @@ fn inner_union(input: &DeriveInput, data: &DataUnion) -> Result<TokenStream2> {
             f.write_str(s)
         },
     };
+    let generics = bounds.apply(&input.generics);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
     Ok(quote! {
         impl #impl_generics ::core::fmt::Display for #ident_name #ty_generics #where_clause {
             #![allow(clippy::if_same_then_else)]
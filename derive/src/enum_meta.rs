@@ -0,0 +1,201 @@
+// Rust language amplification derive library providing multiple generic trait
+// implementations, type wrappers, derive macros and other language enhancements
+//
+// Written in 2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the MIT License
+// along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+use std::collections::HashMap;
+use std::convert::TryInto;
+use std::iter::FromIterator;
+
+use amplify_syn::{ArgValueReq, AttrReq, ListReq, ParametrizedAttr, ValueClass};
+use proc_macro2::TokenStream as TokenStream2;
+use syn::spanned::Spanned;
+use syn::{Data, DeriveInput, Error, Expr, ExprLit, Fields, Lit, LitStr, Result};
+
+use crate::display::CaseStyle;
+use crate::util::get_amplify_crate;
+
+const NAME: &str = "enum_meta";
+
+pub(crate) fn derive(input: DeriveInput) -> Result<TokenStream2> {
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let ident_name = &input.ident;
+    let amplify_crate = get_amplify_crate(&input);
+
+    let data = match input.data {
+        Data::Enum(ref data) => data,
+        _ => {
+            return Err(Error::new_spanned(
+                &input,
+                "EnumMeta can be derived only for enums",
+            ))
+        }
+    };
+
+    if data.variants.is_empty() {
+        return Err(Error::new_spanned(
+            &input,
+            "EnumMeta can't be derived for enums without variants",
+        ));
+    }
+
+    let mut global_param = ParametrizedAttr::with(NAME, &input.attrs)?;
+    global_param.check(AttrReq {
+        path_req: ListReq::Many {
+            whitelist: None,
+            required: false,
+            max_no: Some(1),
+        },
+        ..AttrReq::with(HashMap::new())
+    })?;
+    let case = global_param
+        .paths
+        .first()
+        .map(|path| {
+            path.get_ident()
+                .and_then(CaseStyle::from_ident)
+                .ok_or_else(|| {
+                    Error::new(
+                        path.span(),
+                        format!(
+                            "Attribute `#[{}]`: unknown case style; use one of `lowercase`, \
+                             `UPPERCASE`, `snake_case`, `kebab_case`, `camelCase`, \
+                             `PascalCase`, `SCREAMING_SNAKE_CASE` or `title_case`",
+                            NAME
+                        ),
+                    )
+                })
+        })
+        .transpose()?;
+
+    let mut variants = Vec::with_capacity(data.variants.len());
+    let mut names = Vec::with_capacity(data.variants.len());
+    let mut next_discriminant = Some(0u8);
+    for variant in &data.variants {
+        if !matches!(variant.fields, Fields::Unit) {
+            return Err(Error::new(
+                variant.span(),
+                "EnumMeta can be derived only for enums without variant fields",
+            ));
+        }
+        // Variants without explicit discriminant take the value of the
+        // previous one incremented by one, starting from zero
+        let discriminant = match &variant.discriminant {
+            Some((
+                _,
+                Expr::Lit(ExprLit {
+                    lit: Lit::Int(lit), ..
+                }),
+            )) => lit.base10_parse::<u8>().map_err(|_| {
+                Error::new(
+                    lit.span(),
+                    "EnumMeta requires discriminants to fit into `u8`",
+                )
+            })?,
+            Some((_, expr)) => {
+                return Err(Error::new(
+                    expr.span(),
+                    "EnumMeta requires discriminants to be integer literals",
+                ))
+            }
+            None => next_discriminant.ok_or_else(|| {
+                Error::new(
+                    variant.span(),
+                    format!(
+                        "implicit discriminant of `{}` variant does not fit into `u8`",
+                        variant.ident
+                    ),
+                )
+            })?,
+        };
+        next_discriminant = discriminant.checked_add(1);
+
+        let mut param = ParametrizedAttr::with(NAME, &variant.attrs)?;
+        param.check(AttrReq::with(HashMap::from_iter(vec![(
+            "name",
+            ArgValueReq::Optional(ValueClass::str()),
+        )])))?;
+        let name: Option<LitStr> = param
+            .args
+            .get("name")
+            .map(|a| a.clone().try_into())
+            .transpose()?;
+        let name = name.map(|name| name.value()).unwrap_or_else(|| {
+            let ident = variant.ident.to_string();
+            case.map_or_else(|| ident.clone(), |case| case.apply(&ident))
+        });
+        if names.contains(&name) {
+            return Err(Error::new(
+                variant.span(),
+                format!("repeated enum variant name `{}`", name),
+            ));
+        }
+
+        variants.push(&variant.ident);
+        names.push(name);
+    }
+
+    let count = variants.len();
+    let ident_str = ident_name.to_string();
+    let all_doc = format!(
+        "All variants of [`{}`] in the order of their declaration.",
+        ident_name
+    );
+    let count_doc = format!("Number of variants in [`{}`].", ident_name);
+
+    Ok(quote! {
+        impl #impl_generics #ident_name #ty_generics #where_clause {
+            #[doc = #all_doc]
+            pub const ALL: &'static [Self] = &[ #( Self::#variants ),* ];
+
+            #[doc = #count_doc]
+            pub const COUNT: usize = #count;
+
+            /// Returns name of the enum variant.
+            pub fn name(&self) -> &'static str {
+                match self {
+                    #( Self::#variants => #names, )*
+                }
+            }
+
+            /// Constructs enum variant from its name, returning `None` for
+            /// the unknown names.
+            pub fn from_name(name: &str) -> Option<Self> {
+                match name {
+                    #( #names => Some(Self::#variants), )*
+                    _ => None,
+                }
+            }
+        }
+
+        impl #impl_generics ::core::convert::TryFrom<u8> for #ident_name #ty_generics #where_clause {
+            type Error = #amplify_crate::UnknownDiscriminantError;
+
+            fn try_from(value: u8) -> ::core::result::Result<Self, Self::Error> {
+                match value {
+                    #( _ if value == Self::#variants as u8 => Ok(Self::#variants), )*
+                    _ => Err(#amplify_crate::UnknownDiscriminantError {
+                        ty: #ident_str,
+                        value,
+                    }),
+                }
+            }
+        }
+
+        impl #impl_generics ::core::convert::From<#ident_name #ty_generics> for u8 #where_clause {
+            fn from(value: #ident_name #ty_generics) -> Self {
+                value as u8
+            }
+        }
+    })
+}
@@ -15,20 +15,380 @@
 // If not, see <https://opensource.org/licenses/MIT>.
 
 use proc_macro2::TokenStream as TokenStream2;
-use syn::{DeriveInput, Result};
+use syn::spanned::Spanned;
+use syn::{
+    Attribute, Data, DeriveInput, Field, Fields, GenericArgument, Ident, Index, Meta, NestedMeta,
+    PathArguments, Result, Type,
+};
+
+use crate::util::Target;
+
+const NAME: &str = "error";
+const EXAMPLE: &str = r#"#[error(source)]"#;
+
+/// Error source attributes present on a field or enum variant
+#[derive(Clone, Copy, Default)]
+struct SourceAttr {
+    /// Field is marked with `#[source]`, `#[error(source)]` or
+    /// `#[error(from)]`
+    source: bool,
+    /// Field is marked with `#[error(from)]`
+    from: bool,
+}
+
+/// Parses `#[source]`, `#[error(source)]` and `#[error(from)]` attributes
+fn parse_source_attr(attrs: &[Attribute]) -> Result<SourceAttr> {
+    let mut found = SourceAttr::default();
+    for attr in attrs {
+        if attr.path.is_ident("source") {
+            match attr.parse_meta()? {
+                Meta::Path(_) => found.source = true,
+                _ => return Err(attr_err!(attr, "`#[source]` must not have arguments")),
+            }
+        } else if attr.path.is_ident(NAME) {
+            match attr.parse_meta()? {
+                Meta::List(list) => {
+                    for nested in list.nested {
+                        match nested {
+                            NestedMeta::Meta(Meta::Path(path)) if path.is_ident("source") => {
+                                found.source = true
+                            }
+                            NestedMeta::Meta(Meta::Path(path)) if path.is_ident("from") => {
+                                found.source = true;
+                                found.from = true;
+                            }
+                            _ => return Err(attr_err!(nested, "unrecognized argument")),
+                        }
+                    }
+                }
+                _ => return Err(attr_err!(attr, "arguments must be provided in a list")),
+            }
+        }
+    }
+    Ok(found)
+}
+
+/// Checks whether the type is known not to implement `Error` trait (strings,
+/// primitives and collections), so a field of this type named `source` is not
+/// used as an error source
+fn is_plain_type(ty: &Type) -> bool {
+    const PLAIN: &[&str] = &[
+        "String", "str", "Cow", "Vec", "Option", "bool", "char", "u8", "u16", "u32", "u64", "u128",
+        "usize", "i8", "i16", "i32", "i64", "i128", "isize", "f32", "f64",
+    ];
+    match ty {
+        Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .map(|segment| PLAIN.iter().any(|name| segment.ident == name))
+            .unwrap_or_default(),
+        Type::Reference(reference) => is_plain_type(&reference.elem),
+        Type::Array(_) | Type::Slice(_) | Type::Tuple(_) => true,
+        _ => false,
+    }
+}
+
+/// Detects which of the fields contains error source: the one marked with
+/// `#[source]`, `#[error(source)]` or `#[error(from)]`; otherwise a field named
+/// `source`, unless it has a type which is known not to be an error. If `attr`
+/// is set by the enum variant attributes, the fields must contain a single
+/// field, which is returned as the error source.
+fn source_field(fields: &Fields, attr: SourceAttr) -> Result<Option<(usize, &Field, bool)>> {
+    if attr.source {
+        if fields.len() != 1 {
+            return Err(attr_err!(
+                fields,
+                "error source variant must contain exactly one field"
+            ));
+        }
+        let field = fields.iter().next().expect("we know there is one field");
+        return Ok(Some((0, field, attr.from)));
+    }
+
+    let mut source = None;
+    for (index, field) in fields.iter().enumerate() {
+        let attr = parse_source_attr(&field.attrs)?;
+        if attr.source {
+            if source.is_some() {
+                return Err(attr_err!(
+                    field,
+                    "only a single field may be an error source"
+                ));
+            }
+            if attr.from && fields.len() != 1 {
+                return Err(attr_err!(
+                    field,
+                    "`#[error(from)]` requires the field to be the only one; use `#[from]` \
+                     attribute of `From` derive for types with other fields implementing \
+                     `Default`"
+                ));
+            }
+            source = Some((index, field, attr.from));
+        }
+    }
+    if source.is_none() {
+        source = fields
+            .iter()
+            .enumerate()
+            .find_map(|(index, field)| match field.ident {
+                Some(ref ident) if ident == "source" && !is_plain_type(&field.ty) => {
+                    Some((index, field, false))
+                }
+                _ => None,
+            });
+    }
+    Ok(source)
+}
+
+/// Checks whether the type is a boxed trait object (like `Box<dyn Error>`),
+/// which has to be dereferenced to be returned as an error source
+fn is_boxed_dyn(ty: &Type) -> bool {
+    let segment = match ty {
+        Type::Path(path) => match path.path.segments.last() {
+            Some(segment) if segment.ident == "Box" => segment,
+            _ => return false,
+        },
+        _ => return false,
+    };
+    match &segment.arguments {
+        PathArguments::AngleBracketed(args) => matches!(
+            args.args.first(),
+            Some(GenericArgument::Type(Type::TraitObject(_)))
+        ),
+        _ => false,
+    }
+}
+
+/// Generates pattern binding the source field to `source` variable
+fn source_pattern(index: usize, ident: Option<Ident>) -> TokenStream2 {
+    match ident {
+        Some(ident) => quote! { { #ident: source, .. } },
+        None => {
+            let skip = (0..index).map(|_| quote! { _ });
+            quote! { ( #( #skip, )* source, .. ) }
+        }
+    }
+}
+
+/// Generates constructor of the struct or enum variant from its only field
+fn from_constructor(path: TokenStream2, field: &Field) -> TokenStream2 {
+    match field.ident {
+        Some(ref ident) => quote! { #path { #ident: source } },
+        None => quote! { #path(source) },
+    }
+}
+
+/// Generates `source` method body and `From` conversions for the fields marked
+/// with `#[error(from)]`
+fn source_body(input: &DeriveInput) -> Result<(Option<TokenStream2>, Vec<TokenStream2>)> {
+    let mut from = vec![];
+    match input.data {
+        Data::Struct(ref data) => {
+            if parse_source_attr(&input.attrs)?.source {
+                return Err(attr_err!(
+                    input.ident,
+                    "error source must be specified on a field level"
+                ));
+            }
+            let body = source_field(&data.fields, SourceAttr::default())?.map(
+                |(index, field, is_from)| {
+                    if is_from {
+                        let ty = &field.ty;
+                        from.push((ty.clone(), from_constructor(quote! { Self }, field)));
+                    }
+                    let member = match field.ident {
+                        Some(ref ident) => quote! { #ident },
+                        None => {
+                            let index = Index::from(index);
+                            quote! { #index }
+                        }
+                    };
+                    if is_boxed_dyn(&field.ty) {
+                        quote! { Some(&*self.#member) }
+                    } else {
+                        quote! { Some(&self.#member) }
+                    }
+                },
+            );
+            Ok((body, render_from(input, from)))
+        }
+        Data::Enum(ref data) => {
+            let mut arms = vec![];
+            for variant in &data.variants {
+                let attr = parse_source_attr(&variant.attrs)?;
+                if let Some((index, field, is_from)) = source_field(&variant.fields, attr)? {
+                    let variant_name = &variant.ident;
+                    if is_from {
+                        from.push((
+                            field.ty.clone(),
+                            from_constructor(quote! { Self::#variant_name }, field),
+                        ));
+                    }
+                    let pattern = source_pattern(index, field.ident.clone());
+                    let source = if is_boxed_dyn(&field.ty) {
+                        quote! { &**source }
+                    } else {
+                        quote! { source }
+                    };
+                    arms.push(quote! { Self::#variant_name #pattern => Some(#source), });
+                }
+            }
+            let from = render_from(input, from);
+            if arms.is_empty() {
+                return Ok((None, from));
+            }
+            Ok((
+                Some(quote! {
+                    #[allow(unreachable_patterns)]
+                    match self {
+                        #( #arms )*
+                        _ => None,
+                    }
+                }),
+                from,
+            ))
+        }
+        Data::Union(_) => Ok((None, vec![])),
+    }
+}
+
+/// Renders `From` implementations for the error source types
+fn render_from(input: &DeriveInput, from: Vec<(Type, TokenStream2)>) -> Vec<TokenStream2> {
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let ident_name = &input.ident;
+    from.into_iter()
+        .map(|(ty, constructor)| {
+            quote! {
+                impl #impl_generics From<#ty> for #ident_name #ty_generics #where_clause {
+                    fn from(source: #ty) -> Self {
+                        #constructor
+                    }
+                }
+            }
+        })
+        .collect()
+}
 
 pub(crate) fn inner(input: DeriveInput) -> Result<TokenStream2> {
+    expand(input, Target::detect())
+}
+
+fn expand(input: DeriveInput, target: Target) -> Result<TokenStream2> {
     let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
     let ident_name = &input.ident;
 
-    Ok(quote! {
-        impl #impl_generics ::std::error::Error for #ident_name #ty_generics #where_clause {
+    // Without `std` feature we target `core::error::Error`, so the derive can
+    // be used in `no_std` crates
+    let error_trait = target.error_trait();
+
+    let (source, from) = source_body(&input)?;
+    let source = source.map(|body| {
+        quote! {
+            fn source(&self) -> Option<&(dyn #error_trait + 'static)> {
+                #body
+            }
         }
+    });
 
-        impl #impl_generics From<#ident_name #ty_generics> for String #where_clause {
-            fn from(err: #ident_name #ty_generics) -> Self {
-                err.to_string()
+    let into_string = match target {
+        Target::Std => quote! {
+            impl #impl_generics From<#ident_name #ty_generics> for String #where_clause {
+                fn from(err: #ident_name #ty_generics) -> Self {
+                    err.to_string()
+                }
             }
+        },
+        Target::Alloc => quote! {
+            impl #impl_generics From<#ident_name #ty_generics> for ::alloc::string::String #where_clause {
+                fn from(err: #ident_name #ty_generics) -> Self {
+                    ::alloc::string::ToString::to_string(&err)
+                }
+            }
+        },
+        Target::Core => quote! {},
+    };
+
+    Ok(quote! {
+        impl #impl_generics #error_trait for #ident_name #ty_generics #where_clause {
+            #source
         }
+
+        #into_string
+
+        #( #from )*
     })
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn expand_str(target: Target) -> String {
+        let input: DeriveInput = syn::parse_quote! {
+            enum Error {
+                Io(#[source] Box<dyn Error>),
+                Other,
+            }
+        };
+        expand(input, target).unwrap().to_string()
+    }
+
+    #[test]
+    fn source_name() {
+        let input: DeriveInput = syn::parse_quote! {
+            struct Error {
+                source: std::io::Error,
+            }
+        };
+        let code = expand(input, Target::Std).unwrap().to_string();
+        assert!(code.contains("Some (& self . source)"));
+
+        let input: DeriveInput = syn::parse_quote! {
+            struct Error {
+                source: String,
+            }
+        };
+        let code = expand(input, Target::Std).unwrap().to_string();
+        assert!(!code.contains("fn source"));
+    }
+
+    #[test]
+    fn source_from() {
+        let input: DeriveInput = syn::parse_quote! {
+            enum Error {
+                #[error(from)]
+                Io(std::io::Error),
+                Other,
+            }
+        };
+        let code = expand(input, Target::Std).unwrap().to_string();
+        assert!(code.contains("fn source"));
+        assert!(code.contains("impl From < std :: io :: Error > for Error"));
+        assert!(code.contains("Self :: Io (source)"));
+    }
+
+    #[test]
+    fn no_std() {
+        let code = expand_str(Target::Core);
+        assert!(code.contains(":: core :: error :: Error for Error"));
+        assert!(!code.contains("std ::"));
+        assert!(!code.contains("String"));
+    }
+
+    #[test]
+    fn alloc() {
+        let code = expand_str(Target::Alloc);
+        assert!(code.contains(":: core :: error :: Error for Error"));
+        assert!(code.contains("for :: alloc :: string :: String"));
+        assert!(!code.contains("std ::"));
+    }
+
+    #[test]
+    fn std() {
+        let code = expand_str(Target::Std);
+        assert!(code.contains(":: std :: error :: Error for Error"));
+        assert!(!code.contains("core :: error"));
+        assert!(!code.contains("alloc ::"));
+    }
+}
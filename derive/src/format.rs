@@ -0,0 +1,425 @@
+// Rust language amplification derive library providing multiple generic trait
+// implementations, type wrappers, derive macros and other language enhancements
+//
+// Written in 2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the MIT License
+// along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+//! Parser of the format strings used by `Display` and `FromStr` derives.
+//! Follows the grammar of `std::fmt` and additionally allows arbitrary
+//! expressions in place of the argument names.
+
+use std::fmt::Display;
+use std::ops::Range;
+
+use proc_macro2::{Span, TokenTree};
+use quote::ToTokens;
+use syn::{Error, LitStr, Result};
+
+/// Argument referenced from a placeholder
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub(crate) enum Arg {
+    /// Positional argument, either explicit (`{1}`) or implicit (`{}`)
+    Index(usize),
+    /// Named argument (`{name}`)
+    Name(String),
+    /// Expression (`{self.0 + 1}`)
+    Expr(String),
+}
+
+/// Width or precision of the formatting spec
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub(crate) enum Count {
+    /// Value given in the format string (`{:8}`)
+    Literal(usize),
+    /// Value taken from an argument (`{:1$}`, `{:width$}` or `{:.*}`)
+    Arg(Arg),
+}
+
+/// Formatting spec of a placeholder: everything after the colon
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub(crate) struct Spec {
+    /// Fill, alignment, sign, alternate and zero-padding flags
+    pub flags: String,
+    pub width: Option<Count>,
+    pub precision: Option<Count>,
+    /// Formatting trait selector: `?`, `x`, `e` etc; empty for `Display`
+    pub ty: String,
+}
+
+impl Spec {
+    pub fn is_empty(&self) -> bool {
+        self.flags.is_empty()
+            && self.width.is_none()
+            && self.precision.is_none()
+            && self.ty.is_empty()
+    }
+
+    /// Returns all arguments referenced from the spec
+    pub fn args(&self) -> impl Iterator<Item = &Arg> {
+        self.width
+            .iter()
+            .chain(self.precision.iter())
+            .filter_map(|count| match count {
+                Count::Literal(_) => None,
+                Count::Arg(arg) => Some(arg),
+            })
+    }
+
+    /// Writes the spec back as a string, replacing arguments with the names
+    /// returned by `name`
+    pub fn to_string_with(&self, mut name: impl FnMut(&Arg) -> String) -> String {
+        let mut s = self.flags.clone();
+        let mut count = |count: &Count| match count {
+            Count::Literal(value) => value.to_string(),
+            Count::Arg(arg) => format!("{}$", name(arg)),
+        };
+        if let Some(ref width) = self.width {
+            s.push_str(&count(width));
+        }
+        if let Some(ref precision) = self.precision {
+            s.push('.');
+            s.push_str(&count(precision));
+        }
+        s.push_str(&self.ty);
+        s
+    }
+}
+
+/// Placeholder inside a format string
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub(crate) struct Placeholder {
+    pub arg: Arg,
+    pub spec: Spec,
+    /// Position of the placeholder, including braces, in the format string
+    pub range: Range<usize>,
+}
+
+/// Fragment of a format string
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub(crate) enum Piece {
+    /// Literal text, with unescaped braces
+    Literal(String),
+    /// Formatting placeholder
+    Placeholder(Placeholder),
+}
+
+/// Parses format string literal into pieces. Implicit positional arguments
+/// are resolved into explicit indexes.
+pub(crate) fn parse_format(lit: &LitStr) -> Result<Vec<Piece>> {
+    let s = lit.value();
+    Parser::new(&s)
+        .parse()
+        .map_err(|(range, msg)| Location::with(lit, range).error(msg))
+}
+
+/// Returns text written by a format string without placeholders, with
+/// unescaped braces, or `None` if the string contains placeholders
+pub(crate) fn literal(lit: &LitStr) -> Result<Option<String>> {
+    Ok(parse_format(lit)?
+        .into_iter()
+        .map(|piece| match piece {
+            Piece::Literal(literal) => Some(literal),
+            Piece::Placeholder(_) => None,
+        })
+        .collect())
+}
+
+/// Location of a fragment (like a placeholder) of the format string literal
+/// for error reporting
+#[derive(Clone, Debug)]
+pub(crate) struct Location {
+    /// Span of the fragment; see [`span`] for the details
+    pub span: Span,
+    /// Text of the fragment
+    text: String,
+    /// Byte offset of the fragment in the format string
+    offset: usize,
+}
+
+impl Location {
+    pub fn with(lit: &LitStr, range: Range<usize>) -> Self {
+        Location {
+            span: span(lit, range.clone()),
+            text: lit.value()[range.clone()].to_owned(),
+            offset: range.start,
+        }
+    }
+
+    /// Constructs error pointing at the fragment. Compilers can point inside
+    /// string literals only on nightly, so on stable the error spans the whole
+    /// literal; thus the message also contains the fragment text and its byte
+    /// offset.
+    pub fn error(&self, msg: impl Display) -> Error {
+        Error::new(
+            self.span,
+            format!(
+                "{} (`{}` at byte {} of the format string)",
+                msg, self.text, self.offset
+            ),
+        )
+    }
+}
+
+/// Returns span of a fragment of the format string literal. Falls back to
+/// the span of the whole literal if the compiler can't point inside it (which
+/// is always the case on stable compilers, since `Literal::subspan` is
+/// nightly-only) or the literal contains escapes shifting the positions.
+pub(crate) fn span(lit: &LitStr, range: Range<usize>) -> Span {
+    let token = match lit.to_token_stream().into_iter().next() {
+        Some(TokenTree::Literal(token)) => token,
+        _ => return lit.span(),
+    };
+    let repr = token.to_string();
+    let value = lit.value();
+    let offset = match (repr.find('"'), repr.rfind('"')) {
+        (Some(start), Some(end)) if start < end && repr[start + 1..end] == value => start + 1,
+        _ => return lit.span(),
+    };
+    token
+        .subspan(range.start + offset..range.end + offset)
+        .unwrap_or_else(|| lit.span())
+}
+
+/// Escapes braces so the string can be used as a literal part of a format
+/// string
+pub(crate) fn escape(s: &str) -> String {
+    s.replace('{', "{{").replace('}', "}}")
+}
+
+type ParseResult<T> = ::core::result::Result<T, (Range<usize>, String)>;
+
+struct Parser<'a> {
+    s: &'a str,
+    /// Index of the next implicit positional argument
+    next_index: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(s: &'a str) -> Self {
+        Parser { s, next_index: 0 }
+    }
+
+    fn next_index(&mut self) -> usize {
+        self.next_index += 1;
+        self.next_index - 1
+    }
+
+    fn parse(mut self) -> ParseResult<Vec<Piece>> {
+        let s = self.s;
+        let mut pieces = vec![];
+        let mut literal = String::new();
+        let mut chars = s.char_indices().peekable();
+        while let Some((pos, c)) = chars.next() {
+            match c {
+                '{' if matches!(chars.peek(), Some((_, '{'))) => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if matches!(chars.peek(), Some((_, '}'))) => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '}' => {
+                    return Err((
+                        pos..pos + 1,
+                        "unmatched `}` in format string; use `}}` to print a brace".to_owned(),
+                    ))
+                }
+                '{' => {
+                    let mut depth = 0usize;
+                    let end = loop {
+                        match chars.next() {
+                            None => {
+                                return Err((
+                                    pos..s.len(),
+                                    "unmatched `{` in format string; use `{{` to print a brace"
+                                        .to_owned(),
+                                ))
+                            }
+                            Some((end, '}')) if depth == 0 => break end,
+                            Some((_, '(')) | Some((_, '[')) | Some((_, '{')) => depth += 1,
+                            Some((_, ')')) | Some((_, ']')) | Some((_, '}')) => {
+                                depth = depth.saturating_sub(1)
+                            }
+                            Some(_) => {}
+                        }
+                    };
+                    if !literal.is_empty() {
+                        pieces.push(Piece::Literal(literal));
+                        literal = String::new();
+                    }
+                    let range = pos..end + 1;
+                    let placeholder = self
+                        .placeholder(&s[pos + 1..end], range.clone())
+                        .map_err(|msg| (range, msg))?;
+                    pieces.push(Piece::Placeholder(placeholder));
+                }
+                c => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            pieces.push(Piece::Literal(literal));
+        }
+        Ok(pieces)
+    }
+
+    fn placeholder(
+        &mut self,
+        inner: &str,
+        range: Range<usize>,
+    ) -> ::core::result::Result<Placeholder, String> {
+        let (arg, spec) = split_placeholder(inner);
+        // Precision given with `.*` takes the next positional argument before
+        // the formatted value itself
+        let spec = match spec {
+            Some(spec) => self.spec(spec)?,
+            None => Spec::default(),
+        };
+        let arg = match arg.trim() {
+            "" => Arg::Index(self.next_index()),
+            arg => parse_arg(arg).unwrap_or_else(|| Arg::Expr(arg.to_owned())),
+        };
+        Ok(Placeholder { arg, spec, range })
+    }
+
+    fn spec(&mut self, spec: &str) -> ::core::result::Result<Spec, String> {
+        let invalid = || format!("invalid format spec `{}`", spec);
+        let mut rest = spec;
+        let mut res = Spec::default();
+
+        // [[fill]align]
+        let mut chars = rest.chars();
+        match (chars.next(), chars.next()) {
+            (Some(fill), Some(align)) if is_align(align) => {
+                let len = fill.len_utf8() + 1;
+                res.flags.push_str(&rest[..len]);
+                rest = &rest[len..];
+            }
+            (Some(align), _) if is_align(align) => {
+                res.flags.push(align);
+                rest = &rest[1..];
+            }
+            _ => {}
+        }
+        // [sign]['#']
+        for flag in &["+", "-", "#"] {
+            if let Some(r) = rest.strip_prefix(flag) {
+                res.flags.push_str(flag);
+                rest = r;
+            }
+        }
+        // ['0'], unless it is the width argument index `0$`
+        if rest.starts_with('0') && !rest[1..].starts_with('$') {
+            res.flags.push('0');
+            rest = &rest[1..];
+        }
+        // [width]
+        let (count, r) = parse_count(rest).ok_or_else(invalid)?;
+        res.width = count;
+        rest = r;
+        // ['.' precision]
+        if let Some(r) = rest.strip_prefix('.') {
+            if let Some(r) = r.strip_prefix('*') {
+                res.precision = Some(Count::Arg(Arg::Index(self.next_index())));
+                rest = r;
+            } else {
+                let (count, r) = parse_count(r).ok_or_else(invalid)?;
+                res.precision = Some(count.ok_or_else(invalid)?);
+                rest = r;
+            }
+        }
+        // type
+        if !["", "?", "x?", "X?", "x", "X", "o", "b", "e", "E", "p"].contains(&rest) {
+            return Err(format!("unknown format trait `{}`", rest));
+        }
+        res.ty = rest.to_owned();
+        Ok(res)
+    }
+}
+
+fn is_align(c: char) -> bool {
+    matches!(c, '<' | '^' | '>')
+}
+
+/// Parses argument which is either an integer index or an identifier
+fn parse_arg(s: &str) -> Option<Arg> {
+    if !s.is_empty() && s.chars().all(|c| c.is_ascii_digit()) {
+        return s.parse().ok().map(Arg::Index);
+    }
+    let mut chars = s.chars();
+    if matches!(chars.next(), Some(c) if c.is_alphabetic() || c == '_')
+        && chars.all(|c| c.is_alphanumeric() || c == '_')
+    {
+        return Some(Arg::Name(s.to_owned()));
+    }
+    None
+}
+
+/// Parses optional width or precision count from the beginning of the string,
+/// returning the rest of it. The count is either an integer literal, or an
+/// argument (index or identifier) followed by `$`; everything else is left for
+/// the formatting trait selector.
+fn parse_count(s: &str) -> Option<(Option<Count>, &str)> {
+    let digits = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    if digits > 0 {
+        let (token, rest) = s.split_at(digits);
+        return match rest.strip_prefix('$') {
+            Some(rest) => Some((Some(Count::Arg(parse_arg(token)?)), rest)),
+            None => Some((Some(Count::Literal(token.parse().ok()?)), rest)),
+        };
+    }
+    let len = s
+        .find(|c: char| !c.is_alphanumeric() && c != '_')
+        .unwrap_or(s.len());
+    let (token, rest) = s.split_at(len);
+    match rest.strip_prefix('$') {
+        Some(rest) => Some((Some(Count::Arg(parse_arg(token)?)), rest)),
+        None => Some((None, s)),
+    }
+}
+
+/// Splits placeholder into argument and formatting spec parts, ignoring
+/// path separators and nested brackets inside argument expressions
+fn split_placeholder(s: &str) -> (&str, Option<&str>) {
+    let bytes = s.as_bytes();
+    let mut depth = 0usize;
+    for (pos, c) in bytes.iter().enumerate() {
+        match c {
+            b'(' | b'[' | b'{' => depth += 1,
+            b')' | b']' | b'}' => depth = depth.saturating_sub(1),
+            b':' if depth == 0 => {
+                let prev = pos.checked_sub(1).map(|p| bytes[p]);
+                let next = bytes.get(pos + 1).copied();
+                if prev != Some(b':') && next != Some(b':') {
+                    return (&s[..pos], Some(&s[pos + 1..]));
+                }
+            }
+            _ => {}
+        }
+    }
+    (s, None)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn error_location() {
+        let lit: LitStr = syn::parse_quote! { "value {0} and {1:q}" };
+        let err = parse_format(&lit).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "unknown format trait `q` (`{1:q}` at byte 14 of the format string)"
+        );
+    }
+}
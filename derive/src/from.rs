@@ -15,15 +15,62 @@
 // If not, see <https://opensource.org/licenses/MIT>.
 
 use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::ToTokens;
+use syn::parse::{Parse, ParseStream};
 use syn::punctuated::Punctuated;
 use syn::spanned::Spanned;
 use syn::{
     Attribute, Data, DataEnum, DataStruct, DataUnion, DeriveInput, Error, Field, Fields,
-    FieldsNamed, FieldsUnnamed, Ident, Result, Type,
+    FieldsNamed, FieldsUnnamed, Ident, Member, Meta, NestedMeta, Result, Type, WherePredicate,
 };
 
+use crate::bounds::{parse_bound, Bounds};
+use crate::display::CaseStyle;
+
 const NAME: &str = "from";
 const EXAMPLE: &str = r#"#[from(::std::fmt::Error)]"#;
+const TRY_NAME: &str = "try_from";
+const TRY_EXAMPLE: &str = r#"#[try_from(u8, error = Error)]"#;
+
+/// Kind of the conversion trait implemented for an entry
+#[derive(Clone)]
+enum Conversion {
+    /// Infallible conversion with `From`
+    From,
+    /// Fallible conversion with `TryFrom`, delegated to `TryFrom`
+    /// implementation of the target field type. If the error type is given,
+    /// errors of the field conversion are converted into it with `From`.
+    TryFrom { error: Option<Box<Type>> },
+}
+
+/// Arguments of `#[try_from(Type, error = ErrType)]` attribute
+struct TryFromArgs {
+    from: Type,
+    error: Option<Type>,
+}
+
+impl Parse for TryFromArgs {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let from = input.parse()?;
+        let mut error = None;
+        if input.parse::<Option<Token![,]>>()?.is_some() && !input.is_empty() {
+            let name: Ident = input.parse()?;
+            if name != "error" {
+                return Err(Error::new(
+                    name.span(),
+                    format!(
+                        "Attribute `#[{}]`: unknown argument `{}`\nExample use: {}",
+                        TRY_NAME, name, TRY_EXAMPLE
+                    ),
+                ));
+            }
+            input.parse::<Token![=]>()?;
+            error = Some(input.parse()?);
+            input.parse::<Option<Token![,]>>()?;
+        }
+        Ok(TryFromArgs { from, error })
+    }
+}
 
 #[derive(Clone, PartialEq, Eq, Debug)]
 enum InstructionEntity {
@@ -112,6 +159,98 @@ impl InstructionEntity {
         }
     }
 
+    /// Returns type of the field which is constructed from the converted
+    /// value, if any
+    pub fn target_ty(&self, fields: &Fields) -> Option<Type> {
+        match self {
+            InstructionEntity::Named { field, .. } => fields
+                .iter()
+                .find(|f| f.ident.as_ref() == Some(field))
+                .map(|f| f.ty.clone()),
+            InstructionEntity::Unnamed { index, .. } => {
+                fields.iter().nth(*index).map(|f| f.ty.clone())
+            }
+            _ => None,
+        }
+    }
+
+    /// Adds bounds required for constructing the entity from type `from`:
+    /// the converted field must be constructable from it, and the rest of
+    /// the fields must have default values
+    pub fn add_bounds(
+        &self,
+        from: &Type,
+        fields: &Fields,
+        conversion: &Conversion,
+        bounds: &mut Bounds,
+    ) {
+        let into = |ty: &Type, bounds: &mut Bounds| match conversion {
+            Conversion::From => {
+                if quote! { #ty }.to_string() != quote! { #from }.to_string() {
+                    bounds.add(from, quote! { ::core::convert::Into<#ty> });
+                }
+            }
+            Conversion::TryFrom { error } => {
+                bounds.add(ty, quote! { ::core::convert::TryFrom<#from> });
+                if let Some(error) = error {
+                    bounds.add(
+                        error,
+                        quote! {
+                            ::core::convert::From<
+                                <#ty as ::core::convert::TryFrom<#from>>::Error
+                            >
+                        },
+                    );
+                }
+            }
+        };
+        let default = quote! { ::core::default::Default };
+        match self {
+            InstructionEntity::Default => bounds.add_self(default),
+            InstructionEntity::Unit { .. } => {}
+            InstructionEntity::Named {
+                variant: None,
+                field,
+                ..
+            } => {
+                for f in fields {
+                    if f.ident.as_ref() == Some(field) {
+                        into(&f.ty, bounds);
+                    }
+                }
+                bounds.add_self(default);
+            }
+            InstructionEntity::Named {
+                variant: Some(_),
+                field,
+                ..
+            } => {
+                for f in fields {
+                    if f.ident.as_ref() == Some(field) {
+                        into(&f.ty, bounds);
+                    } else {
+                        bounds.add(&f.ty, default.clone());
+                    }
+                }
+            }
+            InstructionEntity::Unnamed { index, .. } => {
+                for (i, f) in fields.iter().enumerate() {
+                    if i == *index {
+                        into(&f.ty, bounds);
+                    } else {
+                        bounds.add(&f.ty, default.clone());
+                    }
+                }
+            }
+            InstructionEntity::DefaultEnumFields { .. } => {
+                for f in fields {
+                    bounds.add(&f.ty, default.clone());
+                }
+            }
+        }
+    }
+
+    /// Generates expression constructing the entity from the value `v`
     pub fn into_token_stream2(self) -> TokenStream2 {
         match self {
             InstructionEntity::Default => quote! {
@@ -166,8 +305,10 @@ impl InstructionEntity {
     }
 }
 
+/// Conversion from a type into the entity; keeps all fields of the struct or
+/// enum variant which are used to infer bounds for the generic parameters
 #[derive(Clone)]
-struct InstructionEntry(pub Type, pub InstructionEntity);
+struct InstructionEntry(pub Type, pub InstructionEntity, pub Fields, pub Conversion);
 
 impl PartialEq for InstructionEntry {
     // Ugly way, but with current `syn` version no other way is possible
@@ -182,7 +323,12 @@ impl PartialEq for InstructionEntry {
 
 impl InstructionEntry {
     pub fn with_type(ty: &Type, entity: &InstructionEntity) -> Self {
-        Self(ty.clone(), entity.clone())
+        Self(ty.clone(), entity.clone(), Fields::Unit, Conversion::From)
+    }
+
+    pub fn with_fields(mut self, fields: &Fields) -> Self {
+        self.2 = fields.clone();
+        self
     }
 
     pub fn parse(
@@ -191,7 +337,10 @@ impl InstructionEntry {
         entity: InstructionEntity,
     ) -> Result<Vec<InstructionEntry>> {
         let mut list = Vec::<InstructionEntry>::new();
-        for attr in attrs.iter().filter(|attr| attr.path.is_ident(NAME)) {
+        for attr in attrs
+            .iter()
+            .filter(|attr| attr.path.is_ident(NAME) && !is_bound_attr(attr) && !is_into_attr(attr))
+        {
             // #[from]
             if attr.tokens.is_empty() {
                 match (fields.len(), fields.iter().next()) {
@@ -209,6 +358,29 @@ impl InstructionEntry {
                 list.push(InstructionEntry::with_type(&attr.parse_args()?, &entity));
             }
         }
+        for attr in attrs.iter().filter(|attr| attr.path.is_ident(TRY_NAME)) {
+            let TryFromArgs { from, error } = attr.parse_args()?;
+            match entity {
+                InstructionEntity::Named { .. } | InstructionEntity::Unnamed { .. } => {}
+                _ => {
+                    return Err(attr_err!(
+                        attr,
+                        TRY_NAME,
+                        "the conversion must target a single field; for multi-field \
+                         entities specify the attribute right ahead of the target field",
+                        TRY_EXAMPLE
+                    ))
+                }
+            }
+            list.push(InstructionEntry(
+                from,
+                entity.clone(),
+                Fields::Unit,
+                Conversion::TryFrom {
+                    error: error.map(Box::new),
+                },
+            ));
+        }
         Ok(list)
     }
 }
@@ -228,11 +400,15 @@ impl InstructionTable {
         variant: Option<Ident>,
     ) -> Result<&Self> {
         let entity = InstructionEntity::with_fields(fields, variant.clone())?;
-        self.extend(InstructionEntry::parse(fields, attrs, entity.clone())?)?;
+        self.extend(
+            InstructionEntry::parse(fields, attrs, entity.clone())?
+                .into_iter()
+                .map(|entry| entry.with_fields(fields)),
+        )?;
         for (index, field) in fields.iter().enumerate() {
             let mut punctuated = Punctuated::new();
             punctuated.push_value(field.clone());
-            self.extend(InstructionEntry::parse(
+            let entries = InstructionEntry::parse(
                 &field.ident.as_ref().map_or(
                     Fields::Unnamed(FieldsUnnamed {
                         paren_token: Default::default(),
@@ -247,14 +423,21 @@ impl InstructionTable {
                 ),
                 &field.attrs,
                 InstructionEntity::with_field(index, fields.len(), field, fields, variant.clone()),
-            )?)?;
+            )?;
+            if let Some(attr) = field.attrs.iter().find(|attr| is_into_attr(attr)) {
+                return Err(attr_err!(
+                    attr,
+                    "`into` argument must be used on the type or enum variant, not a field"
+                ));
+            }
+            self.extend(entries.into_iter().map(|entry| entry.with_fields(fields)))?;
         }
         if variant.is_none() && fields.len() == 1 && self.0.is_empty() {
             let field = fields
                 .into_iter()
                 .next()
                 .expect("we know we have at least one item");
-            self.push(InstructionEntry::with_type(&field.ty, &entity));
+            self.push(InstructionEntry::with_type(&field.ty, &entity).with_fields(fields));
         }
         Ok(self)
     }
@@ -275,7 +458,7 @@ impl InstructionTable {
                     format!(
                         "Attribute `#[{}]`: repeated use of type `{}`",
                         NAME,
-                        quote! {ty}
+                        entry.0.to_token_stream()
                     ),
                 ))
             })?;
@@ -285,22 +468,234 @@ impl InstructionTable {
         Ok(count)
     }
 
-    pub fn into_token_stream2(self, input: &DeriveInput) -> TokenStream2 {
-        let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    pub fn into_token_stream2(self, input: &DeriveInput) -> Result<TokenStream2> {
         let ident_name = &input.ident;
+        let custom_bounds = custom_bounds(&input.attrs)?;
 
-        self.0.into_iter().fold(TokenStream2::new(), |mut stream, InstructionEntry(from, entity)| {
+        Ok(self.0.into_iter().fold(TokenStream2::new(), |mut stream, InstructionEntry(from, entity, fields, conversion)| {
+            let mut bounds = Bounds::with(&input.generics, custom_bounds.clone());
+            entity.add_bounds(&from, &fields, &conversion, &mut bounds);
+            let generics = bounds.apply(&input.generics);
+            let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+            let target = entity.target_ty(&fields);
             let convert = entity.into_token_stream2();
-            stream.extend(quote! {
-                impl #impl_generics ::core::convert::From<#from> for #ident_name #ty_generics #where_clause {
-                    fn from(v: #from) -> Self {
-                        #convert
+            stream.extend(match conversion {
+                Conversion::From => quote! {
+                    impl #impl_generics ::core::convert::From<#from> for #ident_name #ty_generics #where_clause {
+                        fn from(v: #from) -> Self {
+                            #convert
+                        }
+                    }
+                },
+                Conversion::TryFrom { error } => {
+                    let target = target.expect("fallible conversion always has a target field");
+                    let error = error.map_or_else(
+                        || quote! { <#target as ::core::convert::TryFrom<#from>>::Error },
+                        |error| quote! { #error },
+                    );
+                    quote! {
+                        impl #impl_generics ::core::convert::TryFrom<#from> for #ident_name #ty_generics #where_clause {
+                            type Error = #error;
+
+                            fn try_from(v: #from) -> ::core::result::Result<Self, Self::Error> {
+                                let v = <#target as ::core::convert::TryFrom<#from>>::try_from(v)?;
+                                Ok(#convert)
+                            }
+                        }
                     }
                 }
             });
             stream
-        })
+        }))
+    }
+}
+
+/// Parses bounds for the generic parameters given with
+/// `#[from(bound = "...")]`
+fn custom_bounds(attrs: &[Attribute]) -> Result<Option<Vec<WherePredicate>>> {
+    let mut res = None;
+    for attr in attrs.iter().filter(|attr| attr.path.is_ident(NAME)) {
+        if let Ok(Meta::List(list)) = attr.parse_meta() {
+            for bound in list.nested.iter().filter_map(parse_bound) {
+                if res.is_some() {
+                    return Err(attr_err!(attr, "repeated `bound` argument"));
+                }
+                res = Some(bound?);
+            }
+        }
+    }
+    Ok(res)
+}
+
+fn is_bound_attr(attr: &Attribute) -> bool {
+    match attr.parse_meta() {
+        Ok(Meta::List(list)) => list
+            .nested
+            .iter()
+            .any(|nested| parse_bound(nested).is_some()),
+        _ => false,
+    }
+}
+
+/// Checks whether the attribute is `#[from(into)]`
+pub(crate) fn is_into_attr(attr: &Attribute) -> bool {
+    match attr.parse_meta() {
+        Ok(Meta::List(list)) if attr.path.is_ident(NAME) => {
+            list.nested.len() == 1
+                && matches!(list.nested.first(), Some(NestedMeta::Meta(Meta::Path(path))) if path.is_ident("into"))
+        }
+        _ => false,
+    }
+}
+
+/// Returns the only field of a newtype or enum variant marked with
+/// `#[from(into)]`, which is extracted by the reverse conversions
+fn into_field<'a>(fields: &'a Fields, attrs: &[Attribute]) -> Result<Option<(Member, &'a Type)>> {
+    let attr = match attrs.iter().find(|attr| is_into_attr(attr)) {
+        Some(attr) => attr,
+        None => return Ok(None),
+    };
+    let mut iter = fields.iter();
+    match (iter.next(), iter.next()) {
+        (Some(field), None) => Ok(Some((
+            field
+                .ident
+                .clone()
+                .map_or_else(|| Member::from(0), Member::Named),
+            &field.ty,
+        ))),
+        _ => Err(attr_err!(
+            attr,
+            "`into` argument requires exactly one field to convert into"
+        )),
+    }
+}
+
+/// Generates `From<Newtype> for Inner` for a newtype marked with
+/// `#[from(into)]`
+fn reverse_struct(input: &DeriveInput, data: &DataStruct) -> Result<TokenStream2> {
+    let (member, inner) = match into_field(&data.fields, &input.attrs)? {
+        Some(field) => field,
+        None => return Ok(TokenStream2::new()),
+    };
+    if Bounds::with(&input.generics, None).is_param(inner) {
+        return Err(Error::new(
+            inner.span(),
+            "conversion into a generic type parameter can't be implemented due to the orphan \
+             rules",
+        ));
+    }
+    let ident_name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::core::convert::From<#ident_name #ty_generics> for #inner #where_clause {
+            fn from(v: #ident_name #ty_generics) -> Self {
+                v.#member
+            }
+        }
+    })
+}
+
+/// Generates `is_*`, `as_*` and `into_*` methods and `TryFrom<Enum>`
+/// implementation for the payloads of enum variants marked with
+/// `#[from(into)]`
+fn reverse_enum(input: &DeriveInput, data: &DataEnum) -> Result<TokenStream2> {
+    let ident_name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let bounds = Bounds::with(&input.generics, None);
+
+    let mut methods = vec![];
+    let mut impls = vec![];
+    let mut payloads: Vec<String> = vec![];
+    for variant in &data.variants {
+        let (member, payload) = match into_field(&variant.fields, &variant.attrs)? {
+            Some(field) => field,
+            None => continue,
+        };
+        let var = &variant.ident;
+        let name = CaseStyle::Snake.apply(&var.to_string());
+        let is_fn = Ident::new(&format!("is_{}", name), var.span());
+        let as_fn = Ident::new(&format!("as_{}", name), var.span());
+        let into_fn = Ident::new(&format!("into_{}", name), var.span());
+        let pattern = match member {
+            Member::Named(field) => quote! { Self::#var { #field: v } },
+            Member::Unnamed(_) => quote! { Self::#var(v) },
+        };
+        let is_doc = format!(
+            "Checks whether the value is [`{}::{}`] variant.",
+            ident_name, var
+        );
+        let as_doc = format!(
+            "Returns reference to the data of [`{}::{}`] variant, or `None` for the other \
+             variants.",
+            ident_name, var
+        );
+        let into_doc = format!(
+            "Converts the value into the data of [`{}::{}`] variant; returns the value back \
+             as an error for the other variants.",
+            ident_name, var
+        );
+        methods.push(quote! {
+            #[doc = #is_doc]
+            #[inline]
+            pub fn #is_fn(&self) -> bool {
+                match self {
+                    #pattern => true,
+                    #[allow(unreachable_patterns)]
+                    _ => false,
+                }
+            }
+
+            #[doc = #as_doc]
+            #[inline]
+            pub fn #as_fn(&self) -> Option<&#payload> {
+                match self {
+                    #pattern => Some(v),
+                    #[allow(unreachable_patterns)]
+                    _ => None,
+                }
+            }
+
+            #[doc = #into_doc]
+            #[inline]
+            pub fn #into_fn(self) -> ::core::result::Result<#payload, Self> {
+                match self {
+                    #pattern => Ok(v),
+                    #[allow(unreachable_patterns)]
+                    other => Err(other),
+                }
+            }
+        });
+
+        // Conversions into generic type parameters are prohibited by the
+        // orphan rules, and repeated payload types would produce conflicting
+        // implementations
+        let repr = payload.to_token_stream().to_string();
+        if bounds.is_param(payload) || payloads.contains(&repr) {
+            continue;
+        }
+        payloads.push(repr);
+        impls.push(quote! {
+            impl #impl_generics ::core::convert::TryFrom<#ident_name #ty_generics> for #payload #where_clause {
+                type Error = #ident_name #ty_generics;
+
+                fn try_from(v: #ident_name #ty_generics) -> ::core::result::Result<Self, Self::Error> {
+                    v.#into_fn()
+                }
+            }
+        });
     }
+
+    if methods.is_empty() {
+        return Ok(TokenStream2::new());
+    }
+    Ok(quote! {
+        impl #impl_generics #ident_name #ty_generics #where_clause {
+            #( #methods )*
+        }
+
+        #( #impls )*
+    })
 }
 
 pub(crate) fn inner(input: DeriveInput) -> Result<TokenStream2> {
@@ -314,7 +709,9 @@ pub(crate) fn inner(input: DeriveInput) -> Result<TokenStream2> {
 fn inner_struct(input: &DeriveInput, data: &DataStruct) -> Result<TokenStream2> {
     let mut instructions = InstructionTable::new();
     instructions.parse(&data.fields, &input.attrs, None)?;
-    Ok(instructions.into_token_stream2(input))
+    let mut stream = instructions.into_token_stream2(input)?;
+    stream.extend(reverse_struct(input, data)?);
+    Ok(stream)
 }
 
 fn inner_enum(input: &DeriveInput, data: &DataEnum) -> Result<TokenStream2> {
@@ -322,7 +719,9 @@ fn inner_enum(input: &DeriveInput, data: &DataEnum) -> Result<TokenStream2> {
     input
         .attrs
         .iter()
-        .find(|attr| attr.path.is_ident(NAME))
+        .find(|attr| {
+            (attr.path.is_ident(NAME) && !is_bound_attr(attr)) || attr.path.is_ident(TRY_NAME)
+        })
         .map_or(Ok(()), |a| {
             Err(attr_err!(
                 a,
@@ -334,11 +733,16 @@ fn inner_enum(input: &DeriveInput, data: &DataEnum) -> Result<TokenStream2> {
     for v in &data.variants {
         instructions.parse(&v.fields, &v.attrs, Some(v.ident.clone()))?;
     }
-    Ok(instructions.into_token_stream2(input))
+    let mut stream = instructions.into_token_stream2(input)?;
+    stream.extend(reverse_enum(input, data)?);
+    Ok(stream)
 }
 
 fn inner_union(input: &DeriveInput, data: &DataUnion) -> Result<TokenStream2> {
+    if let Some(attr) = input.attrs.iter().find(|attr| is_into_attr(attr)) {
+        return Err(attr_err!(attr, "`into` argument is not supported for unions"));
+    }
     let mut instructions = InstructionTable::new();
     instructions.parse(&Fields::Named(data.fields.clone()), &input.attrs, None)?;
-    Ok(instructions.into_token_stream2(input))
+    instructions.into_token_stream2(input)
 }
@@ -0,0 +1,360 @@
+// Rust language amplification derive library providing multiple generic trait
+// implementations, type wrappers, derive macros and other language enhancements
+//
+// Written in 2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the MIT License
+// along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+use std::collections::HashMap;
+use std::convert::TryInto;
+use std::iter::FromIterator;
+
+use amplify_syn::{ArgValueReq, AttrReq, ParametrizedAttr, ValueClass};
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use syn::spanned::Spanned;
+use syn::{Data, DeriveInput, Error, Fields, Ident, LitStr, Result};
+
+use crate::display::{struct_technique, EnumTechniques, FieldFmt, Technique};
+use crate::format::{self, parse_format, Arg, Location, Piece, Placeholder};
+use crate::util::Target;
+
+const NAME: &str = "display";
+const EXAMPLE: &str = r#"#[display("{field1}:{field2}" | inner | lowercase)]"#;
+
+/// Parser of a string representation of a struct or an enum variant
+struct Parser {
+    /// Description of the accepted string representation used in the error
+    /// message
+    expected: String,
+    /// Code returning `Some(Self)` if the string `s` matches the parser
+    code: TokenStream2,
+}
+
+impl Parser {
+    /// Parser matching string to a literal
+    fn literal(constructor: TokenStream2, lit: &str, case_insensitive: bool) -> Self {
+        let code = if case_insensitive {
+            quote! {
+                if s.eq_ignore_ascii_case(#lit) {
+                    return Ok(#constructor);
+                }
+            }
+        } else {
+            quote! {
+                if s == #lit {
+                    return Ok(#constructor);
+                }
+            }
+        };
+        Parser {
+            expected: format!("`{}`", lit),
+            code,
+        }
+    }
+
+    /// Parser splitting string on the format string literals and parsing
+    /// fields from the placeholders between them
+    fn template(
+        constructor: TokenStream2,
+        fmt: &LitStr,
+        fields: &Fields,
+        case_insensitive: bool,
+    ) -> Result<Self> {
+        let field_fmts = FieldFmt::with_fields(fields)?;
+        let pieces = parse_format(fmt)?;
+
+        let mut steps = vec![];
+        let mut values: Vec<Option<Ident>> = vec![None; field_fmts.len()];
+        let mut iter = pieces.iter().peekable();
+        let mut first = true;
+        while let Some(piece) = iter.next() {
+            let Placeholder { arg, spec, range } = match piece {
+                Piece::Literal(lit) if first && case_insensitive => {
+                    let len = lit.len();
+                    steps.push(quote! {
+                        let s = match s.get(..#len) {
+                            Some(prefix) if prefix.eq_ignore_ascii_case(#lit) => &s[#len..],
+                            _ => return None,
+                        };
+                    });
+                    first = false;
+                    continue;
+                }
+                Piece::Literal(lit) => {
+                    steps.push(quote! { let s = s.strip_prefix(#lit)?; });
+                    first = false;
+                    continue;
+                }
+                Piece::Placeholder(placeholder) => placeholder,
+            };
+            first = false;
+
+            let location = Location::with(fmt, range.clone());
+            let index = FieldFmt::find(&field_fmts, arg).ok_or_else(|| {
+                let msg = match arg {
+                    Arg::Index(index) => format!(
+                        "format string references field at position {}, but there are only {} \
+                         fields",
+                        index,
+                        field_fmts.len()
+                    ),
+                    _ => "placeholder can't be parsed back: only fields may be used in format \
+                          strings of types deriving `FromStr`"
+                        .to_owned(),
+                };
+                location.error(msg)
+            })?;
+            let field = &field_fmts[index];
+            if !spec.is_empty() || field.attr.with.is_some() || field.attr.fmt_trait.is_some() {
+                return Err(location.error(format!(
+                    "field `{}` can't be parsed back since it uses custom formatting",
+                    field.name
+                )));
+            }
+            if values[index].is_some() {
+                return Err(location.error(format!(
+                    "field `{}` is used in format string multiple times and can't be \
+                         parsed back",
+                    field.name
+                )));
+            }
+
+            let value = Ident::new(&format!("__value{}", index), location.span);
+            match iter.peek() {
+                Some(Piece::Literal(lit)) => {
+                    steps.push(quote! {
+                        let (#value, s) = s.split_once(#lit)?;
+                    });
+                    // The separator is consumed by the split
+                    iter.next();
+                }
+                None => steps.push(quote! {
+                    let (#value, s) = (s, "");
+                }),
+                Some(Piece::Placeholder(_)) => {
+                    return Err(location.error(format!(
+                        "field `{}` is not separated from the next field in format string, \
+                             so it can't be parsed back",
+                        field.name
+                    )))
+                }
+            }
+            values[index] = Some(value);
+        }
+
+        let values = values
+            .into_iter()
+            .zip(&field_fmts)
+            .map(|(value, field)| match value {
+                Some(value) => Ok(quote! { #value.parse().ok()? }),
+                None => Err(Error::new(
+                    fmt.span(),
+                    format!(
+                        "field `{}` is not used in format string, so it can't be parsed back",
+                        field.name
+                    ),
+                )),
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let construct = match fields {
+            Fields::Named(_) => {
+                let names = field_fmts.iter().map(|field| &field.name);
+                quote! { #constructor { #( #names: #values, )* } }
+            }
+            Fields::Unnamed(_) => quote! { #constructor ( #( #values, )* ) },
+            Fields::Unit => constructor,
+        };
+
+        Ok(Parser {
+            expected: format!("`{}`", fmt.value()),
+            code: quote! {
+                let parsed = (|| -> Option<Self> {
+                    #( #steps )*
+                    if !s.is_empty() {
+                        return None;
+                    }
+                    Some(#construct)
+                })();
+                if let Some(parsed) = parsed {
+                    return Ok(parsed);
+                }
+            },
+        })
+    }
+
+    fn with(
+        constructor: TokenStream2,
+        name: &str,
+        fields: &Fields,
+        technique: Option<&Technique>,
+        span: Span,
+    ) -> Result<Self> {
+        match (technique, fields) {
+            (None, Fields::Unit) | (Some(Technique::Inner), Fields::Unit) => {
+                Ok(Parser::literal(constructor, name, false))
+            }
+            (None, _) => Err(Error::new(
+                span,
+                format!(
+                    "`{}` uses default display representation which can't be parsed back; \
+                     please provide `#[display(...)]` attribute",
+                    name
+                ),
+            )),
+            (Some(Technique::FromTrait(_)), _)
+            | (Some(Technique::FromMethod(_)), _)
+            | (Some(Technique::FromWriteMethod(_)), _) => Err(Error::new(
+                span,
+                format!(
+                    "`{}` is displayed with formatting trait or function and can't be \
+                         parsed back",
+                    name
+                ),
+            )),
+            (Some(Technique::Inner), fields) => {
+                let mut parser =
+                    Parser::template(constructor, &LitStr::new("{0}", span), fields, false)?;
+                let ty = &fields.iter().next().expect("template has a field").ty;
+                parser.expected = format!(
+                    "a string representation of the inner `{}`",
+                    quote! { #ty }.to_string().replace(' ', "")
+                );
+                Ok(parser)
+            }
+            (Some(Technique::Case(_, fmt)), Fields::Unit) => {
+                Ok(Parser::literal(constructor, fmt, true))
+            }
+            (Some(Technique::Case(_, fmt)), fields) => {
+                Parser::template(constructor, &LitStr::new(fmt, span), fields, true)
+            }
+            // Display writes format strings of unit types as they are
+            (Some(Technique::WithFormat(fmt)), Fields::Unit) => match format::literal(fmt)? {
+                Some(literal) => Ok(Parser::literal(constructor, &literal, false)),
+                None => Parser::template(constructor, fmt, fields, false),
+            },
+            (Some(Technique::DocComments(doc)), Fields::Unit) => {
+                Ok(Parser::literal(constructor, doc, false))
+            }
+            (Some(Technique::WithFormat(fmt)), fields) => {
+                Parser::template(constructor, fmt, fields, false)
+            }
+            (Some(Technique::DocComments(doc)), fields) => {
+                Parser::template(constructor, &LitStr::new(doc, span), fields, false)
+            }
+        }
+    }
+}
+
+pub(crate) fn inner(input: DeriveInput) -> Result<TokenStream2> {
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let ident_name = &input.ident;
+    let vis = &input.vis;
+    let mut attr = ParametrizedAttr::with("from_str", &input.attrs)?;
+    attr.check(AttrReq::with(HashMap::from_iter(vec![(
+        "error",
+        ArgValueReq::Optional(ValueClass::str()),
+    )])))?;
+    let error_name = match attr.args.get("error") {
+        Some(name) => {
+            let name: LitStr = name.clone().try_into()?;
+            name.parse()?
+        }
+        None => Ident::new(&format!("Parse{}Error", ident_name), ident_name.span()),
+    };
+
+    let parsers = match input.data {
+        Data::Struct(ref data) => {
+            let technique = struct_technique(&input, data)?;
+            vec![Parser::with(
+                quote! { Self },
+                &ident_name.to_string(),
+                &data.fields,
+                Some(&technique),
+                input.span(),
+            )?]
+        }
+        Data::Enum(ref data) => {
+            let techniques = EnumTechniques::with(&input, data)?;
+            if techniques.use_global && techniques.global.is_some() && data.variants.len() > 1 {
+                return Err(attr_err!(
+                    input.ident,
+                    "all enum variants share the same display representation, so `FromStr` \
+                     can't be derived"
+                ));
+            }
+            data.variants
+                .iter()
+                .zip(techniques.variants)
+                .map(|(v, technique)| {
+                    let variant_name = &v.ident;
+                    Parser::with(
+                        quote! { Self::#variant_name },
+                        &variant_name.to_string(),
+                        &v.fields,
+                        technique.as_ref(),
+                        v.span(),
+                    )
+                })
+                .collect::<Result<Vec<_>>>()?
+        }
+        Data::Union(_) => {
+            return Err(Error::new(
+                input.span(),
+                "Deriving `FromStr` is not supported for unions",
+            ))
+        }
+    };
+
+    let expected = parsers
+        .iter()
+        .map(|parser| parser.expected.as_str())
+        .collect::<Vec<_>>()
+        .join(", ");
+    let message = if parsers.len() == 1 {
+        format!(
+            "invalid string representation of `{}`; expected {}",
+            ident_name, expected
+        )
+    } else {
+        format!(
+            "invalid string representation of `{}`; expected one of: {}",
+            ident_name, expected
+        )
+    };
+    let doc = format!(" Error parsing string representation of [`{}`]", ident_name);
+    let code = parsers.iter().map(|parser| &parser.code);
+
+    let error_trait = Target::detect().error_trait();
+
+    Ok(quote! {
+        #[doc = #doc]
+        #[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Default)]
+        #vis struct #error_name;
+
+        impl ::core::fmt::Display for #error_name {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                f.write_str(#message)
+            }
+        }
+
+        impl #error_trait for #error_name {}
+
+        impl #impl_generics ::core::str::FromStr for #ident_name #ty_generics #where_clause {
+            type Err = #error_name;
+
+            #[allow(clippy::redundant_closure_call)]
+            fn from_str(s: &str) -> ::core::result::Result<Self, Self::Err> {
+                #( #code )*
+                Err(#error_name)
+            }
+        }
+    })
+}
@@ -20,11 +20,23 @@ use proc_macro2::{TokenStream as TokenStream2, Span, Ident};
 use quote::ToTokens;
 use syn::spanned::Spanned;
 use syn::{
-    Data, DeriveInput, Error, Fields, Result, LitStr, Attribute, DataStruct, ImplGenerics,
-    TypeGenerics, WhereClause, Field,
+    Data, DeriveInput, Error, Fields, Result, LitStr, Attribute, DataStruct, DataEnum,
+    ImplGenerics, TypeGenerics, WhereClause, Field, Member, Type, Path, Visibility, PathArguments,
+    GenericArgument,
 };
 
-use amplify_syn::{ParametrizedAttr, AttrReq, ArgValueReq, ArgValue, ValueClass};
+use amplify_syn::{ParametrizedAttr, AttrReq, ArgValueReq, ArgValue, ValueClass, TypeClass};
+
+/// Method derivation arguments; the ones given on the field level replace
+/// all of the type-level ones
+const METHOD_ARGS: [&str; 6] = [
+    "as_copy",
+    "as_clone",
+    "as_ref",
+    "as_deref",
+    "as_ref_opt",
+    "map",
+];
 
 pub(crate) fn derive(input: DeriveInput) -> Result<TokenStream2> {
     let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
@@ -42,10 +54,14 @@ pub(crate) fn derive(input: DeriveInput) -> Result<TokenStream2> {
             ty_generics,
             where_clause,
         ),
-        Data::Enum(_) => Err(Error::new_spanned(
-            &input,
-            "Deriving getters is not supported in enums",
-        )),
+        Data::Enum(data) => derive_enum_impl(
+            data,
+            struct_name,
+            global_param,
+            impl_generics,
+            ty_generics,
+            where_clause,
+        ),
         Data::Union(_) => Err(Error::new_spanned(
             &input,
             "Deriving getters is not supported in unions",
@@ -63,6 +79,12 @@ struct GetterDerive {
     pub main: Option<LitStr>,
     pub as_ref: Option<LitStr>,
     pub as_mut: Option<LitStr>,
+    pub as_deref: Option<LitStr>,
+    pub as_ref_opt: Option<LitStr>,
+    /// Function converting reference to the field value, together with the
+    /// type it returns
+    pub map: Option<(Path, Type)>,
+    pub vis: Visibility,
 }
 
 impl GetterDerive {
@@ -75,11 +97,20 @@ impl GetterDerive {
             ("as_clone", ArgValueReq::with_default("")),
             ("as_ref", ArgValueReq::with_default("")),
             ("as_mut", ArgValueReq::with_default("_mut")),
+            ("as_deref", ArgValueReq::with_default("")),
+            ("as_ref_opt", ArgValueReq::with_default("")),
+            ("vis", ArgValueReq::Optional(ValueClass::str())),
         ]);
 
         if !global {
+            map.insert(
+                "map",
+                ArgValueReq::Optional(ValueClass::Type(TypeClass::Path)),
+            );
+            map.insert("ret", ArgValueReq::Optional(ValueClass::str()));
             map.insert("skip", ArgValueReq::Prohibited);
             map.insert("base_name", ArgValueReq::Optional(ValueClass::str()));
+            map.insert("name", ArgValueReq::Optional(ValueClass::str()));
         }
 
         attr.check(AttrReq::with(map))?;
@@ -102,6 +133,13 @@ impl GetterDerive {
                 .insert("as_mut".to_owned(), ArgValue::from("_mut"));
         }
 
+        if attr.args.contains_key("base_name") && attr.args.contains_key("name") {
+            return Err(Error::new(
+                Span::call_site(),
+                "`name` is a synonym for `base_name`, so they can't be present together",
+            ));
+        }
+
         if attr.args.contains_key("as_clone") && attr.args.contains_key("as_copy") {
             return Err(Error::new(
                 Span::call_site(),
@@ -109,10 +147,21 @@ impl GetterDerive {
             ));
         }
 
-        // If we have to return copy or a clone of value and did not explicitly
-        // specified different prefix for borrowing accessor, we need not to derive it
-        // since we will have a naming conflict
-        if (attr.args.contains_key("as_clone") || attr.args.contains_key("as_copy"))
+        if attr.args.contains_key("map") != attr.args.contains_key("ret") {
+            return Err(Error::new(
+                Span::call_site(),
+                "`map` attribute requires the type returned by the function, like `map = \
+                 path, ret = \"Type\"`, and `ret` can't be used without `map`",
+            ));
+        }
+
+        // If we have to return copy or a clone of value (or some other form of
+        // it) and did not explicitly specified different prefix for borrowing
+        // accessor, we need not to derive it since we will have a naming
+        // conflict
+        if METHOD_ARGS
+            .iter()
+            .any(|arg| *arg != "as_ref" && attr.args.contains_key(*arg))
             && attr
                 .args
                 .get("as_ref")
@@ -128,12 +177,9 @@ impl GetterDerive {
             attr.args.remove("as_ref");
         }
 
-        // If we are not provided with any options, default to deriving borrows
-        if !(attr.args.contains_key("as_clone")
-            || attr.args.contains_key("as_copy")
-            || attr.args.contains_key("as_ref")
-            || attr.args.contains_key("as_mut"))
-        {
+        // If we are not provided with any options (other than `as_mut`, which
+        // is always added to the other methods), default to deriving borrows
+        if !METHOD_ARGS.iter().any(|arg| attr.args.contains_key(*arg)) {
             attr.args.insert("as_ref".to_owned(), ArgValue::from(""));
         }
 
@@ -149,6 +195,7 @@ impl GetterDerive {
             base: attr
                 .args
                 .get("base_name")
+                .or_else(|| attr.args.get("name"))
                 .map(|a| a.clone().try_into())
                 .transpose()?,
             main: attr
@@ -167,6 +214,32 @@ impl GetterDerive {
                 .get("as_mut")
                 .map(|a| a.clone().try_into())
                 .transpose()?,
+            as_deref: attr
+                .args
+                .get("as_deref")
+                .map(|a| a.clone().try_into())
+                .transpose()?,
+            as_ref_opt: attr
+                .args
+                .get("as_ref_opt")
+                .map(|a| a.clone().try_into())
+                .transpose()?,
+            map: match (attr.args.get("map"), attr.args.get("ret")) {
+                (Some(map), Some(ret)) => {
+                    let ret: LitStr = ret.clone().try_into()?;
+                    Some((map.clone().try_into()?, ret.parse()?))
+                }
+                _ => None,
+            },
+            vis: attr
+                .args
+                .get("vis")
+                .map(|a| -> Result<Visibility> {
+                    let vis: LitStr = a.clone().try_into()?;
+                    vis.parse()
+                })
+                .transpose()?
+                .unwrap_or_else(|| parse_quote! { pub }),
         })
     }
 }
@@ -176,6 +249,9 @@ enum GetterMethod {
     Main { copy: bool },
     AsRef,
     AsMut,
+    AsDeref,
+    AsRefOpt,
+    Map,
 }
 
 impl GetterMethod {
@@ -185,6 +261,9 @@ impl GetterMethod {
             GetterMethod::Main { copy: false } => "cloning",
             GetterMethod::AsRef => "borrowing",
             GetterMethod::AsMut => "returning mutable borrow of",
+            GetterMethod::AsDeref => "dereferencing",
+            GetterMethod::AsRefOpt => "returning optional borrow of",
+            GetterMethod::Map => "converting",
         }
     }
 
@@ -194,25 +273,111 @@ impl GetterMethod {
             GetterMethod::Main { copy: false } => quote! {},
             GetterMethod::AsRef => quote! {},
             GetterMethod::AsMut => quote! { mut },
+            _ => quote! {},
         }
     }
 
-    fn ret_prefix(&self) -> TokenStream2 {
+    /// Returns type returned by the method for a field of type `ty`
+    fn ret_ty(&self, getter: &GetterDerive, ty: &Type) -> Result<TokenStream2> {
+        Ok(match self {
+            GetterMethod::Main { .. } => quote! { #ty },
+            GetterMethod::AsRef => quote! { &#ty },
+            GetterMethod::AsMut => quote! { &mut #ty },
+            GetterMethod::AsDeref => match deref_target(ty) {
+                Some(target) => target,
+                None => quote! { &<#ty as ::core::ops::Deref>::Target },
+            },
+            GetterMethod::AsRefOpt => {
+                let inner = generic_arg(ty, "Option").ok_or_else(|| {
+                    Error::new(
+                        ty.span(),
+                        "`as_ref_opt` can be used only with `Option` fields",
+                    )
+                })?;
+                quote! { Option<&#inner> }
+            }
+            GetterMethod::Map => {
+                let (_, ret) = getter
+                    .map
+                    .as_ref()
+                    .expect("map method without map argument");
+                quote! { #ret }
+            }
+        })
+    }
+
+    /// Converts field value, given as a place expression, into the value
+    /// returned by the method
+    fn value(&self, getter: &GetterDerive, ty: &Type, place: TokenStream2) -> TokenStream2 {
         match self {
-            GetterMethod::Main { copy: true } => quote! {},
-            GetterMethod::Main { copy: false } => quote! {},
-            GetterMethod::AsRef => quote! { & },
-            GetterMethod::AsMut => quote! { &mut },
+            GetterMethod::Main { copy: true } => quote! { #place },
+            GetterMethod::Main { copy: false } => quote! { #place.clone() },
+            GetterMethod::AsRef => quote! { &#place },
+            GetterMethod::AsMut => quote! { &mut #place },
+            GetterMethod::AsDeref if generic_arg(ty, "Option").is_some() => {
+                quote! { #place.as_ref() }
+            }
+            GetterMethod::AsDeref => quote! { ::core::ops::Deref::deref(&#place) },
+            GetterMethod::AsRefOpt => quote! { #place.as_ref() },
+            GetterMethod::Map => {
+                let (map, _) = getter
+                    .map
+                    .as_ref()
+                    .expect("map method without map argument");
+                quote! { #map(&#place) }
+            }
         }
     }
 
-    fn ret_suffix(&self) -> TokenStream2 {
+    /// Converts reference to the field value, bound by a `match` pattern,
+    /// into the returned value
+    fn binding_value(&self, getter: &GetterDerive, ty: &Type, value: &Ident) -> TokenStream2 {
         match self {
-            GetterMethod::Main { copy: true } => quote! {},
-            GetterMethod::Main { copy: false } => quote! { .clone() },
-            GetterMethod::AsRef => quote! {},
-            GetterMethod::AsMut => quote! {},
+            GetterMethod::Main { copy: true } => quote! { *#value },
+            GetterMethod::Main { copy: false } => quote! { #value.clone() },
+            GetterMethod::AsRef => quote! { #value },
+            GetterMethod::AsMut => quote! { #value },
+            _ => self.value(getter, ty, quote! { (*#value) }),
+        }
+    }
+}
+
+/// Returns the only generic type argument of a type if its name (the last
+/// path segment) matches `name`, like `T` for `Option<T>`
+fn generic_arg<'ty>(ty: &'ty Type, name: &str) -> Option<&'ty Type> {
+    let segment = match ty {
+        Type::Path(path) if path.qself.is_none() => path.path.segments.last()?,
+        _ => return None,
+    };
+    if segment.ident != name {
+        return None;
+    }
+    match &segment.arguments {
+        PathArguments::AngleBracketed(args) if args.args.len() == 1 => match &args.args[0] {
+            GenericArgument::Type(ty) => Some(ty),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Returns readable form of the type returned by `as_deref` method for the
+/// well-known types
+fn deref_target(ty: &Type) -> Option<TokenStream2> {
+    if let Some(inner) = generic_arg(ty, "Option") {
+        return Some(quote! { Option<&#inner> });
+    }
+    if let Some(inner) = generic_arg(ty, "Vec") {
+        return Some(quote! { &[#inner] });
+    }
+    if let Some(inner) = generic_arg(ty, "Box") {
+        return Some(quote! { &#inner });
+    }
+    match ty {
+        Type::Path(path) if path.qself.is_none() && path.path.is_ident("String") => {
+            Some(quote! { &str })
         }
+        _ => None,
     }
 }
 
@@ -228,6 +393,15 @@ impl GetterDerive {
         if self.as_mut.is_some() {
             methods.push(GetterMethod::AsMut);
         }
+        if self.as_deref.is_some() {
+            methods.push(GetterMethod::AsDeref);
+        }
+        if self.as_ref_opt.is_some() {
+            methods.push(GetterMethod::AsRefOpt);
+        }
+        if self.map.is_some() {
+            methods.push(GetterMethod::Map);
+        }
         methods
     }
 
@@ -244,40 +418,67 @@ impl GetterDerive {
             .or_else(|| field_name.map(Ident::to_string))
             .ok_or_else(|| {
                 Error::new(
-                span,
-                "Unnamed fields must be equipped with `#[getter(base_name = \"name\"]` attribute",
-            )
+                    span,
+                    "Unnamed fields must be equipped with `#[getter(name = \"name\")]` attribute",
+                )
             })?;
 
-        let name_lit = match method {
+        let suffix = match method {
             GetterMethod::Main { .. } => &self.main,
             GetterMethod::AsRef => &self.as_ref,
             GetterMethod::AsMut => &self.as_mut,
+            GetterMethod::AsDeref => &self.as_deref,
+            GetterMethod::AsRefOpt => &self.as_ref_opt,
+            GetterMethod::Map => {
+                return Ok(Ident::new(
+                    &format!("{}{}", self.prefix.value(), base_string),
+                    span,
+                ))
+            }
         }
-        .clone()
+        .as_ref()
+        .map(LitStr::value)
         .expect("Internal inconsistency in getter derivation macro implementation");
 
-        let s = format!("{}{}{}", self.prefix.value(), base_string, name_lit.value());
+        let s = format!("{}{}{}", self.prefix.value(), base_string, suffix);
 
         Ok(Ident::new(&s, span))
     }
 
+    /// Returns all methods which has to be derived together with their names,
+    /// checking that the names do not collide
+    pub fn named_methods(
+        &self,
+        field_name: Option<&Ident>,
+        span: Span,
+    ) -> Result<Vec<(GetterMethod, Ident)>> {
+        let mut methods: Vec<(GetterMethod, Ident)> = vec![];
+        for method in self.all_methods() {
+            let name = self.getter_fn_ident(method, field_name, span)?;
+            if methods.iter().any(|(_, other)| *other == name) {
+                return Err(Error::new(
+                    span,
+                    format!(
+                        "several getter methods are named `{}`; please provide different \
+                         suffixes for them, like `as_ref = \"_ref\"`",
+                        name
+                    ),
+                ));
+            }
+            methods.push((method, name));
+        }
+        Ok(methods)
+    }
+
+    /// Generates method documentation; `subject` describes the field, like
+    /// "[`Struct::field`] field"
     pub fn getter_fn_doc(
         &self,
         method: GetterMethod,
-        struct_name: &Ident,
-        field_name: Option<&Ident>,
-        field_index: usize,
+        subject: &str,
         field_doc: Option<&Attribute>,
     ) -> TokenStream2 {
-        let fn_doc = format!(
-            "Method {} [`{}::{}`] field.\n",
-            method.doc_phrase(),
-            struct_name,
-            field_name
-                .map(Ident::to_string)
-                .unwrap_or_else(|| field_index.to_string())
-        );
+        let fn_doc = format!("Method {} {}.\n", method.doc_phrase(), subject);
 
         if let Some(field_doc) = field_doc {
             quote! {
@@ -302,8 +503,8 @@ fn derive_struct_impl(
 ) -> Result<TokenStream2> {
     let mut methods = Vec::with_capacity(data.fields.len());
     match data.fields {
-        Fields::Named(ref fields) => {
-            for (index, field) in fields.named.iter().enumerate() {
+        Fields::Named(_) | Fields::Unnamed(_) => {
+            for (index, field) in data.fields.iter().enumerate() {
                 methods.extend(derive_field_methods(
                     field,
                     index,
@@ -312,12 +513,6 @@ fn derive_struct_impl(
                 )?)
             }
         }
-        Fields::Unnamed(_) => {
-            return Err(Error::new(
-                Span::call_site(),
-                "Deriving getters is not supported for tuple-bases structs",
-            ))
-        }
         Fields::Unit => {
             return Err(Error::new(
                 Span::call_site(),
@@ -333,63 +528,215 @@ fn derive_struct_impl(
     })
 }
 
-fn derive_field_methods(
-    field: &Field,
-    index: usize,
-    struct_name: &Ident,
-    global_param: &ParametrizedAttr,
-) -> Result<Vec<TokenStream2>> {
-    let mut local_param = ParametrizedAttr::with("getter", &field.attrs)?;
+/// Combines type-level getter parameters with the parameters of the field
+fn field_getter(field: &Field, global_param: &ParametrizedAttr) -> Result<GetterDerive> {
+    let local_param = ParametrizedAttr::with("getter", &field.attrs)?;
 
+    // Method arguments given on the field level override the type-level ones,
+    // so we need to know whether they were given before the defaults are
+    // applied
+    let overrides = local_param
+        .args
+        .keys()
+        .cloned()
+        .chain(
+            local_param
+                .paths
+                .iter()
+                .filter_map(|path| path.get_ident().map(Ident::to_string)),
+        )
+        .any(|arg| arg == "all" || METHOD_ARGS.contains(&arg.as_str()));
     // First, test individual attribute
-    let _ = GetterDerive::try_from(&mut local_param, false)?;
+    let mut checked_param = local_param.clone();
+    let _ = GetterDerive::try_from(&mut checked_param, false)?;
     // Second, combine global and local together
-    let mut local_args = local_param.args.clone();
     let mut params = global_param.clone().merged(local_param)?;
-    if local_args
-        .keys()
-        .any(|k| k == "as_copy" || k == "as_clone" || k == "as_ref")
-    {
+    if overrides {
         // we have to use local arguments since they do override globals
-        params.args.remove("as_copy");
-        params.args.remove("as_clone");
-        params.args.remove("as_ref");
-        local_args
-            .remove("as_copy")
-            .map(|a| params.args.insert("as_copy".to_owned(), a));
-        local_args
-            .remove("as_clone")
-            .map(|a| params.args.insert("as_clone".to_owned(), a));
-        local_args
-            .remove("as_ref")
-            .map(|a| params.args.insert("as_ref".to_owned(), a));
+        params.paths.retain(|path| !path.is_ident("all"));
+        for arg in METHOD_ARGS.iter().chain(&["ret"]) {
+            params.args.remove(*arg);
+        }
+        // `as_mut` is not overridden, but may come from `all`
+        for arg in METHOD_ARGS.iter().chain(&["ret", "as_mut"]) {
+            if let Some(value) = checked_param.args.get(*arg) {
+                params.args.insert((*arg).to_owned(), value.clone());
+            }
+        }
     }
-    let getter = GetterDerive::try_from(&mut params, false)?;
+    GetterDerive::try_from(&mut params, false)
+}
+
+fn derive_field_methods(
+    field: &Field,
+    index: usize,
+    struct_name: &Ident,
+    global_param: &ParametrizedAttr,
+) -> Result<Vec<TokenStream2>> {
+    let getter = field_getter(field, global_param)?;
 
     if getter.skip {
         return Ok(Vec::new());
     }
 
     let field_name = field.ident.as_ref();
+    let member = field
+        .ident
+        .clone()
+        .map_or_else(|| Member::from(index), Member::Named);
+    let subject = match field_name {
+        Some(name) => format!("[`{}::{}`] field", struct_name, name),
+        None => format!("field `{}` of [`{}`]", index, struct_name),
+    };
     let ty = &field.ty;
     let doc = field.attrs.iter().find(|a| a.path.is_ident("doc"));
 
+    let vis = &getter.vis;
+
     let mut res = Vec::with_capacity(3);
-    for method in getter.all_methods() {
-        let fn_name = getter.getter_fn_ident(method, field_name, field.span())?;
-        let fn_doc = getter.getter_fn_doc(method, struct_name, field_name, index, doc);
-        let ret_prefix = method.ret_prefix();
-        let ret_suffix = method.ret_suffix();
+    for (method, fn_name) in getter.named_methods(field_name, field.span())? {
+        let fn_doc = getter.getter_fn_doc(method, &subject, doc);
+        let ret_ty = method.ret_ty(&getter, ty)?;
+        let value = method.value(&getter, ty, quote! { self.#member });
         let mut_prefix = method.mut_prefix();
 
         res.push(quote_spanned! { field.span() =>
             #fn_doc
             #[inline]
-            pub fn #fn_name(&#mut_prefix self) -> #ret_prefix #ty {
-                #ret_prefix self.#field_name#ret_suffix
+            #vis fn #fn_name(&#mut_prefix self) -> #ret_ty {
+                #value
             }
         })
     }
 
     Ok(res)
 }
+
+/// Field which is present in one or more enum variants under the same name
+struct EnumField {
+    /// Field name or the name given with `#[getter(name = "...")]`
+    name: Ident,
+    ty: Type,
+    /// Getter parameters taken from the first occurrence of the field
+    getter: GetterDerive,
+    doc: Option<Attribute>,
+    /// Variants containing the field together with the field member
+    variants: Vec<(Ident, Member)>,
+}
+
+fn derive_enum_impl(
+    data: DataEnum,
+    enum_name: &Ident,
+    global_param: ParametrizedAttr,
+    impl_generics: ImplGenerics,
+    ty_generics: TypeGenerics,
+    where_clause: Option<&WhereClause>,
+) -> Result<TokenStream2> {
+    let mut fields: Vec<EnumField> = vec![];
+    for variant in &data.variants {
+        for (index, field) in variant.fields.iter().enumerate() {
+            let getter = field_getter(field, &global_param)?;
+            if getter.skip {
+                continue;
+            }
+            // Unnamed fields get getters only if they are named explicitly
+            let name = match (&getter.base, &field.ident) {
+                (Some(name), _) => Ident::new(&name.value(), name.span()),
+                (None, Some(ident)) => ident.clone(),
+                (None, None) => continue,
+            };
+            let member = field
+                .ident
+                .clone()
+                .map_or_else(|| Member::from(index), Member::Named);
+            match fields.iter_mut().find(|f| f.name == name) {
+                Some(existing) => {
+                    if existing.ty.to_token_stream().to_string()
+                        != field.ty.to_token_stream().to_string()
+                    {
+                        return Err(Error::new(
+                            field.span(),
+                            format!(
+                                "field `{}` has different types in enum variants, so getters \
+                                 can't be derived for it",
+                                name
+                            ),
+                        ));
+                    }
+                    existing.variants.push((variant.ident.clone(), member));
+                }
+                None => fields.push(EnumField {
+                    name,
+                    ty: field.ty.clone(),
+                    doc: field.attrs.iter().find(|a| a.path.is_ident("doc")).cloned(),
+                    getter,
+                    variants: vec![(variant.ident.clone(), member)],
+                }),
+            }
+        }
+    }
+
+    let mut methods = vec![];
+    for field in fields {
+        let partial = field.variants.len() < data.variants.len();
+        let subject = format!("`{}` field of [`{}`] variants", field.name, enum_name);
+        let value = Ident::new("value", Span::call_site());
+        let patterns = field
+            .variants
+            .iter()
+            .map(|(variant, member)| match member {
+                Member::Named(ident) => quote! { Self::#variant { #ident: #value, .. } },
+                Member::Unnamed(index) => {
+                    let skipped = (0..index.index).map(|_| quote! { _ });
+                    quote! { Self::#variant( #( #skipped, )* #value, .. ) }
+                }
+            })
+            .collect::<Vec<_>>();
+        let ty = &field.ty;
+        let getter = &field.getter;
+
+        for (method, fn_name) in getter.named_methods(Some(&field.name), field.name.span())? {
+            let mut fn_doc = getter.getter_fn_doc(method, &subject, field.doc.as_ref());
+            let ret_ty = method.ret_ty(getter, ty)?;
+            let mut_prefix = method.mut_prefix();
+            let ret_value = method.binding_value(getter, ty, &value);
+            let body = if partial {
+                fn_doc.extend(quote! {
+                    #[doc = "\nReturns `None` for the variants which do not have the field."]
+                });
+                quote! {
+                    match self {
+                        #( #patterns => Some(#ret_value), )*
+                        _ => None,
+                    }
+                }
+            } else {
+                quote! {
+                    match self {
+                        #( #patterns => #ret_value, )*
+                    }
+                }
+            };
+            let ret_ty = if partial {
+                quote! { Option<#ret_ty> }
+            } else {
+                ret_ty
+            };
+            let vis = &getter.vis;
+
+            methods.push(quote_spanned! { field.name.span() =>
+                #fn_doc
+                #[inline]
+                #vis fn #fn_name(&#mut_prefix self) -> #ret_ty {
+                    #body
+                }
+            })
+        }
+    }
+
+    Ok(quote! {
+        impl #impl_generics #enum_name #ty_generics #where_clause {
+            #( #methods )*
+        }
+    })
+}
@@ -39,10 +39,17 @@ extern crate proc_macro;
 mod util;
 
 mod as_any;
+mod bounds;
+mod builder;
 mod display;
+mod enum_meta;
 mod error;
+mod format;
 mod from;
+mod from_str;
 mod getters;
+mod setters;
+mod strict_encoding;
 mod wrapper;
 
 use proc_macro::TokenStream;
@@ -96,9 +103,65 @@ use syn::DeriveInput;
 ///     fn some_fmt(_: &Enum) -> String { s!("Some") }
 ///     assert_eq!(format!("{}", Enum::Once(3)), s!("Some"))
 ///    ```
-///    Formatting function must return [`String`] and take a single `self`
-///    argument (if you need formatting with streamed output, use one of
-///    existing formatting traits as shown in pt. 1).
+///    Formatting function must take a single `self` argument and return any
+///    type implementing [`Display`], like [`String`], `&str` or
+///    `impl Display`:
+///    ```
+///     # #[macro_use] extern crate amplify_derive;
+///     use std::fmt::{self, Display, Formatter};
+///
+///     #[derive(Display)]
+///     #[display(Table::summary)]
+///     struct Table { rows: Vec<u32> }
+///
+///     impl Table {
+///         fn summary(&self) -> impl Display + '_ {
+///             self.rows.len()
+///         }
+///     }
+///
+///     assert_eq!(Table { rows: vec![1, 2, 3] }.to_string(), "3");
+///    ```
+///    If the output should be streamed into the formatter without any
+///    intermediary values, use method with `fn(&self, &mut Formatter) ->
+///    fmt::Result` signature, provided with `fmt` argument:
+///    ```
+///     # #[macro_use] extern crate amplify_derive;
+///     use std::fmt::{self, Formatter};
+///
+///     #[derive(Display)]
+///     #[display(fmt = Table::write_rows)]
+///     struct Table { rows: Vec<u32> }
+///
+///     impl Table {
+///         fn write_rows(&self, f: &mut Formatter) -> fmt::Result {
+///             for row in &self.rows {
+///                 writeln!(f, "| {:>4} |", row)?;
+///             }
+///             Ok(())
+///         }
+///     }
+///
+///     #[derive(Display)]
+///     enum Report {
+///         #[display(fmt = Self::write_table)]
+///         Table(Table),
+///         Empty,
+///     }
+///
+///     impl Report {
+///         fn write_table(&self, f: &mut Formatter) -> fmt::Result {
+///             match self {
+///                 Report::Table(table) => table.write_rows(f),
+///                 Report::Empty => Ok(()),
+///             }
+///         }
+///     }
+///
+///     let table = Table { rows: vec![1, 20] };
+///     assert_eq!(table.to_string(), "|    1 |\n|   20 |\n");
+///     assert_eq!(Report::Table(table).to_string(), "|    1 |\n|   20 |\n");
+///    ```
 /// 3. Custom format string:
 ///    ```
 ///     # #[macro_use] extern crate amplify_derive;
@@ -116,6 +179,34 @@ use syn::DeriveInput;
 ///     assert_eq!(format!("{}", Point { x: 0, y: 1 }), "(0, 1)");
 ///     assert_eq!(format!("{:#}", Point { x: 0, y: 1 }), "0:1");
 ///    ```
+///    Further alternatives are selected by other formatter flags: `verbose`
+///    format is used when precision is given (like in `{:.1}`), and `plus`
+///    – with the `+` flag (`{:+}`). The default format may also be given
+///    as `short`, and `long` is a synonym for `alt`. Alternatives may be
+///    combined with any other technique, not only format strings:
+///    ```
+///     # #[macro_use] extern crate amplify_derive;
+///     #[derive(Debug, Display)]
+///     #[display(
+///         short = "{name}",
+///         long = "{name} <{email}>",
+///         verbose = "{name} <{email}>, id {id}",
+///         plus = "+{name}"
+///     )]
+///     struct User { name: &'static str, email: &'static str, id: u32 }
+///
+///     #[derive(Debug, Display)]
+///     #[display(Debug, alt = "user #{0}")]
+///     struct UserId(u32);
+///
+///     let user = User { name: "alice", email: "a@b.c", id: 7 };
+///     assert_eq!(format!("{}", user), "alice");
+///     assert_eq!(format!("{:#}", user), "alice <a@b.c>");
+///     assert_eq!(format!("{:.1}", user), "alice <a@b.c>, id 7");
+///     assert_eq!(format!("{:+}", user), "+alice");
+///     assert_eq!(format!("{}", UserId(7)), "UserId(7)");
+///     assert_eq!(format!("{:#}", UserId(7)), "user #7");
+///    ```
 /// 5. Use of doc comments for descrition representation. In this case doc
 ///    comments may also contain formatting like in the case 3:
 ///    ```
@@ -166,7 +257,8 @@ use syn::DeriveInput;
 ///     #[display(doc_comments)]
 ///     pub struct UnitStruct;
 ///    ```
-/// 7. Print the name of enum variant in lowercase/uppercase:
+/// 7. Print the name of enum variant converted to some case style, like
+///    lowercase/uppercase:
 ///    ```
 ///     # #[macro_use] extern crate amplify_derive;
 ///     #[derive(Display)]
@@ -197,6 +289,99 @@ use syn::DeriveInput;
 ///     assert_eq!(format!("{}", Event::Load(Message::ChangeColor(0, 255, 0))),
 ///         "LOAD(changecolor(0, 255, 0))");
 ///    ```
+///    Other supported case styles are `snake_case`, `kebab_case`,
+///    `camel_case`, `pascal_case`, `screaming_snake_case` and `title_case`;
+///    they may be given at the enum or variant level. Style names are
+///    case-insensitive, so `#[display(KebabCase)]` works as well:
+///    ```
+///     # #[macro_use] extern crate amplify_derive;
+///     #[derive(Display)]
+///     #[display(kebab_case)]
+///     enum Command {
+///         ListAll,
+///         HTTPServer { port: u16 },
+///         #[display(screaming_snake_case)]
+///         DropDatabase,
+///         #[display(title_case)]
+///         ShowVersion,
+///         #[display(camel_case)]
+///         ExportToJson,
+///     }
+///
+///     assert_eq!(Command::ListAll.to_string(), "list-all");
+///     assert_eq!(
+///         Command::HTTPServer { port: 80 }.to_string(),
+///         "http-server { port: 80 }"
+///     );
+///     assert_eq!(Command::DropDatabase.to_string(), "DROP_DATABASE");
+///     assert_eq!(Command::ShowVersion.to_string(), "Show Version");
+///     assert_eq!(Command::ExportToJson.to_string(), "exportToJson");
+///    ```
+/// 8. Field-level parameters: formatting trait used for the field when the
+///    format string does not specify one, function converting field value
+///    into a displayable type (`with`), and fields excluded from the display
+///    (`skip`), which are also omitted by the lowercase/uppercase styles:
+///    ```
+///     # #[macro_use] extern crate amplify_derive;
+///     #[macro_use] extern crate amplify;
+///
+///     fn describe_keys(keys: &[u8]) -> String {
+///         keys.iter().map(u8::to_string).collect::<Vec<_>>().join("+")
+///     }
+///
+///     #[derive(Display)]
+///     #[display("{name} #{id}, keys {keys}")]
+///     struct Account {
+///         name: String,
+///         #[display(LowerHex)]
+///         id: u32,
+///         #[display(with = describe_keys)]
+///         keys: Vec<u8>,
+///     }
+///
+///     #[derive(Display)]
+///     #[display(lowercase)]
+///     enum Event {
+///         Login { user: String, #[display(skip)] password: String },
+///     }
+///
+///     let account = Account { name: s!("alice"), id: 0xdead, keys: vec![1, 2] };
+///     assert_eq!(format!("{}", account), "alice #dead, keys 1+2");
+///     let event = Event::Login { user: s!("bob"), password: s!("secret") };
+///     assert_eq!(format!("{}", event), "login { user: bob }");
+///    ```
+/// 9. Format strings may contain expressions over the fields (referenced by
+///    their names, `_0`, `_1` for the tuple fields) and `self`:
+///    ```
+///     # #[macro_use] extern crate amplify_derive;
+///     #[macro_use] extern crate amplify;
+///
+///     #[derive(Display)]
+///     #[display("{name}: {keys.len()} keys, {self.status()}")]
+///     struct Account {
+///         name: String,
+///         keys: Vec<u8>,
+///     }
+///
+///     impl Account {
+///         fn status(&self) -> &'static str {
+///             if self.keys.is_empty() { "inactive" } else { "active" }
+///         }
+///     }
+///
+///     #[derive(Display)]
+///     enum Data {
+///         #[display("{_0.len()} bytes")]
+///         Bytes(Vec<u8>),
+///         #[display("{_0:?}")]
+///         Other(Option<u8>),
+///     }
+///
+///     let account = Account { name: s!("alice"), keys: vec![1, 2] };
+///     assert_eq!(format!("{}", account), "alice: 2 keys, active");
+///     assert_eq!(format!("{}", Data::Bytes(vec![0; 4])), "4 bytes");
+///     assert_eq!(format!("{}", Data::Other(None)), "None");
+///    ```
 /// # Example
 ///
 /// Advanced use with enums:
@@ -262,6 +447,39 @@ use syn::DeriveInput;
 /// assert_eq!(format!("{}", Tuple(5)), format!("{}", Tuple2(5)))
 /// ```
 ///
+/// Format strings follow the syntax of [`std::fmt`]: fields may be referenced
+/// by position (explicitly or with `{}`), and other fields may provide
+/// width and precision for the value, while doubled braces are printed as is:
+/// ```
+/// # #[macro_use] extern crate amplify_derive;
+/// #[derive(Clone, Copy, Debug, Display)]
+/// #[display("{{{}: {:>2$.3$}}}")]
+/// struct Aligned(&'static str, f32, usize, usize);
+///
+/// assert_eq!(Aligned("pi", 3.14159, 7, 2).to_string(), "{pi:    3.14}");
+///
+/// #[derive(Clone, Copy, Debug, Display)]
+/// #[display("{0}..{10}")]
+/// struct Wide(u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8);
+///
+/// assert_eq!(Wide(0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10).to_string(), "0..10");
+///
+/// #[derive(Clone, Copy, Debug, Display)]
+/// #[display("{hex:08x} {hex:#010x} {float:.2e} {float:>width$.1}")]
+/// struct Numbers {
+///     hex: u32,
+///     float: f64,
+///     width: usize,
+/// }
+///
+/// let numbers = Numbers {
+///     hex: 0xbeef,
+///     float: 1234.5,
+///     width: 8,
+/// };
+/// assert_eq!(numbers.to_string(), "0000beef 0x0000beef 1.23e3   1234.5");
+/// ```
+///
 /// Using inner enum variant representation, defaulting to the variant name
 /// if the variant does not have inner data:
 /// ```
@@ -284,6 +502,32 @@ use syn::DeriveInput;
 ///     "127.0.0.1"
 /// );
 /// ```
+///
+/// Generic types get trait bounds for the types of the fields which are
+/// formatted, depending on the formatting traits used by the placeholders.
+/// The inferred bounds may be replaced with `bound` argument:
+/// ```
+/// # #[macro_use] extern crate amplify_derive;
+/// use std::fmt::Debug;
+///
+/// #[derive(Clone, Debug, Display)]
+/// #[display("{key} = {value:?}")]
+/// struct Entry<K, V> {
+///     key: K,
+///     value: Vec<V>,
+/// }
+///
+/// #[derive(Clone, Debug, Display)]
+/// #[display(Self::method, bound = "T: Debug")]
+/// struct Custom<T>(T);
+///
+/// impl<T: Debug> Custom<T> {
+///     fn method(&self) -> String { format!("custom {:?}", self.0) }
+/// }
+///
+/// assert_eq!(Entry { key: "a", value: vec![1, 2] }.to_string(), "a = [1, 2]");
+/// assert_eq!(Custom("b").to_string(), "custom \"b\"");
+/// ```
 #[proc_macro_derive(Display, attributes(display))]
 pub fn derive_display(input: TokenStream) -> TokenStream {
     let derive_input = parse_macro_input!(input as DeriveInput);
@@ -292,11 +536,119 @@ pub fn derive_display(input: TokenStream) -> TokenStream {
         .into()
 }
 
+/// Derives [`FromStr`] parsing exactly the strings produced by `Display`
+/// derive macro from the same `#[display(...)]` attributes:
+/// - unit structs and enum variants are parsed from their display
+///   representations; names converted into some case style (like
+///   `#[display(kebab_case)]`) are matched case-insensitively;
+/// - `#[display(inner)]` delegates parsing to [`FromStr`] implementation of
+///   the inner type;
+/// - format strings like `#[display("{x}:{y}")]` are split on their literal
+///   parts, and each of the fields is parsed with its own [`FromStr`]
+///   implementation. All fields must be used in the format string.
+///
+/// Types which can't be parsed back (ones using formatting traits and
+/// functions, expressions in format strings, or fields which are not
+/// separated by literals, or fields not used in the format string) produce
+/// compile-time error.
+///
+/// The macro also generates `Parse{Type}Error` error type, with the same
+/// visibility as the type itself, which lists all valid representations. The
+/// name of the error type may be changed with `#[from_str(error = "Name")]`
+/// attribute. The error type is a copyable unit struct shared by all the
+/// variants, so errors returned by the [`FromStr`] implementations of the
+/// fields and inner types are not kept as its source; types which need them
+/// should implement [`FromStr`] manually.
+///
+/// # Example
+///
+/// ```
+/// # #[macro_use] extern crate amplify_derive;
+/// use std::str::FromStr;
+///
+/// #[derive(Clone, Copy, PartialEq, Eq, Debug, Display, FromStr)]
+/// #[display(kebab_case)]
+/// enum Command {
+///     ListAll,
+///     #[display("run:{_0}")]
+///     Run(u16),
+///     #[display(inner)]
+///     Point(Point),
+/// }
+///
+/// #[derive(Clone, Copy, PartialEq, Eq, Debug, Display, FromStr)]
+/// #[display("{x}:{y}")]
+/// struct Point {
+///     x: i32,
+///     y: i32,
+/// }
+///
+/// for cmd in [Command::ListAll, Command::Run(8), Command::Point(Point { x: 1, y: -1 })] {
+///     assert_eq!(Command::from_str(&cmd.to_string()), Ok(cmd));
+/// }
+/// assert_eq!(Command::from_str("LIST-ALL"), Ok(Command::ListAll));
+/// assert_eq!(
+///     Command::from_str("list").unwrap_err().to_string(),
+///     "invalid string representation of `Command`; expected one of: `list-all`, \
+///      `run:{_0}`, a string representation of the inner `Point`"
+/// );
+/// assert_eq!(Point::from_str("4:2"), Ok(Point { x: 4, y: 2 }));
+/// assert_eq!(Point::from_str("4:2:"), Err(ParsePointError));
+///
+/// #[derive(Clone, Copy, PartialEq, Eq, Debug, Display, FromStr)]
+/// #[display(inner)]
+/// struct Port(u16);
+///
+/// assert_eq!(Port::from_str("8080"), Ok(Port(8080)));
+/// assert_eq!(
+///     Port::from_str("http").unwrap_err().to_string(),
+///     "invalid string representation of `Port`; expected a string representation of the \
+///      inner `u16`"
+/// );
+///
+/// #[derive(Clone, Copy, PartialEq, Eq, Debug, Display, FromStr)]
+/// #[display("{0}%")]
+/// #[from_str(error = "InvalidPercent")]
+/// struct Percent(u8);
+///
+/// assert_eq!(Percent::from_str("42%"), Ok(Percent(42)));
+/// assert_eq!(Percent::from_str("42"), Err(InvalidPercent));
+/// ```
+///
+/// Fields which are not used in the format string can't be parsed back:
+/// ```compile_fail
+/// # #[macro_use] extern crate amplify_derive;
+/// #[derive(Display, FromStr)]
+/// #[display("{x}")]
+/// struct Point {
+///     x: i32,
+///     y: i32,
+/// }
+/// ```
+///
+/// [`FromStr`]: core::str::FromStr
+#[proc_macro_derive(FromStr, attributes(display, from_str))]
+pub fn derive_from_str(input: TokenStream) -> TokenStream {
+    let derive_input = parse_macro_input!(input as DeriveInput);
+    from_str::inner(derive_input)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
+
 /// Error derive macro works to the full extend only when other derive macros
 /// are used. With `#[derive(Display)]` and `[display(doc_comments)]` it uses
 /// doc comments for generating error descriptions; with `#[derive(From)]` it
 /// may automatically implement transofrations from other error types.
 ///
+/// By default the macro implements [`std::error::Error`] and conversion of the
+/// error into [`String`]. With `std` feature of `amplify_derive` crate turned
+/// off the macro implements `core::error::Error` (requires rust 1.81) instead,
+/// making it usable in `no_std` crates; the conversion into `String` is
+/// generated only if `alloc` feature is enabled (the crate using the derive
+/// must declare `extern crate alloc` in this case). When the macro is used
+/// through `amplify` crate, these features follow `std` and `alloc` features
+/// of `amplify`.
+///
 /// # Example
 ///
 /// ```
@@ -319,7 +671,130 @@ pub fn derive_display(input: TokenStream) -> TokenStream {
 ///     "Zero division with 2"
 /// );
 /// ```
-#[proc_macro_derive(Error)]
+///
+/// # Error sources
+///
+/// [`std::error::Error::source`] is implemented returning the field marked
+/// with `#[source]` or `#[error(source)]` attribute. Enum variants having a
+/// single field may be marked with the attribute as a whole. Without the
+/// attributes, a field named `source` is used, unless it has a type which is
+/// known not to be an error (strings, primitives, `Vec`, `Option`, arrays,
+/// slices and tuples). Variants without a source field return `None`. Boxed
+/// trait objects (like `Box<dyn Error + Send + Sync>`) are dereferenced and
+/// may be used as a source as well.
+///
+/// `#[error(from)]` marks the error source and additionally implements
+/// [`From`] conversion from the source type; the field must be the only one
+/// in the struct or enum variant. For types with other fields use `#[from]`
+/// attribute of `#[derive(From)]`, which fills them with [`Default`] values.
+///
+/// ```
+/// # #[macro_use] extern crate amplify_derive;
+/// use std::error::Error as StdError;
+///
+/// #[derive(Debug, Display, Error)]
+/// #[display("config error")]
+/// struct ConfigError {
+///     path: String,
+///     source: std::io::Error,
+/// }
+///
+/// #[derive(Debug, Display, Error)]
+/// #[display(doc_comments)]
+/// enum Error {
+///     /// I/O error
+///     #[error(from)]
+///     Io(std::io::Error),
+///
+///     /// configuration error in line {line}
+///     Config {
+///         line: usize,
+///         #[error(source)]
+///         cause: ConfigError,
+///     },
+///
+///     /// number parsing error
+///     Number(#[source] std::num::ParseIntError),
+///
+///     /// math overflow
+///     Overflow,
+/// }
+///
+/// let io = || std::io::Error::new(std::io::ErrorKind::Other, "disk failure");
+/// let err = Error::Config {
+///     line: 1,
+///     cause: ConfigError {
+///         path: "amplify.toml".to_string(),
+///         source: io(),
+///     },
+/// };
+/// assert_eq!(err.source().unwrap().to_string(), "config error");
+/// assert_eq!(
+///     err.source().unwrap().source().unwrap().to_string(),
+///     "disk failure"
+/// );
+/// assert_eq!(Error::from(io()).source().unwrap().to_string(), "disk failure");
+/// let number = Error::Number("x".parse::<u8>().unwrap_err());
+/// assert!(number.source().is_some());
+/// assert!(Error::Overflow.source().is_none());
+/// ```
+///
+/// Boxed sources and fields named `source` having non-error types:
+/// ```
+/// # #[macro_use] extern crate amplify_derive;
+/// use std::error::Error as StdError;
+///
+/// #[derive(Debug, Display, Error)]
+/// #[display("{source}")]
+/// struct Described {
+///     source: String,
+/// }
+///
+/// #[derive(Debug, Display, Error)]
+/// #[display("boxed")]
+/// struct Boxed {
+///     source: Box<dyn StdError + Send + Sync>,
+/// }
+///
+/// #[derive(Debug, Display, Error)]
+/// #[display("dynamic")]
+/// enum Dynamic {
+///     Boxed(#[source] Box<dyn StdError + Send + Sync>),
+///     Other {
+///         #[error(source)]
+///         cause: Box<dyn StdError>,
+///     },
+/// }
+///
+/// let described = Described {
+///     source: "not an error".to_string(),
+/// };
+/// assert!(described.source().is_none());
+///
+/// let boxed = Boxed {
+///     source: Box::new(described),
+/// };
+/// assert_eq!(boxed.source().unwrap().to_string(), "not an error");
+/// let dynamic = Dynamic::Boxed(Box::new(boxed));
+/// assert_eq!(dynamic.source().unwrap().to_string(), "boxed");
+/// let other = Dynamic::Other {
+///     cause: Box::new(dynamic),
+/// };
+/// assert_eq!(other.source().unwrap().to_string(), "dynamic");
+/// ```
+///
+/// `#[error(from)]` can't be used if there are other fields:
+/// ```compile_fail
+/// # #[macro_use] extern crate amplify_derive;
+/// #[derive(Debug, Display, Error)]
+/// #[display("config error")]
+/// struct ConfigError {
+///     path: String,
+///     #[error(from)]
+///     io: std::io::Error,
+/// }
+/// ```
+#[proc_macro_derive(Error, attributes(error, source))]
 pub fn derive_error(input: TokenStream) -> TokenStream {
     let derive_input = parse_macro_input!(input as DeriveInput);
     error::inner(derive_input)
@@ -376,6 +851,119 @@ pub fn derive_error(input: TokenStream) -> TokenStream {
 /// pub struct Wrapper(u32, i16);
 /// ```
 ///
+/// For generic types conversions are bounded by [`Into`] for the converted
+/// field and by [`Default`] for the rest of the fields (or the whole type),
+/// if they depend on the type parameters. The bounds may be replaced with
+/// `#[from(bound = "T: Trait")]` attribute.
+/// ```
+/// # #[macro_use] extern crate amplify_derive;
+/// #[derive(Default, From)]
+/// pub struct Pair<T, U> {
+///     #[from]
+///     first: T,
+///     second: Option<U>,
+/// }
+///
+/// let pair = Pair::<u8, u16>::from(1);
+/// assert_eq!((pair.first, pair.second), (1, None));
+/// ```
+///
+/// Fallible conversions are derived with `#[try_from(Type)]` attribute, which
+/// may be used in the same places as `#[from(Type)]`. It implements
+/// [`TryFrom`] delegating the conversion to the [`TryFrom`] implementation of
+/// the target field type; the rest of the fields must implement [`Default`].
+/// The error type of the field conversion is used, unless a different type
+/// is given with `#[try_from(Type, error = ErrType)]`; in the latter case
+/// field conversion errors are wrapped into `ErrType` with its [`From`]
+/// implementation.
+/// ```
+/// # #[macro_use] extern crate amplify_derive;
+/// use std::convert::TryFrom;
+/// use std::num::TryFromIntError;
+///
+/// #[derive(Clone, Copy, PartialEq, Eq, Debug, From)]
+/// pub enum Error {
+///     #[from]
+///     Int(TryFromIntError),
+/// }
+///
+/// #[derive(Clone, Copy, PartialEq, Eq, Debug, From)]
+/// #[try_from(u32)]
+/// pub struct Port(u16);
+///
+/// #[derive(Clone, Copy, PartialEq, Eq, Debug, From)]
+/// pub enum Address {
+///     #[try_from(u64, error = Error)]
+///     Short(u32),
+///     Long {
+///         #[try_from(u128, error = Error)]
+///         value: u64,
+///         checksum: u8,
+///     },
+/// }
+///
+/// assert_eq!(Port::try_from(8080u32), Ok(Port(8080)));
+/// assert!(Port::try_from(1u32 << 20).is_err());
+/// assert_eq!(Address::try_from(7u64), Ok(Address::Short(7)));
+/// assert!(matches!(Address::try_from(1u64 << 40), Err(Error::Int(_))));
+/// assert_eq!(
+///     Address::try_from(1u128 << 40),
+///     Ok(Address::Long {
+///         value: 1 << 40,
+///         checksum: 0
+///     })
+/// );
+/// ```
+///
+/// Reverse conversions are derived with `#[from(into)]` attribute. On a
+/// newtype it implements [`From`] converting the newtype into its inner
+/// type. On an enum variant with a single field it generates `is_variant`,
+/// `as_variant` and `into_variant` methods (named after the variant in snake
+/// case) and implements [`TryFrom`] converting the enum into the variant data,
+/// returning the enum value back as an error for the other variants.
+/// [`TryFrom`] is not implemented for data types which are generic type
+/// parameters (due to the orphan rules) or are repeated in several variants.
+/// Newtypes deriving `Wrapper` already get the conversion into the inner
+/// type, so using `#[from(into)]` on them is a compile-time error.
+/// ```
+/// # #[macro_use] extern crate amplify_derive;
+/// use std::convert::TryFrom;
+/// use std::fmt;
+///
+/// #[derive(Clone, PartialEq, Eq, Debug, From)]
+/// #[from(into)]
+/// pub struct Alias(String);
+///
+/// #[derive(Clone, PartialEq, Eq, Debug, From)]
+/// pub enum Error {
+///     #[from]
+///     #[from(into)]
+///     Format(fmt::Error),
+///     #[from]
+///     #[from(into)]
+///     Parse { details: std::num::ParseIntError },
+///     Unknown,
+/// }
+///
+/// let alias = Alias::from("node".to_owned());
+/// assert_eq!(String::from(alias), "node");
+///
+/// let err = Error::from(fmt::Error);
+/// assert!(err.is_format());
+/// assert!(!err.is_parse());
+/// assert_eq!(err.as_format(), Some(&fmt::Error));
+/// assert_eq!(fmt::Error::try_from(err.clone()), Ok(fmt::Error));
+/// assert_eq!(err.clone().into_parse(), Err(err));
+/// assert_eq!(Error::Unknown.into_format(), Err(Error::Unknown));
+/// ```
+///
+/// ```compile_fail
+/// # #[macro_use] extern crate amplify_derive;
+/// #[derive(Wrapper, From)]
+/// #[from(into)]
+/// pub struct Id(u32);
+/// ```
+///
 /// If you use rust nightly and `#![feature(never_type)]` for [`!`], you can
 /// even do the following:
 /// ```ignore
@@ -394,7 +982,9 @@ pub fn derive_error(input: TokenStream) -> TokenStream {
 /// # fn main () {
 /// # }
 /// ```
-#[proc_macro_derive(From, attributes(from))]
+///
+/// [`TryFrom`]: core::convert::TryFrom
+#[proc_macro_derive(From, attributes(from, try_from))]
 pub fn derive_from(input: TokenStream) -> TokenStream {
     let derive_input = parse_macro_input!(input as DeriveInput);
     from::inner(derive_input)
@@ -451,8 +1041,20 @@ pub fn derive_as_any(input: TokenStream) -> TokenStream {
         .into()
 }
 
-/// Derives getter methods for structures. The return type and naming of the
-/// methods depends on the provided attribute arguments.
+/// Derives getter methods for structures and enums. The return type and
+/// naming of the methods depends on the provided attribute arguments.
+///
+/// For tuple structs getters are derived only for the fields having a name
+/// assigned with `#[getter(name = "...")]`.
+///
+/// For enums getters are derived for each field name met in the enum
+/// variants; unnamed fields of the variants participate only if they have a
+/// name assigned with `#[getter(name = "...")]`. A field must have the same
+/// type in all variants containing it. If the field is present in all
+/// variants, the getter returns the value directly; otherwise the value is
+/// wrapped into `Option`, which is `None` for the variants without the field.
+/// Field-level arguments are taken from the first variant containing the
+/// field.
 ///
 /// # Attribute `#[getter(...)]`
 ///
@@ -479,6 +1081,13 @@ pub fn derive_as_any(input: TokenStream) -> TokenStream {
 ///   suffixed with `_ref`; otherwise the base name is used (see below)
 /// - `as_mut`: derives method returning mutable reference. Method name is
 ///   suffixed with `_mut`
+/// - `as_deref`: derives method returning dereferenced value: `&str` for
+///   `String`, `&[T]` for `Vec<T>`, `&T` for `Box<T>`, `Option<&T>` for
+///   `Option<T>` and `&T::Target` for other types implementing `Deref`. Like
+///   with `as_copy` and `as_clone`, method uses the base name, unless a
+///   suffix is given as `as_deref = "_suffix"`
+/// - `as_ref_opt`: derives method returning `Option<&T>` for `Option<T>`
+///   fields, using the base name unless a suffix is given
 /// - `all`: equivalent to `as_clone, as_ref, as_mut`
 ///
 /// **Can be used**: at type and field level
@@ -504,13 +1113,40 @@ pub fn derive_as_any(input: TokenStream) -> TokenStream {
 ///
 /// **Can be used**: at field level
 ///
+/// ### `#[getter(name = "...")]`
+/// Synonym for `base_name`, which reads better for the unnamed fields of tuple
+/// structs and enum variants. Can't be combined with `base_name`.
+///
+/// **Can be used**: at field level
+///
+/// ### `#[getter(map = path, ret = "Type")]`
+/// Derives method named with the base name, which returns the result of
+/// calling function `path` with a reference to the field value. Since the
+/// macro can't know the type returned by the function, it must be provided
+/// with `ret`. Considered a method derivation argument, i.e. it replaces the
+/// type-level set of methods.
+///
+/// **Can be used**: at field level
+///
+/// ### `#[getter(vis = "...")]`
+/// Defines visibility of the derived methods, like `vis = "pub(crate)"`.
+///
+/// **Defaults to**: `pub`
+///
+/// **Can be used**: at type and field level
+///
 /// # Errors
 ///
-/// Enums and units are not supported; attempt to derive `Getters` on them will
-/// result in a compile-time error.
+/// Unions are not supported; attempt to derive `Getters` on them will result
+/// in a compile-time error.
 ///
-/// Deriving getters on unit structs and structs with unnamed fields (tupe
-/// structs) is not supported (since it's meaningless), and results in a error.
+/// Deriving getters on unit structs is not supported (since it's
+/// meaningless), and results in a error. Unnamed fields of tuple structs
+/// without `name` (and not skipped) also result in a error.
+///
+/// Enum fields sharing the same name, but having different types, are
+/// reported as an error; the same applies to the methods for a field which
+/// end up with the same name.
 ///
 /// Additionally to these two cases, macro errors on argument inconsistencies,
 /// as described in the argument-specific sections.
@@ -593,6 +1229,83 @@ pub fn derive_as_any(input: TokenStream) -> TokenStream {
 /// // method does not exist: assert_eq!(one.get_field(), u8::default());
 /// ```
 ///
+/// Return type transformations and visibility:
+///
+/// ```
+/// # #[macro_use] extern crate amplify_derive;
+/// mod node {
+///     #[derive(Getters, Default)]
+///     #[getter(as_deref)]
+///     pub struct Node {
+///         alias: String,
+///         features: Vec<u8>,
+///         #[getter(as_ref_opt)]
+///         color: Option<u32>,
+///         #[getter(map = str::len, ret = "usize", name = "alias_len")]
+///         alias_copy: String,
+///         #[getter(as_copy, vis = "pub(crate)")]
+///         connected: bool,
+///     }
+/// }
+///
+/// let node = node::Node::default();
+/// let alias: &str = node.alias();
+/// let features: &[u8] = node.features();
+/// assert_eq!(alias, "");
+/// assert!(features.is_empty());
+/// assert_eq!(node.color(), None);
+/// assert_eq!(node.alias_len(), 0);
+/// assert!(!node.connected());
+/// ```
+///
+/// Tuple structs and enums:
+///
+/// ```
+/// # #[macro_use] extern crate amplify_derive;
+/// #[derive(Getters)]
+/// struct Point(
+///     #[getter(as_copy, name = "x")] i32,
+///     #[getter(as_copy, name = "y")] i32,
+///     #[getter(skip)] (),
+/// );
+///
+/// #[derive(Getters)]
+/// enum Message {
+///     Ping {
+///         #[getter(as_copy)]
+///         id: u64,
+///         #[getter(as_copy)]
+///         timestamp: u32,
+///     },
+///     Data {
+///         id: u64,
+///         timestamp: u32,
+///         #[getter(as_mut)]
+///         payload: Vec<u8>,
+///     },
+///     Ack(#[getter(name = "id")] u64, #[getter(name = "timestamp")] u32),
+/// }
+///
+/// let point = Point(1, 2, ());
+/// assert_eq!(point.x(), 1);
+/// assert_eq!(point.y(), 2);
+///
+/// let mut msg = Message::Data {
+///     id: 5,
+///     timestamp: 1000,
+///     payload: vec![],
+/// };
+/// assert_eq!(msg.id(), 5);
+/// assert_eq!(msg.timestamp(), 1000);
+/// msg.payload_mut().unwrap().push(1);
+/// assert_eq!(msg.payload_mut(), Some(&mut vec![1]));
+///
+/// let ack = Message::Ack(6, 1001);
+/// assert_eq!(ack.id(), 6);
+/// assert_eq!(ack.timestamp(), 1001);
+/// assert_eq!(Message::Ack(6, 1001).payload_mut(), None);
+/// ```
+///
 /// this will end up in the following generated code:
 /// ```
 /// # struct One {
@@ -644,6 +1357,404 @@ pub fn derive_getters(input: TokenStream) -> TokenStream {
         .into()
 }
 
+/// Derives setter methods for structures, complementing [`Getters`] derive
+/// and using the same attribute vocabulary.
+///
+/// # Attribute `#[setter(...)]`
+///
+/// Macro is provided with `#[setter]` attribute, which may be used on both
+/// type and field level.
+///
+/// ## Arguments
+///
+/// ### Method derivation arguments
+/// Define which forms of methods should be derived. At the type level they
+/// apply to all fields; at the field level they replace the type-level set of
+/// methods.
+///
+/// - `set`: derives method taking `&mut self` and returning `&mut Self`, which
+///   allows chaining calls. Method name is prefixed with `set_`; a different
+///   prefix may be given as `set = "prefix"`.
+/// - `with`: derives method taking `self` by value and returning it with the
+///   field updated. Method name is prefixed with `with_`; a different prefix
+///   may be given as `with = "prefix"`.
+/// - `all`: equivalent to `set, with`
+///
+/// **Can be used**: at type and field level
+///
+/// **Defaults to**: `set`
+///
+/// ### `#[setter(into)]`
+/// Makes methods accept any value convertible into the field type with
+/// [`Into`].
+///
+/// **Can be used**: at type and field level
+///
+/// ### `#[setter(skip)]`
+/// Skips derivation of all setter methods for this field
+///
+/// ### `#[setter(base_name = "...")]` or `#[setter(name = "...")]`
+/// Defines base name for the setter methods, which is prefixed with the
+/// method-specific prefix.
+///
+/// **Defaults to**: field name
+///
+/// **Can be used**: at field level
+///
+/// ### `#[setter(vis = "...")]`
+/// Defines visibility of the derived methods, like `vis = "pub(crate)"`.
+///
+/// **Defaults to**: `pub`
+///
+/// **Can be used**: at type and field level
+///
+/// # Errors
+///
+/// Enums, unions and unit structs are not supported and result in a
+/// compile-time error. Unnamed fields of tuple structs must be either skipped
+/// or named with `#[setter(name = "...")]`.
+///
+/// # Example
+///
+/// ```
+/// # #[macro_use] extern crate amplify_derive;
+/// #[derive(Setters, Default)]
+/// #[setter(all)]
+/// struct Config {
+///     #[setter(into)]
+///     name: String,
+///     port: u16,
+///     #[setter(with = "using_")]
+///     verbose: bool,
+///     #[setter(skip)]
+///     connected: bool,
+/// }
+///
+/// #[derive(Setters, Default)]
+/// #[setter(vis = "pub(crate)")]
+/// struct Point(#[setter(name = "x")] i32, #[setter(name = "y", vis = "")] i32);
+///
+/// let mut config = Config::default().with_name("node").using_verbose(true);
+/// config.set_port(8080).set_name(String::from("relay"));
+/// assert_eq!(config.name, "relay");
+/// assert_eq!(config.port, 8080);
+/// assert!(config.verbose);
+///
+/// let mut point = Point::default();
+/// point.set_x(1).set_y(2);
+/// assert_eq!((point.0, point.1), (1, 2));
+/// ```
+#[proc_macro_derive(Setters, attributes(setter))]
+pub fn derive_setters(input: TokenStream) -> TokenStream {
+    let derive_input = parse_macro_input!(input as DeriveInput);
+    setters::derive(derive_input)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
+
+/// Derives builder type for a structure with named fields.
+///
+/// The builder is named after the structure with `Builder` suffix and has the
+/// same visibility. It is constructed with `builder()` method of the
+/// structure and has a by-value method for each field named after the field.
+/// `build()` method of the builder checks that all required fields are set,
+/// returning `amplify::MissingFieldError` otherwise; thus, the macro requires
+/// `amplify` crate (its path may be given with `#[amplify_crate(path)]`).
+///
+/// # Attribute `#[builder(...)]`
+///
+/// - `name = "..."`: name of the builder type; can be used at type level.
+/// - `into`: makes builder methods accept any value convertible into the
+///   field type with [`Into`]; can be used at type and field level.
+/// - `default`: makes field optional, using [`Default`] value if the field
+///   was not set; a different value may be given as an expression
+///   `default = "expr"`. Can be used at field level.
+///
+/// # Errors
+///
+/// Enums, unions, unit and tuple structs are not supported and result in a
+/// compile-time error.
+///
+/// # Example
+///
+/// ```
+/// # #[macro_use] extern crate amplify_derive;
+/// # extern crate amplify;
+/// use amplify::MissingFieldError;
+///
+/// #[derive(Builder, PartialEq, Debug)]
+/// #[builder(into)]
+/// struct Config {
+///     name: String,
+///     #[builder(default = "8080")]
+///     port: u16,
+///     #[builder(default)]
+///     verbose: bool,
+/// }
+///
+/// let config = Config::builder().name("node").build().unwrap();
+/// assert_eq!(config, Config {
+///     name: "node".to_owned(),
+///     port: 8080,
+///     verbose: false
+/// });
+///
+/// let config = Config::builder().port(9735u16).verbose(true).name("node").build();
+/// assert_eq!(config.unwrap().port, 9735);
+///
+/// assert_eq!(
+///     Config::builder().port(1u16).build(),
+///     Err(MissingFieldError {
+///         ty: "Config",
+///         field: "name"
+///     })
+/// );
+/// ```
+///
+/// The generated code does not depend on the names imported into the scope:
+/// ```
+/// # #[macro_use] extern crate amplify_derive;
+/// # extern crate amplify;
+/// type Result<T> = std::result::Result<T, String>;
+/// struct Option;
+/// struct Default;
+///
+/// #[derive(Builder, Setters)]
+/// #[setter(into)]
+/// struct Peer<T: std::default::Default> {
+///     #[builder(into)]
+///     addr: String,
+///     #[builder(default)]
+///     data: T,
+/// }
+///
+/// fn peer() -> Result<Peer<u8>> {
+///     Peer::builder().addr("localhost").build().map_err(|err| err.to_string())
+/// }
+/// let mut peer = peer().unwrap();
+/// peer.set_data(5u8);
+/// assert_eq!(peer.data, 5);
+/// ```
+#[proc_macro_derive(Builder, attributes(builder, amplify_crate))]
+pub fn derive_builder(input: TokenStream) -> TokenStream {
+    let derive_input = parse_macro_input!(input as DeriveInput);
+    builder::derive(derive_input)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
+
+/// Derives metadata and `u8` conversions for enums without variant fields.
+///
+/// Generates inherent `ALL` constant listing all variants in the order of
+/// their declaration, `COUNT` constant with the number of variants, `name()`
+/// method returning name of the variant and `from_name()` constructor doing
+/// the reverse. Also implements `TryFrom<u8>` for the enum and `From<Enum>`
+/// for `u8` using the variant discriminants, which must fit into `u8`; failed
+/// conversions return `amplify::UnknownDiscriminantError`, thus the macro
+/// requires `amplify` crate (its path may be given with
+/// `#[amplify_crate(path)]`).
+///
+/// # Attribute `#[enum_meta(...)]`
+///
+/// - case style, like `snake_case` or `kebab_case` (same as in
+///   `#[display(...)]`): converts variant names to the given case; can be used
+///   at type level.
+/// - `name = "..."`: explicit name for the variant; can be used at variant
+///   level.
+///
+/// # Errors
+///
+/// Structs, unions and enums with variant fields are not supported and result
+/// in a compile-time error; the same happens for discriminants which are not
+/// integer literals (including negative values and constant expressions), for
+/// explicit and implicit discriminants not fitting into `u8` and for repeated
+/// variant names.
+///
+/// # Example
+///
+/// ```
+/// # #[macro_use] extern crate amplify_derive;
+/// # extern crate amplify;
+/// use std::convert::TryFrom;
+///
+/// use amplify::UnknownDiscriminantError;
+///
+/// #[derive(EnumMeta, Copy, Clone, PartialEq, Eq, Debug)]
+/// #[enum_meta(snake_case)]
+/// #[repr(u8)]
+/// enum Network {
+///     MainNet = 1,
+///     TestNet = 3,
+///     #[enum_meta(name = "regtest")]
+///     RegTest = 0x10,
+///     SigNet,
+/// }
+///
+/// assert_eq!(Network::COUNT, 4);
+/// assert_eq!(Network::ALL, &[
+///     Network::MainNet,
+///     Network::TestNet,
+///     Network::RegTest,
+///     Network::SigNet
+/// ]);
+/// assert_eq!(Network::TestNet.name(), "test_net");
+/// assert_eq!(Network::from_name("regtest"), Some(Network::RegTest));
+/// assert_eq!(Network::from_name("RegTest"), None);
+///
+/// assert_eq!(u8::from(Network::RegTest), 16);
+/// assert_eq!(u8::from(Network::SigNet), 17);
+/// assert_eq!(Network::try_from(3), Ok(Network::TestNet));
+/// assert_eq!(
+///     Network::try_from(2),
+///     Err(UnknownDiscriminantError {
+///         ty: "Network",
+///         value: 2
+///     })
+/// );
+/// ```
+///
+/// Implicit discriminants are checked as well:
+/// ```compile_fail
+/// # #[macro_use] extern crate amplify_derive;
+/// # extern crate amplify;
+/// #[derive(EnumMeta, Copy, Clone)]
+/// enum Overflow {
+///     Last = 255,
+///     Next,
+/// }
+/// ```
+///
+/// ```compile_fail
+/// # #[macro_use] extern crate amplify_derive;
+/// # extern crate amplify;
+/// const FIRST: u8 = 1;
+///
+/// #[derive(EnumMeta, Copy, Clone)]
+/// enum Constant {
+///     First = FIRST as isize,
+/// }
+/// ```
+#[proc_macro_derive(EnumMeta, attributes(enum_meta, amplify_crate))]
+pub fn derive_enum_meta(input: TokenStream) -> TokenStream {
+    let derive_input = parse_macro_input!(input as DeriveInput);
+    enum_meta::derive(derive_input)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
+
+/// Derives [strict encoding](https://docs.rs/amplify/latest/amplify/trait.StrictEncode.html)
+/// of a structure or enum, writing the fields in the order of their
+/// declaration. Enum variants are prefixed with `u8` tag, which is taken from
+/// `#[strict(tag = N)]` attribute or variant discriminant; variants without
+/// both use the tag of the previous variant plus one, starting with zero.
+/// Tags must be unique and fit into `u8`.
+///
+/// Generic type parameters used by fields get `StrictEncode` bound. The
+/// macro requires `amplify` crate with `std` feature; its path may be given
+/// with `#[amplify_crate(path)]`.
+///
+/// # Errors
+///
+/// Unions, repeated tags, tags not fitting into `u8` and non-literal
+/// discriminants without explicit tag result in a compile-time error.
+///
+/// # Example
+///
+/// ```
+/// # #[macro_use] extern crate amplify_derive;
+/// # extern crate amplify;
+/// use amplify::{StrictDecode, StrictEncode, StrictEncodingError};
+///
+/// #[derive(StrictEncode, StrictDecode, PartialEq, Debug)]
+/// struct Header {
+///     version: u16,
+///     flags: Option<u8>,
+/// }
+///
+/// #[derive(StrictEncode, StrictDecode, PartialEq, Debug)]
+/// enum Message {
+///     Ping,
+///     Data(Header, Vec<u8>),
+///     #[strict(tag = 0x10)]
+///     Text { text: String },
+/// }
+///
+/// let header = Header {
+///     version: 1,
+///     flags: Some(0xFF),
+/// };
+/// assert_eq!(header.strict_serialize().unwrap(), vec![1, 0, 1, 0xFF]);
+///
+/// let msg = Message::Data(header, vec![7]);
+/// let data = msg.strict_serialize().unwrap();
+/// assert_eq!(data, vec![1, 1, 0, 1, 0xFF, 1, 0, 7]);
+/// assert_eq!(Message::strict_deserialize(&data), Ok(msg));
+///
+/// let msg = Message::Text { text: "hi".to_owned() };
+/// assert_eq!(msg.strict_serialize().unwrap(), vec![0x10, 2, 0, b'h', b'i']);
+/// assert_eq!(
+///     Message::strict_deserialize(&[2]),
+///     Err(StrictEncodingError::UnknownTag {
+///         ty: "Message",
+///         tag: 2
+///     })
+/// );
+/// ```
+///
+/// Repeated tags are rejected:
+/// ```compile_fail
+/// # #[macro_use] extern crate amplify_derive;
+/// # extern crate amplify;
+/// #[derive(StrictEncode)]
+/// enum Repeated {
+///     #[strict(tag = 1)]
+///     First,
+///     Second = 1,
+/// }
+/// ```
+///
+/// as well as tags not fitting into `u8`, including the implicit ones:
+/// ```compile_fail
+/// # #[macro_use] extern crate amplify_derive;
+/// # extern crate amplify;
+/// #[derive(StrictEncode)]
+/// enum Overflow {
+///     #[strict(tag = 255)]
+///     Last,
+///     Next,
+/// }
+/// ```
+///
+/// ```compile_fail
+/// # #[macro_use] extern crate amplify_derive;
+/// # extern crate amplify;
+/// #[derive(StrictEncode)]
+/// enum Overflow {
+///     #[strict(tag = 256)]
+///     Large,
+/// }
+/// ```
+#[proc_macro_derive(StrictEncode, attributes(strict, amplify_crate))]
+pub fn derive_strict_encode(input: TokenStream) -> TokenStream {
+    let derive_input = parse_macro_input!(input as DeriveInput);
+    strict_encoding::derive_encode(derive_input)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
+
+/// Derives [strict decoding](https://docs.rs/amplify/latest/amplify/trait.StrictDecode.html)
+/// of a structure or enum, reading data written by `StrictEncode` derive;
+/// see its documentation for the encoding rules and supported attributes.
+/// Decoding of an enum fails with `StrictEncodingError::UnknownTag` if the
+/// tag does not match any of the variants.
+#[proc_macro_derive(StrictDecode, attributes(strict, amplify_crate))]
+pub fn derive_strict_decode(input: TokenStream) -> TokenStream {
+    let derive_input = parse_macro_input!(input as DeriveInput);
+    strict_encoding::derive_decode(derive_input)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
+
 /// Creates rust new type wrapping existing type. Can be used in sturctures
 /// containing multiple named or unnamed fields; in this case the field you'd
 /// like to wrap should be marked with `#[wrap]` attribute; otherwise the first
@@ -655,12 +1766,15 @@ pub fn derive_getters(input: TokenStream) -> TokenStream {
 ///
 /// Supports automatic implementation of the following traits:
 /// * `amplify::Wrapper`
+/// * `amplify::DeepWrapper`, only for `#[wrapper(deep)]` and
+///   `#[wrapper(deep_root)]`
 /// * [`AsRef`]
-/// * [`AsMut`]
 /// * [`std::borrow::Borrow`]
-/// * [`std::borrow::BorrowMut`]
 /// * [`std::ops::Deref`]
-/// * [`std::ops::DerefMut`]
+///
+/// The wrapped data are accessible only for reading; mutable access is
+/// provided by a separate [`macro@WrapperMut`] derive, so types with
+/// invariants on their inner data may use this derive without breaking them.
 ///
 /// You can implement additonal derives, it they are implemented for the wrapped
 /// type, using `#[wrapper()]` proc macro:
@@ -669,35 +1783,75 @@ pub fn derive_getters(input: TokenStream) -> TokenStream {
 /// * [`std::fmt::LowerExp`]
 /// * [`std::fmt::UpperExp`]
 /// * [`std::fmt::Octal`]
+/// * [`std::fmt::Binary`]
+/// * `Hex`: implements [`std::fmt::LowerHex`] and `amplify::hex::FromHex`
+///   (and, thus, `amplify::hex::ToHex`)
+/// * `Serialize` and `Deserialize`: transparent `serde` implementations
+///   delegating to the wrapped type; require `serde` feature of `amplify`
+///   crate, which is used for accessing `serde` (see `#[amplify_crate]`)
 /// * [`std::ops::Index`]
-/// * [`std::ops::IndexMut`]
 /// * [`std::ops::Neg`]
 /// * [`std::ops::Not`]
 /// * [`std::ops::Add`]
-/// * [`std::ops::AddAssign`]
 /// * [`std::ops::Sub`]
-/// * [`std::ops::SubAssign`]
 /// * [`std::ops::Mul`]
-/// * [`std::ops::MulAssign`]
 /// * [`std::ops::Div`]
-/// * [`std::ops::DivAssign`]
 /// * [`std::ops::Rem`]
-/// * [`std::ops::RemAssign`]
 /// * [`std::ops::Shl`]
-/// * [`std::ops::ShlAssign`]
 /// * [`std::ops::Shr`]
-/// * [`std::ops::ShrAssign`]
 /// * [`std::ops::BitAnd`]
-/// * [`std::ops::BitAndAssign`]
 /// * [`std::ops::BitOr`]
-/// * [`std::ops::BitOrAssign`]
 /// * [`std::ops::BitXor`]
-/// * [`std::ops::BitXorAssign`]
+/// * [`IntoIterator`] for the wrapper consuming it; `IntoIteratorRef`
+///   implements [`IntoIterator`] for a reference to the wrapper
+/// * [`std::iter::FromIterator`] for any item type supported by the wrapped
+///   type
+/// * [`std::iter::Sum`] and [`std::iter::Product`]
+/// * `PartialEqInner` and `PartialOrdInner`: [`PartialEq`] and [`PartialOrd`]
+///   comparing the wrapper with the values of the wrapped type
+/// * [`Hash`]: hashes the wrapped value only, ignoring other fields
 ///
 /// Other traits, such as [`PartialEq`], [`Eq`], [`PartialOrd`], [`Ord`],
 /// [`Hash`] can be implemented using standard `#[derive]` attribute in the
 /// same manner as [`Default`], [`Debug`] and [`From`]
 ///
+/// For generic types each implementation requires the wrapped type to
+/// implement the corresponding trait (and [`Clone`], required by
+/// `amplify::Wrapper`); these bounds may be replaced with
+/// `#[wrapper(bound = "T: Trait")]`. Conversion of the wrapper into the
+/// inner type is not implemented if the inner type is a bare type parameter,
+/// since this is prohibited by the orphan rules.
+///
+/// Types with invariants on the inner data may provide a validator with
+/// `#[wrapper(validate = path, error = Type)]`, where `path` refers to a
+/// function taking reference to the inner data and returning
+/// `Result<(), E>` with `E` convertible into `Type`. In this case the derive
+/// * implements `TryFrom` for the inner type, running the validator (unless
+///   the inner type is a bare type parameter);
+/// * makes `FromStr` and `Deserialize` run the validator; the former requires
+///   `Type` to implement `From` for the parsing error of the inner type and
+///   the latter requires it to implement [`Display`](std::fmt::Display);
+/// * constructs the wrapper in `from_inner` directly, without `From`, which
+///   must not be derived for the inner type. `from_inner` remains an unchecked
+///   constructor.
+///
+/// Derives constructing the wrapper from the values produced by the inner
+/// type (`Hex`, arithmetic operations, `FromIterator`, `Sum` and `Product`)
+/// would bypass the validator and can't be used together with it.
+///
+/// Wrappers of other wrapped types (like `struct TxId(Sha256)`, where
+/// `struct Sha256(Slice32)`) should use `#[wrapper(deep)]` attribute, which
+/// implements `amplify::DeepWrapper` giving access to the innermost data. It
+/// requires the wrapped type to implement `amplify::DeepWrapper` as well, so
+/// each level of wrapping must be marked as `deep`, down to the lowest one,
+/// which is marked with `#[wrapper(deep_root)]` (or implements the trait
+/// manually) and thus defines its wrapped type as the innermost one.
+/// All traits listed in `#[wrapper(...)]` of `deep` wrappers are then
+/// implemented by delegating to the innermost type, so the intermediate
+/// wrappers do not have to implement them; the intermediate wrappers are
+/// constructed with `from_inner` without any checks, thus neither `deep` nor
+/// `deep_root` can be combined with `validate`.
+///
 /// # Example
 ///
 /// Simple wrapper:
@@ -711,10 +1865,22 @@ pub fn derive_getters(input: TokenStream) -> TokenStream {
 /// #[display(inner)]
 /// #[wrapper(LowerHex, UpperHex, Octal)]
 /// #[wrapper(Neg, Add, Sub, Div, Mul, Rem)]
-/// #[wrapper(AddAssign, SubAssign, DivAssign, MulAssign, RemAssign)]
 /// #[wrapper(Not, Shl, Shr, BitAnd, BitOr, BitXor)]
-/// #[wrapper(ShlAssign, ShrAssign, BitAndAssign, BitOrAssign, BitXorAssign)]
 /// struct Int64(i64);
+///
+/// let int = Int64::from(5);
+/// assert_eq!(*int, 5);
+/// assert_eq!(int + Int64::from(1), Int64::from(6));
+/// ```
+///
+/// Validated wrapper, which can't be modified in place:
+/// ```compile_fail
+/// # #[macro_use] extern crate amplify_derive;
+/// #[derive(Wrapper, Clone, From)]
+/// struct SortedVec(Vec<u8>);
+///
+/// let mut sorted = SortedVec::from(vec![1, 2, 3]);
+/// sorted.push(0);
 /// ```
 ///
 /// More complex wrapper with multiple unnamed fields:
@@ -740,6 +1906,23 @@ pub fn derive_getters(input: TokenStream) -> TokenStream {
 /// assert_eq!(w.into_inner(), HashMap::<usize, Vec<u8>>::default());
 /// ```
 ///
+/// Wrappers with hex and serde support:
+/// ```
+/// # #[macro_use] extern crate amplify_derive;
+/// use amplify::hex::{FromHex, ToHex};
+/// use amplify::{Slice32, Wrapper};
+///
+/// #[derive(Wrapper, Clone, Copy, PartialEq, Eq, Debug, From)]
+/// #[wrapper(Hex, Serialize, Deserialize)]
+/// struct Hash(Slice32);
+///
+/// let s = "a3401bcceb26201b55978ff705fecf7d8a0a03598ebeccf2a947030b91a0ff53";
+/// let hash = Hash::from_hex(s).unwrap();
+/// assert_eq!(hash.to_hex(), s);
+/// assert_eq!(serde_json::to_string(&hash).unwrap(), format!("\"{}\"", s));
+/// assert_eq!(serde_json::from_str::<Hash>(&format!("\"{}\"", s)).unwrap(), hash);
+/// ```
+///
 /// Wrappers for indexable types
 /// ```
 /// # #[macro_use] extern crate amplify_derive;
@@ -749,6 +1932,113 @@ pub fn derive_getters(input: TokenStream) -> TokenStream {
 /// #[wrapper(Index, IndexRange, IndexFrom, IndexTo, IndexInclusive, IndexFull)]
 /// struct VecNewtype(Vec<u8>);
 /// ```
+///
+/// Wrappers for collections and numbers
+/// ```
+/// # #[macro_use] extern crate amplify_derive;
+/// use std::collections::BTreeMap;
+///
+/// #[derive(Wrapper, Clone, PartialEq, Default, From, Debug)]
+/// #[wrapper(IntoIterator, IntoIteratorRef, FromIterator, PartialEqInner)]
+/// struct Names(BTreeMap<u8, String>);
+///
+/// let names: Names = vec![(1, "one".to_owned())].into_iter().collect();
+/// for (no, name) in &names {
+///     assert_eq!((*no, name.as_str()), (1, "one"));
+/// }
+/// assert_eq!(names.into_iter().count(), 1);
+///
+/// #[derive(Wrapper, Clone, Copy, PartialEq, Eq, Hash, From, Debug)]
+/// #[wrapper(Add, Sum, Product, Binary, PartialEqInner, PartialOrdInner)]
+/// struct Amount(u64);
+///
+/// let total: Amount = vec![Amount(2), Amount(3)].into_iter().sum();
+/// assert_eq!(total, 5);
+/// assert!(total > 4);
+/// assert_eq!(format!("{:b}", total), "101");
+/// ```
+///
+/// Wrapper with validated inner data:
+/// ```
+/// # #[macro_use] extern crate amplify_derive;
+/// use std::convert::TryFrom;
+/// use std::num::ParseIntError;
+/// use amplify::Wrapper;
+///
+/// #[derive(Clone, PartialEq, Eq, Debug, Display, From)]
+/// #[display(doc_comments)]
+/// enum PercentError {
+///     /// value {0} exceeds 100
+///     TooLarge(u8),
+///
+///     /// {0}
+///     #[from]
+///     Parse(ParseIntError),
+/// }
+///
+/// #[derive(Wrapper, Clone, Copy, PartialEq, Eq, Debug)]
+/// #[wrapper(FromStr, Deserialize, PartialEqInner)]
+/// #[wrapper(validate = Self::check, error = PercentError)]
+/// struct Percent(u8);
+///
+/// impl Percent {
+///     fn check(value: &u8) -> Result<(), PercentError> {
+///         if *value > 100 {
+///             return Err(PercentError::TooLarge(*value));
+///         }
+///         Ok(())
+///     }
+/// }
+///
+/// assert_eq!(Percent::try_from(42).unwrap(), 42);
+/// assert_eq!(Percent::try_from(142), Err(PercentError::TooLarge(142)));
+/// assert_eq!("142".parse::<Percent>(), Err(PercentError::TooLarge(142)));
+/// assert!(serde_json::from_str::<Percent>("142").is_err());
+/// // Unchecked construction
+/// assert_eq!(Percent::from_inner(142), 142);
+/// ```
+///
+/// Multi-level wrappers:
+/// ```
+/// # #[macro_use] extern crate amplify_derive;
+/// use amplify::hex::{FromHex, ToHex};
+/// use amplify::{DeepWrapper, Slice32, Wrapper};
+///
+/// #[derive(Wrapper, Clone, Copy, PartialEq, Eq, Debug, From)]
+/// #[wrapper(deep_root)]
+/// struct Sha256(Slice32);
+///
+/// #[derive(Wrapper, Clone, Copy, PartialEq, Eq, Debug, From)]
+/// #[wrapper(deep)]
+/// struct Hash(Sha256);
+///
+/// #[derive(Wrapper, Clone, Copy, PartialEq, Eq, Debug, From)]
+/// #[wrapper(deep, Hex, Display, FromStr, PartialEqInner)]
+/// struct TxId(Hash);
+///
+/// let s = "a3401bcceb26201b55978ff705fecf7d8a0a03598ebeccf2a947030b91a0ff53";
+/// let txid = TxId::from_hex(s).unwrap();
+/// assert_eq!(txid.to_hex(), s);
+/// assert_eq!(txid.to_string(), s);
+/// assert_eq!(txid, *txid.as_deep());
+/// assert_eq!(TxId::from_deep(txid.into_deep()), txid);
+/// assert_eq!(txid.to_deep(), Slice32::from_hex(s).unwrap());
+/// ```
+///
+/// Wrappers not marked as `deep` do not implement `amplify::DeepWrapper`:
+/// ```compile_fail
+/// # #[macro_use] extern crate amplify_derive;
+/// use amplify::{DeepWrapper, Wrapper};
+///
+/// #[derive(Wrapper, Clone, From)]
+/// struct Plain(u8);
+///
+/// #[derive(Wrapper, Clone, From)]
+/// #[wrapper(deep)]
+/// struct Nested(Plain);
+///
+/// let _ = Nested::from_deep(5);
+/// ```
 #[proc_macro_derive(Wrapper, attributes(wrap, wrapper, amplify_crate))]
 pub fn derive_wrapper(input: TokenStream) -> TokenStream {
     let derive_input = parse_macro_input!(input as DeriveInput);
@@ -756,3 +2046,154 @@ pub fn derive_wrapper(input: TokenStream) -> TokenStream {
         .unwrap_or_else(|e| e.to_compile_error())
         .into()
 }
+
+/// Derives mutable access to the data of the types created with
+/// [`macro@Wrapper`] derive, which must be present as well. Uses the same
+/// `#[wrap]` field attribute to detect the wrapped field.
+///
+/// Supports automatic implementation of the following traits:
+/// * `amplify::WrapperMut`
+/// * [`AsMut`]
+/// * [`std::borrow::BorrowMut`]
+/// * [`std::ops::DerefMut`]
+///
+/// Implementations requiring mutable access to the wrapped data are provided
+/// with `#[wrapper_mut()]` attribute:
+/// * [`std::ops::IndexMut`]
+/// * [`std::ops::AddAssign`]
+/// * [`std::ops::SubAssign`]
+/// * [`std::ops::MulAssign`]
+/// * [`std::ops::DivAssign`]
+/// * [`std::ops::RemAssign`]
+/// * [`std::ops::ShlAssign`]
+/// * [`std::ops::ShrAssign`]
+/// * [`std::ops::BitAndAssign`]
+/// * [`std::ops::BitOrAssign`]
+/// * [`std::ops::BitXorAssign`]
+/// * [`Extend`] for any item type supported by the wrapped type
+///
+/// Bounds for generic types are inferred in the same way as for
+/// [`macro@Wrapper`] derive; custom bounds are taken from
+/// `#[wrapper_mut(bound = "...")]` or, if absent, from
+/// `#[wrapper(bound = "...")]`.
+///
+/// Wrappers marked with `#[wrapper(deep)]` or `#[wrapper(deep_root)]` also
+/// implement `amplify::DeepWrapperMut`, giving mutable access to the
+/// innermost data. For `deep` wrappers it requires the wrapped type to
+/// implement `amplify::DeepWrapperMut` as well, and the implementations from
+/// `#[wrapper_mut(...)]` modify the innermost data.
+///
+/// # Example
+///
+/// ```
+/// # #[macro_use] extern crate amplify_derive;
+/// use amplify::{Wrapper, WrapperMut};
+///
+/// #[derive(Wrapper, WrapperMut, Clone, Copy, PartialEq, Eq, Default, From, Debug)]
+/// #[wrapper(Add, Sub)]
+/// #[wrapper_mut(AddAssign, SubAssign)]
+/// struct Int64(i64);
+///
+/// let mut int = Int64::from(5);
+/// int += Int64::from(2);
+/// *int.as_inner_mut() -= 1;
+/// *int += 1;
+/// assert_eq!(int.into_inner(), 7);
+///
+/// #[derive(Wrapper, WrapperMut, Clone, Copy, PartialEq, Eq, From, Debug)]
+/// #[wrapper_mut(
+///     AddAssign,
+///     SubAssign,
+///     MulAssign,
+///     DivAssign,
+///     RemAssign,
+///     ShlAssign,
+///     ShrAssign,
+///     BitAndAssign,
+///     BitOrAssign,
+///     BitXorAssign
+/// )]
+/// struct Bits(u32);
+///
+/// let mut bits = Bits::from(10);
+/// bits += Bits::from(5);
+/// assert_eq!(bits, Bits::from(15));
+/// bits -= Bits::from(3);
+/// assert_eq!(bits, Bits::from(12));
+/// bits *= Bits::from(3);
+/// assert_eq!(bits, Bits::from(36));
+/// bits /= Bits::from(5);
+/// assert_eq!(bits, Bits::from(7));
+/// bits %= Bits::from(4);
+/// assert_eq!(bits, Bits::from(3));
+/// bits <<= Bits::from(4);
+/// assert_eq!(bits, Bits::from(0b11_0000));
+/// bits >>= Bits::from(2);
+/// assert_eq!(bits, Bits::from(0b1100));
+/// bits &= Bits::from(0b0110);
+/// assert_eq!(bits, Bits::from(0b0100));
+/// bits |= Bits::from(0b0011);
+/// assert_eq!(bits, Bits::from(0b0111));
+/// bits ^= Bits::from(0b0101);
+/// assert_eq!(bits, Bits::from(0b0010));
+///
+/// #[derive(Wrapper, WrapperMut, Clone, Default, From)]
+/// #[wrapper(Index)]
+/// #[wrapper_mut(IndexMut)]
+/// struct Bytes(Vec<u8>);
+///
+/// let mut bytes = Bytes::from(vec![0u8; 4]);
+/// bytes[1] = 1;
+/// bytes.push(2);
+/// assert_eq!(bytes.as_inner(), &[0, 1, 0, 0, 2]);
+///
+/// #[derive(Wrapper, WrapperMut, Clone, Default, From)]
+/// #[wrapper_mut(Extend)]
+/// struct Text(String);
+///
+/// let mut text = Text::default();
+/// text.extend(vec!['a', 'b']);
+/// text.extend(vec!["cd"]);
+/// assert_eq!(text.as_inner(), "abcd");
+/// ```
+///
+/// Multi-level wrappers:
+/// ```
+/// # #[macro_use] extern crate amplify_derive;
+/// use amplify::{DeepWrapper, DeepWrapperMut, Wrapper};
+///
+/// #[derive(Wrapper, WrapperMut, Clone, Copy, PartialEq, Eq, Debug, From)]
+/// #[wrapper(deep_root)]
+/// struct Counter(u64);
+///
+/// #[derive(Wrapper, WrapperMut, Clone, Copy, PartialEq, Eq, Debug, From)]
+/// #[wrapper(deep, Add)]
+/// #[wrapper_mut(AddAssign)]
+/// struct Height(Counter);
+///
+/// #[derive(Wrapper, WrapperMut, Clone, Default, Debug, From)]
+/// #[wrapper(deep_root)]
+/// struct Bytes(Vec<u8>);
+///
+/// #[derive(Wrapper, WrapperMut, Clone, Default, Debug, From)]
+/// #[wrapper(deep, Index)]
+/// #[wrapper_mut(IndexMut, Extend)]
+/// struct Message(Bytes);
+///
+/// let mut height = Height::from_deep(5);
+/// height += Height::from_deep(2);
+/// *height.as_deep_mut() += 1;
+/// assert_eq!(height.into_deep(), 8);
+///
+/// let mut msg = Message::from_deep(vec![0u8; 2]);
+/// msg[1] = 1;
+/// msg.extend(vec![2u8]);
+/// assert_eq!(msg.as_deep(), &[0, 1, 2]);
+/// ```
+#[proc_macro_derive(WrapperMut, attributes(wrap, wrapper, wrapper_mut, amplify_crate))]
+pub fn derive_wrapper_mut(input: TokenStream) -> TokenStream {
+    let derive_input = parse_macro_input!(input as DeriveInput);
+    wrapper::inner_mut(derive_input)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
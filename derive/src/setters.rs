@@ -0,0 +1,319 @@
+// Rust language amplification derive library providing multiple generic trait
+// implementations, type wrappers, derive macros and other language enhancements
+//
+// Written in 2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the MIT License
+// along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+use std::collections::HashMap;
+use std::convert::TryInto;
+use std::iter::FromIterator;
+
+use amplify_syn::{ArgValue, ArgValueReq, AttrReq, ParametrizedAttr, ValueClass};
+use proc_macro2::{Ident, Span, TokenStream as TokenStream2};
+use syn::spanned::Spanned;
+use syn::{Data, DeriveInput, Error, Field, Fields, LitStr, Member, Result, Visibility};
+
+pub(crate) fn derive(input: DeriveInput) -> Result<TokenStream2> {
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let struct_name = &input.ident;
+
+    let mut global_param = ParametrizedAttr::with("setter", &input.attrs)?;
+    let _ = SetterDerive::try_from(&mut global_param, true)?;
+
+    let data = match input.data {
+        Data::Struct(ref data) => data,
+        Data::Enum(_) => {
+            return Err(Error::new_spanned(
+                &input,
+                "Deriving setters is not supported in enums",
+            ))
+        }
+        Data::Union(_) => {
+            return Err(Error::new_spanned(
+                &input,
+                "Deriving setters is not supported in unions",
+            ))
+        }
+    };
+    if let Fields::Unit = data.fields {
+        return Err(Error::new(
+            Span::call_site(),
+            "Deriving setters is meaningless for unit structs",
+        ));
+    }
+
+    let mut methods = Vec::with_capacity(data.fields.len());
+    for (index, field) in data.fields.iter().enumerate() {
+        methods.extend(derive_field_methods(
+            field,
+            index,
+            struct_name,
+            &global_param,
+        )?);
+    }
+
+    Ok(quote! {
+        impl #impl_generics #struct_name #ty_generics #where_clause {
+            #( #methods )*
+        }
+    })
+}
+
+#[derive(Clone)]
+struct SetterDerive {
+    pub skip: bool,
+    pub into: bool,
+    pub base: Option<LitStr>,
+    pub set: Option<LitStr>,
+    pub with: Option<LitStr>,
+    pub vis: Visibility,
+}
+
+impl SetterDerive {
+    fn try_from(attr: &mut ParametrizedAttr, global: bool) -> Result<SetterDerive> {
+        let mut map = HashMap::from_iter(vec![
+            ("all", ArgValueReq::Prohibited),
+            ("set", ArgValueReq::with_default("set_")),
+            ("with", ArgValueReq::with_default("with_")),
+            ("into", ArgValueReq::Prohibited),
+            ("vis", ArgValueReq::Optional(ValueClass::str())),
+        ]);
+
+        if !global {
+            map.insert("skip", ArgValueReq::Prohibited);
+            map.insert("base_name", ArgValueReq::Optional(ValueClass::str()));
+            map.insert("name", ArgValueReq::Optional(ValueClass::str()));
+        }
+
+        attr.check(AttrReq::with(map))?;
+
+        if attr.args.contains_key("all") {
+            if attr.args.contains_key("set") || attr.args.contains_key("with") {
+                return Err(Error::new(
+                    Span::call_site(),
+                    "`all` attribute can't be combined with `set` or `with`",
+                ));
+            }
+            attr.args.remove("all");
+            attr.args.insert("set".to_owned(), ArgValue::from("set_"));
+            attr.args.insert("with".to_owned(), ArgValue::from("with_"));
+        }
+
+        if attr.args.contains_key("base_name") && attr.args.contains_key("name") {
+            return Err(Error::new(
+                Span::call_site(),
+                "`name` is a synonym for `base_name`, so they can't be present together",
+            ));
+        }
+
+        // If we are not provided with any options, default to `set_` methods
+        if !(attr.args.contains_key("set") || attr.args.contains_key("with")) {
+            attr.args.insert("set".to_owned(), ArgValue::from("set_"));
+        }
+
+        Ok(SetterDerive {
+            skip: attr.args.contains_key("skip"),
+            into: attr.args.contains_key("into"),
+            base: attr
+                .args
+                .get("base_name")
+                .or_else(|| attr.args.get("name"))
+                .map(|a| a.clone().try_into())
+                .transpose()?,
+            set: attr
+                .args
+                .get("set")
+                .map(|a| a.clone().try_into())
+                .transpose()?,
+            with: attr
+                .args
+                .get("with")
+                .map(|a| a.clone().try_into())
+                .transpose()?,
+            vis: attr
+                .args
+                .get("vis")
+                .map(|a| -> Result<Visibility> {
+                    let vis: LitStr = a.clone().try_into()?;
+                    vis.parse()
+                })
+                .transpose()?
+                .unwrap_or_else(|| parse_quote! { pub }),
+        })
+    }
+}
+
+#[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Debug)]
+enum SetterMethod {
+    /// Method taking `&mut self` and returning `&mut Self`
+    Set,
+    /// Method taking `self` by value and returning it
+    With,
+}
+
+impl SetterMethod {
+    fn doc_phrase(&self) -> &'static str {
+        match self {
+            SetterMethod::Set => "assigning",
+            SetterMethod::With => "consuming the value and assigning",
+        }
+    }
+
+    fn doc_suffix(&self) -> &'static str {
+        match self {
+            SetterMethod::Set => "returning mutable reference to self for chaining",
+            SetterMethod::With => "returning the updated value",
+        }
+    }
+}
+
+impl SetterDerive {
+    pub fn all_methods(&self) -> Vec<SetterMethod> {
+        let mut methods = Vec::with_capacity(2);
+        if self.set.is_some() {
+            methods.push(SetterMethod::Set);
+        }
+        if self.with.is_some() {
+            methods.push(SetterMethod::With);
+        }
+        methods
+    }
+
+    pub fn setter_fn_ident(
+        &self,
+        method: SetterMethod,
+        field_name: Option<&Ident>,
+        span: Span,
+    ) -> Result<Ident> {
+        let base_string = self
+            .base
+            .as_ref()
+            .map(LitStr::value)
+            .or_else(|| field_name.map(Ident::to_string))
+            .ok_or_else(|| {
+                Error::new(
+                    span,
+                    "Unnamed fields must be equipped with `#[setter(name = \"name\")]` attribute",
+                )
+            })?;
+
+        let prefix = match method {
+            SetterMethod::Set => &self.set,
+            SetterMethod::With => &self.with,
+        }
+        .clone()
+        .expect("Internal inconsistency in setter derivation macro implementation");
+
+        Ok(Ident::new(
+            &format!("{}{}", prefix.value(), base_string),
+            span,
+        ))
+    }
+}
+
+/// Combines type-level setter parameters with the parameters of the field
+fn field_setter(field: &Field, global_param: &ParametrizedAttr) -> Result<SetterDerive> {
+    let local_param = ParametrizedAttr::with("setter", &field.attrs)?;
+
+    // Methods given on the field level replace the type-level ones, so we
+    // need to know whether they were given before the defaults are applied
+    let overrides = local_param
+        .args
+        .keys()
+        .cloned()
+        .chain(
+            local_param
+                .paths
+                .iter()
+                .filter_map(|path| path.get_ident().map(Ident::to_string)),
+        )
+        .any(|arg| arg == "set" || arg == "with" || arg == "all");
+    // First, test individual attribute
+    let mut checked_param = local_param.clone();
+    let _ = SetterDerive::try_from(&mut checked_param, false)?;
+    // Second, combine global and local together
+    let mut params = global_param.clone().merged(local_param)?;
+    if overrides {
+        params.paths.retain(|path| !path.is_ident("all"));
+        for key in &["set", "with"] {
+            params.args.remove(*key);
+            if let Some(arg) = checked_param.args.get(*key) {
+                params.args.insert((*key).to_owned(), arg.clone());
+            }
+        }
+    }
+    SetterDerive::try_from(&mut params, false)
+}
+
+fn derive_field_methods(
+    field: &Field,
+    index: usize,
+    struct_name: &Ident,
+    global_param: &ParametrizedAttr,
+) -> Result<Vec<TokenStream2>> {
+    let setter = field_setter(field, global_param)?;
+
+    if setter.skip {
+        return Ok(Vec::new());
+    }
+
+    let field_name = field.ident.as_ref();
+    let member = field
+        .ident
+        .clone()
+        .map_or_else(|| Member::from(index), Member::Named);
+    let subject = match field_name {
+        Some(name) => format!("[`{}::{}`] field", struct_name, name),
+        None => format!("field `{}` of [`{}`]", index, struct_name),
+    };
+    let ty = &field.ty;
+    let (arg_ty, value) = if setter.into {
+        (
+            quote! { impl ::core::convert::Into<#ty> },
+            quote! { ::core::convert::Into::into(value) },
+        )
+    } else {
+        (quote! { #ty }, quote! { value })
+    };
+
+    let vis = &setter.vis;
+    let mut res = Vec::with_capacity(2);
+    for method in setter.all_methods() {
+        let fn_name = setter.setter_fn_ident(method, field_name, field.span())?;
+        let fn_doc = format!(
+            "Method {} a new value to {}, {}.",
+            method.doc_phrase(),
+            subject,
+            method.doc_suffix()
+        );
+        res.push(match method {
+            SetterMethod::Set => quote_spanned! { field.span() =>
+                #[doc = #fn_doc]
+                #[inline]
+                #vis fn #fn_name(&mut self, value: #arg_ty) -> &mut Self {
+                    self.#member = #value;
+                    self
+                }
+            },
+            SetterMethod::With => quote_spanned! { field.span() =>
+                #[doc = #fn_doc]
+                #[inline]
+                #vis fn #fn_name(mut self, value: #arg_ty) -> Self {
+                    self.#member = #value;
+                    self
+                }
+            },
+        })
+    }
+
+    Ok(res)
+}
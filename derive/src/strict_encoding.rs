@@ -0,0 +1,298 @@
+// Rust language amplification derive library providing multiple generic trait
+// implementations, type wrappers, derive macros and other language enhancements
+//
+// Written in 2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the MIT License
+// along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+use std::collections::HashMap;
+use std::convert::TryInto;
+use std::iter::FromIterator;
+
+use amplify_syn::{ArgValueReq, AttrReq, ParametrizedAttr, ValueClass};
+use proc_macro2::{Ident, Span, TokenStream as TokenStream2};
+use syn::spanned::Spanned;
+use syn::{Data, DataEnum, DeriveInput, Error, Expr, ExprLit, Fields, Lit, LitInt, Member, Result};
+
+use crate::bounds::Bounds;
+use crate::util::get_amplify_crate;
+
+const NAME: &str = "strict";
+
+pub(crate) fn derive_encode(input: DeriveInput) -> Result<TokenStream2> {
+    let amplify_crate = get_amplify_crate(&input);
+    let ident_name = &input.ident;
+
+    let mut bounds = Bounds::with(&input.generics, None);
+    let (body, writer) = match input.data {
+        Data::Struct(ref data) => {
+            for field in &data.fields {
+                bounds.add(&field.ty, quote! { #amplify_crate::StrictEncode });
+            }
+            let members = members(&data.fields);
+            let body = quote! {
+                let mut len = 0usize;
+                #( len += #amplify_crate::StrictEncode::strict_encode(&self.#members, &mut writer)?; )*
+                Ok(len)
+            };
+            if members.is_empty() {
+                (quote! { Ok(0) }, quote! { _: W })
+            } else {
+                (body, quote! { mut writer: W })
+            }
+        }
+        Data::Enum(ref data) => {
+            let tags = tags(data)?;
+            let mut arms = Vec::with_capacity(data.variants.len());
+            for (variant, tag) in data.variants.iter().zip(tags) {
+                for field in &variant.fields {
+                    bounds.add(&field.ty, quote! { #amplify_crate::StrictEncode });
+                }
+                let ident = &variant.ident;
+                if let Fields::Unit = variant.fields {
+                    arms.push(quote_spanned! { variant.span() =>
+                        Self::#ident => #amplify_crate::StrictEncode::strict_encode(&#tag, writer),
+                    });
+                    continue;
+                }
+                let (pattern, bindings) = bindings(&variant.fields);
+                arms.push(quote_spanned! { variant.span() =>
+                    Self::#ident #pattern => {
+                        let mut len = #amplify_crate::StrictEncode::strict_encode(&#tag, &mut writer)?;
+                        #( len += #amplify_crate::StrictEncode::strict_encode(#bindings, &mut writer)?; )*
+                        Ok(len)
+                    }
+                });
+            }
+            (
+                quote! {
+                    match self {
+                        #( #arms )*
+                    }
+                },
+                if data
+                    .variants
+                    .iter()
+                    .all(|v| matches!(v.fields, Fields::Unit))
+                {
+                    quote! { writer: W }
+                } else {
+                    quote! { mut writer: W }
+                },
+            )
+        }
+        Data::Union(_) => {
+            return Err(Error::new_spanned(
+                &input,
+                "Deriving `StrictEncode` is not supported in unions",
+            ))
+        }
+    };
+
+    let generics = bounds.apply(&input.generics);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics #amplify_crate::StrictEncode for #ident_name #ty_generics #where_clause {
+            fn strict_encode<W: ::std::io::Write>(
+                &self,
+                #writer,
+            ) -> ::core::result::Result<usize, #amplify_crate::StrictEncodingError> {
+                #body
+            }
+        }
+    })
+}
+
+pub(crate) fn derive_decode(input: DeriveInput) -> Result<TokenStream2> {
+    let amplify_crate = get_amplify_crate(&input);
+    let ident_name = &input.ident;
+
+    let mut bounds = Bounds::with(&input.generics, None);
+    let decode = quote! { #amplify_crate::StrictDecode::strict_decode(&mut reader)? };
+    let (body, uses_reader) = match input.data {
+        Data::Struct(ref data) => {
+            for field in &data.fields {
+                bounds.add(&field.ty, quote! { #amplify_crate::StrictDecode });
+            }
+            let value = construct(&quote! { Self }, &data.fields, &decode);
+            (quote! { Ok(#value) }, !data.fields.is_empty())
+        }
+        Data::Enum(ref data) => {
+            let tags = tags(data)?;
+            let ident_str = ident_name.to_string();
+            let mut arms = Vec::with_capacity(data.variants.len());
+            for (variant, tag) in data.variants.iter().zip(tags) {
+                for field in &variant.fields {
+                    bounds.add(&field.ty, quote! { #amplify_crate::StrictDecode });
+                }
+                let ident = &variant.ident;
+                let value = construct(&quote! { Self::#ident }, &variant.fields, &decode);
+                arms.push(quote_spanned! { variant.span() => #tag => Ok(#value), });
+            }
+            (
+                quote! {
+                    match <u8 as #amplify_crate::StrictDecode>::strict_decode(&mut reader)? {
+                        #( #arms )*
+                        tag => Err(#amplify_crate::StrictEncodingError::UnknownTag {
+                            ty: #ident_str,
+                            tag,
+                        }),
+                    }
+                },
+                true,
+            )
+        }
+        Data::Union(_) => {
+            return Err(Error::new_spanned(
+                &input,
+                "Deriving `StrictDecode` is not supported in unions",
+            ))
+        }
+    };
+
+    let generics = bounds.apply(&input.generics);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let reader = if uses_reader {
+        quote! { mut reader: R }
+    } else {
+        quote! { _: R }
+    };
+
+    Ok(quote! {
+        impl #impl_generics #amplify_crate::StrictDecode for #ident_name #ty_generics #where_clause {
+            fn strict_decode<R: ::std::io::Read>(
+                #reader,
+            ) -> ::core::result::Result<Self, #amplify_crate::StrictEncodingError> {
+                #body
+            }
+        }
+    })
+}
+
+/// Returns members of the fields in the order of their declaration
+fn members(fields: &Fields) -> Vec<Member> {
+    fields
+        .iter()
+        .enumerate()
+        .map(|(index, field)| {
+            field
+                .ident
+                .clone()
+                .map_or_else(|| Member::from(index), Member::Named)
+        })
+        .collect()
+}
+
+/// Returns pattern destructuring enum variant fields and the names of the
+/// variables bound by the pattern
+fn bindings(fields: &Fields) -> (TokenStream2, Vec<Ident>) {
+    let members = members(fields);
+    let names = members
+        .iter()
+        .map(|member| match member {
+            Member::Named(ident) => ident.clone(),
+            Member::Unnamed(index) => Ident::new(&format!("_{}", index.index), index.span),
+        })
+        .collect::<Vec<_>>();
+    let pattern = match fields {
+        Fields::Named(_) => quote! { { #( #names ),* } },
+        Fields::Unnamed(_) => quote! { ( #( #names ),* ) },
+        Fields::Unit => quote! {},
+    };
+    (pattern, names)
+}
+
+/// Returns expression constructing the struct or enum variant with each field
+/// initialized with `value`
+fn construct(path: &TokenStream2, fields: &Fields, value: &TokenStream2) -> TokenStream2 {
+    let members = members(fields);
+    match fields {
+        Fields::Named(_) => quote! { #path { #( #members: #value ),* } },
+        Fields::Unnamed(_) => {
+            let values = members.iter().map(|_| value);
+            quote! { #path ( #( #values ),* ) }
+        }
+        Fields::Unit => quote! { #path },
+    }
+}
+
+/// Computes tags of the enum variants: explicit `#[strict(tag = N)]` or the
+/// discriminant, if any; otherwise the tag of the previous variant plus one,
+/// starting with zero
+fn tags(data: &DataEnum) -> Result<Vec<LitInt>> {
+    if data.variants.is_empty() {
+        return Err(Error::new(
+            Span::call_site(),
+            "strict encoding can't be derived for enums without variants",
+        ));
+    }
+    let mut tags = Vec::<u8>::with_capacity(data.variants.len());
+    let mut next = Some(0u8);
+    for variant in &data.variants {
+        let mut attr = ParametrizedAttr::with(NAME, &variant.attrs)?;
+        attr.check(AttrReq::with(HashMap::from_iter(vec![(
+            "tag",
+            ArgValueReq::Optional(ValueClass::int()),
+        )])))?;
+        let tag: Option<LitInt> = attr
+            .args
+            .get("tag")
+            .map(|a| a.clone().try_into())
+            .transpose()?;
+        let tag = match (tag, &variant.discriminant) {
+            (Some(tag), _) => tag
+                .base10_parse::<u8>()
+                .map_err(|_| Error::new(tag.span(), "strict encoding tags must fit into `u8`"))?,
+            (
+                None,
+                Some((
+                    _,
+                    Expr::Lit(ExprLit {
+                        lit: Lit::Int(lit), ..
+                    }),
+                )),
+            ) => lit.base10_parse::<u8>().map_err(|_| {
+                Error::new(
+                    lit.span(),
+                    "discriminants used as strict encoding tags must fit into `u8`; use \
+                     `#[strict(tag = N)]` to provide a different tag",
+                )
+            })?,
+            (None, Some((_, expr))) => {
+                return Err(Error::new(
+                    expr.span(),
+                    "complex discriminants can't be used as strict encoding tags; use \
+                     `#[strict(tag = N)]` to provide explicit tag",
+                ))
+            }
+            (None, None) => next.ok_or_else(|| {
+                Error::new(
+                    variant.span(),
+                    "strict encoding tag of the variant exceeds `u8`; use `#[strict(tag = N)]` \
+                     to provide explicit tag",
+                )
+            })?,
+        };
+        if tags.contains(&tag) {
+            return Err(Error::new(
+                variant.span(),
+                format!("strict encoding tag {} is repeated", tag),
+            ));
+        }
+        next = tag.checked_add(1);
+        tags.push(tag);
+    }
+    Ok(tags
+        .into_iter()
+        .map(|tag| LitInt::new(&format!("{}u8", tag), Span::call_site()))
+        .collect())
+}
@@ -15,6 +15,7 @@
 
 #![allow(dead_code)]
 
+use proc_macro2::TokenStream as TokenStream2;
 use syn::{DeriveInput, Lit, Result, Meta, Ident, Attribute, NestedMeta, Path, MetaNameValue};
 use syn::punctuated::IntoIter;
 use syn::spanned::Spanned;
@@ -42,6 +43,41 @@ macro_rules! attr_err {
     };
 }
 
+/// Standard library environment targeted by the generated code, which is
+/// selected with the features of this crate
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub(crate) enum Target {
+    /// Generated code uses `std`
+    Std,
+    /// Generated code uses `core` and `alloc`; requires the user crate to have
+    /// `extern crate alloc`
+    Alloc,
+    /// Generated code uses `core` only
+    Core,
+}
+
+impl Target {
+    /// Detects the target from the features this crate was compiled with
+    pub fn detect() -> Self {
+        if cfg!(feature = "std") {
+            Target::Std
+        } else if cfg!(feature = "alloc") {
+            Target::Alloc
+        } else {
+            Target::Core
+        }
+    }
+
+    /// Path to the `Error` trait. Outside of `std` this is
+    /// `core::error::Error`, which is stable since Rust 1.81 only
+    pub fn error_trait(self) -> TokenStream2 {
+        match self {
+            Target::Std => quote! { ::std::error::Error },
+            Target::Alloc | Target::Core => quote! { ::core::error::Error },
+        }
+    }
+}
+
 pub(crate) fn get_amplify_crate(input: &DeriveInput) -> Path {
     let name = "amplify_crate";
     let example = "#[amplify_crate(amplify_crate_path)]";
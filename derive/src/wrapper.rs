@@ -14,15 +14,23 @@
 // If not, see <https://opensource.org/licenses/MIT>.
 
 use proc_macro2::TokenStream as TokenStream2;
+use quote::ToTokens;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
 use syn::{
-    DeriveInput, Result, Data, Error, Fields, Index, Meta, MetaList, Path, NestedMeta,
-    spanned::Spanned,
+    DeriveInput, Generics, Result, Data, Error, Fields, Ident, Index, Member, Meta, Path,
+    NestedMeta, Type, WherePredicate, spanned::Spanned,
 };
 
+use crate::bounds::{parse_bound, Bounds};
+use crate::from;
 use crate::util::get_amplify_crate;
 
 const NAME: &str = "wrapper";
 const EXAMPLE: &str = r#"#[wrapper(LowerHex, Add)]"#;
+const NAME_MUT: &str = "wrapper_mut";
+const EXAMPLE_MUT: &str = r#"#[wrapper_mut(AddAssign, IndexMut)]"#;
+const EXAMPLE_VALIDATE: &str = r#"#[wrapper(validate = Self::check, error = Error)]"#;
 
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
 enum WrapperDerives {
@@ -34,6 +42,9 @@ enum WrapperDerives {
     UpperHex,
     LowerExp,
     UpperExp,
+    Hex,
+    Serialize,
+    Deserialize,
     BorrowSlice,
     Index,
     IndexMut,
@@ -64,6 +75,16 @@ enum WrapperDerives {
     BitAndAssign,
     BitOrAssign,
     BitXorAssign,
+    Binary,
+    IntoIterator,
+    IntoIteratorRef,
+    FromIterator,
+    Extend,
+    Sum,
+    Product,
+    PartialEqInner,
+    PartialOrdInner,
+    Hash,
 }
 
 impl WrapperDerives {
@@ -85,6 +106,9 @@ impl WrapperDerives {
                     "UpperHex" => Some(WrapperDerives::UpperHex),
                     "LowerExp" => Some(WrapperDerives::LowerExp),
                     "UpperExp" => Some(WrapperDerives::UpperExp),
+                    "Hex" => Some(WrapperDerives::Hex),
+                    "Serialize" => Some(WrapperDerives::Serialize),
+                    "Deserialize" => Some(WrapperDerives::Deserialize),
                     "BorrowSlice" => Some(WrapperDerives::BorrowSlice),
                     "Index" => Some(WrapperDerives::Index),
                     "IndexMut" => Some(WrapperDerives::IndexMut),
@@ -115,30 +139,204 @@ impl WrapperDerives {
                     "BitAndAssign" => Some(WrapperDerives::BitAndAssign),
                     "BitOrAssign" => Some(WrapperDerives::BitOrAssign),
                     "BitXorAssign" => Some(WrapperDerives::BitXorAssign),
+                    "Binary" => Some(WrapperDerives::Binary),
+                    "IntoIterator" => Some(WrapperDerives::IntoIterator),
+                    "IntoIteratorRef" => Some(WrapperDerives::IntoIteratorRef),
+                    "FromIterator" => Some(WrapperDerives::FromIterator),
+                    "Extend" => Some(WrapperDerives::Extend),
+                    "Sum" => Some(WrapperDerives::Sum),
+                    "Product" => Some(WrapperDerives::Product),
+                    "PartialEqInner" => Some(WrapperDerives::PartialEqInner),
+                    "PartialOrdInner" => Some(WrapperDerives::PartialOrdInner),
+                    "Hash" => Some(WrapperDerives::Hash),
                     _ => None,
                 })
             },
         )
     }
 
-    pub fn into_token_stream2(self, input: &DeriveInput) -> TokenStream2 {
-        let impl_generics_params = input.generics.params.clone();
-        let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    /// Detects whether the implementation requires mutable access to the
+    /// inner data, and thus can be used only with `WrapperMut` derive
+    pub fn is_mut(self) -> bool {
+        matches!(
+            self,
+            WrapperDerives::IndexMut
+                | WrapperDerives::AddAssign
+                | WrapperDerives::SubAssign
+                | WrapperDerives::MulAssign
+                | WrapperDerives::DivAssign
+                | WrapperDerives::RemAssign
+                | WrapperDerives::ShlAssign
+                | WrapperDerives::ShrAssign
+                | WrapperDerives::BitAndAssign
+                | WrapperDerives::BitOrAssign
+                | WrapperDerives::BitXorAssign
+                | WrapperDerives::Extend
+        )
+    }
+
+    /// Detects whether the implementation constructs the wrapper from the
+    /// inner data produced by the inner type, and thus would bypass the
+    /// validator given with `#[wrapper(validate = ...)]`
+    pub fn constructs(self) -> bool {
+        matches!(
+            self,
+            WrapperDerives::Hex
+                | WrapperDerives::Neg
+                | WrapperDerives::Not
+                | WrapperDerives::Add
+                | WrapperDerives::Sub
+                | WrapperDerives::Mul
+                | WrapperDerives::Div
+                | WrapperDerives::Rem
+                | WrapperDerives::Shl
+                | WrapperDerives::Shr
+                | WrapperDerives::BitAnd
+                | WrapperDerives::BitOr
+                | WrapperDerives::BitXor
+                | WrapperDerives::FromIterator
+                | WrapperDerives::Sum
+                | WrapperDerives::Product
+        )
+    }
+
+    /// Returns trait bound required from the inner type by the implementation.
+    /// Implementations with additional generic parameters add their bounds
+    /// themselves, returning `None`.
+    pub fn bound(self, inner: &Type, amplify_crate: &Path) -> Option<TokenStream2> {
+        Some(match self {
+            WrapperDerives::FromStr => quote! { ::core::str::FromStr },
+            WrapperDerives::Display => quote! { ::core::fmt::Display },
+            WrapperDerives::Debug => quote! { ::core::fmt::Debug },
+            WrapperDerives::Octal => quote! { ::core::fmt::Octal },
+            WrapperDerives::LowerHex => quote! { ::core::fmt::LowerHex },
+            WrapperDerives::UpperHex => quote! { ::core::fmt::UpperHex },
+            WrapperDerives::LowerExp => quote! { ::core::fmt::LowerExp },
+            WrapperDerives::UpperExp => quote! { ::core::fmt::UpperExp },
+            WrapperDerives::Hex => {
+                quote! { ::core::fmt::LowerHex + #amplify_crate::hex::FromHex }
+            }
+            WrapperDerives::Serialize => quote! { #amplify_crate::_export::serde::Serialize },
+            WrapperDerives::Deserialize => quote! { #amplify_crate::_export::serde::Deserialize<'de> },
+            WrapperDerives::BorrowSlice => quote! { ::core::borrow::Borrow<[u8]> },
+            WrapperDerives::Index => quote! { ::core::ops::Index<usize> },
+            WrapperDerives::IndexMut => quote! { ::core::ops::IndexMut<usize> },
+            WrapperDerives::IndexRange => {
+                quote! { ::core::ops::Index<::core::ops::Range<usize>> }
+            }
+            WrapperDerives::IndexFull => quote! { ::core::ops::Index<::core::ops::RangeFull> },
+            WrapperDerives::IndexFrom => {
+                quote! { ::core::ops::Index<::core::ops::RangeFrom<usize>> }
+            }
+            WrapperDerives::IndexTo => {
+                quote! { ::core::ops::Index<::core::ops::RangeTo<usize>> }
+            }
+            WrapperDerives::IndexInclusive => {
+                quote! { ::core::ops::Index<::core::ops::RangeInclusive<usize>> }
+            }
+            WrapperDerives::Neg => quote! { ::core::ops::Neg<Output = #inner> },
+            WrapperDerives::Not => quote! { ::core::ops::Not<Output = #inner> },
+            WrapperDerives::Add => quote! { ::core::ops::Add<Output = #inner> },
+            WrapperDerives::Sub => quote! { ::core::ops::Sub<Output = #inner> },
+            WrapperDerives::Mul => quote! { ::core::ops::Mul<Output = #inner> },
+            WrapperDerives::Div => quote! { ::core::ops::Div<Output = #inner> },
+            WrapperDerives::Rem => quote! { ::core::ops::Rem<Output = #inner> },
+            WrapperDerives::Shl => quote! { ::core::ops::Shl<Output = #inner> },
+            WrapperDerives::Shr => quote! { ::core::ops::Shr<Output = #inner> },
+            WrapperDerives::BitAnd => quote! { ::core::ops::BitAnd<Output = #inner> },
+            WrapperDerives::BitOr => quote! { ::core::ops::BitOr<Output = #inner> },
+            WrapperDerives::BitXor => quote! { ::core::ops::BitXor<Output = #inner> },
+            WrapperDerives::AddAssign => quote! { ::core::ops::AddAssign },
+            WrapperDerives::SubAssign => quote! { ::core::ops::SubAssign },
+            WrapperDerives::MulAssign => quote! { ::core::ops::MulAssign },
+            WrapperDerives::DivAssign => quote! { ::core::ops::DivAssign },
+            WrapperDerives::RemAssign => quote! { ::core::ops::RemAssign },
+            WrapperDerives::ShlAssign => quote! { ::core::ops::ShlAssign },
+            WrapperDerives::ShrAssign => quote! { ::core::ops::ShrAssign },
+            WrapperDerives::BitAndAssign => quote! { ::core::ops::BitAndAssign },
+            WrapperDerives::BitOrAssign => quote! { ::core::ops::BitOrAssign },
+            WrapperDerives::BitXorAssign => quote! { ::core::ops::BitXorAssign },
+            WrapperDerives::Binary => quote! { ::core::fmt::Binary },
+            WrapperDerives::IntoIterator => quote! { ::core::iter::IntoIterator },
+            WrapperDerives::Sum => quote! { ::core::iter::Sum },
+            WrapperDerives::Product => quote! { ::core::iter::Product },
+            WrapperDerives::PartialEqInner => quote! { ::core::cmp::PartialEq },
+            WrapperDerives::PartialOrdInner => quote! { ::core::cmp::PartialOrd },
+            WrapperDerives::Hash => quote! { ::core::hash::Hash },
+            WrapperDerives::IntoIteratorRef
+            | WrapperDerives::FromIterator
+            | WrapperDerives::Extend => return None,
+        })
+    }
+
+    /// Generates implementation for the type with the generics extended with
+    /// the required bounds. For the validated types `FromStr` and
+    /// `Deserialize` run the validator before constructing the wrapper.
+    /// Deep wrappers delegate the implementations to the innermost type using
+    /// `DeepWrapper` trait.
+    pub fn into_token_stream2(
+        self,
+        input: &DeriveInput,
+        generics: &Generics,
+        validator: Option<&(Path, Type)>,
+        deep: bool,
+    ) -> TokenStream2 {
+        let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
         let ident_name = &input.ident;
         let amplify_crate = get_amplify_crate(input);
+        let (wrapper, inner, as_inner, from_inner, into_inner) = if deep {
+            (
+                quote! { DeepWrapper },
+                quote! { Deep },
+                quote! { as_deep },
+                quote! { from_deep },
+                quote! { into_deep },
+            )
+        } else {
+            (
+                quote! { Wrapper },
+                quote! { Inner },
+                quote! { as_inner },
+                quote! { from_inner },
+                quote! { into_inner },
+            )
+        };
+        let (wrapper_mut, as_inner_mut) = if deep {
+            (quote! { DeepWrapperMut }, quote! { as_deep_mut })
+        } else {
+            (quote! { WrapperMut }, quote! { as_inner_mut })
+        };
 
         match self {
+            WrapperDerives::FromStr if validator.is_some() => {
+                let (validate, error) = validator.expect("checked above");
+                quote! {
+                    impl #impl_generics ::core::str::FromStr for #ident_name #ty_generics #where_clause
+                    {
+                        type Err = #error;
+
+                        #[inline]
+                        fn from_str(s: &str) -> Result<Self, Self::Err> {
+                            use ::core::str::FromStr;
+                            use #amplify_crate::#wrapper;
+                            let inner = <Self as #wrapper>::#inner::from_str(s)?;
+                            #validate(&inner)?;
+                            Ok(#wrapper::#from_inner(inner))
+                        }
+                    }
+                }
+            }
             WrapperDerives::FromStr => quote! {
                 impl #impl_generics ::core::str::FromStr for #ident_name #ty_generics #where_clause
                 {
-                    type Err = <<Self as #amplify_crate::Wrapper>::Inner as ::core::str::FromStr>::Err;
+                    type Err = <<Self as #amplify_crate::#wrapper>::#inner as ::core::str::FromStr>::Err;
 
                     #[inline]
                     fn from_str(s: &str) -> Result<Self, Self::Err> {
                         use ::core::str::FromStr;
-                        use #amplify_crate::Wrapper;
-                        Ok(Wrapper::from_inner(
-                            <Self as #amplify_crate::Wrapper>::Inner::from_str(s)?,
+                        use #amplify_crate::#wrapper;
+                        Ok(#wrapper::#from_inner(
+                            <Self as #amplify_crate::#wrapper>::#inner::from_str(s)?,
                         ))
                     }
                 }
@@ -148,8 +346,8 @@ impl WrapperDerives {
                 {
                     #[inline]
                     fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
-                        use #amplify_crate::Wrapper;
-                        ::core::fmt::Display::fmt(Wrapper::as_inner(self), f)
+                        use #amplify_crate::#wrapper;
+                        ::core::fmt::Display::fmt(#wrapper::#as_inner(self), f)
                     }
                 }
             },
@@ -158,8 +356,8 @@ impl WrapperDerives {
                 {
                     #[inline]
                     fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
-                        use #amplify_crate::Wrapper;
-                        ::core::fmt::Debug::fmt(Wrapper::as_inner(self), f)
+                        use #amplify_crate::#wrapper;
+                        ::core::fmt::Debug::fmt(#wrapper::#as_inner(self), f)
                     }
                 }
             },
@@ -168,8 +366,8 @@ impl WrapperDerives {
                 {
                     #[inline]
                     fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
-                        use #amplify_crate::Wrapper;
-                        ::core::fmt::Octal::fmt(Wrapper::as_inner(self), f)
+                        use #amplify_crate::#wrapper;
+                        ::core::fmt::Octal::fmt(#wrapper::#as_inner(self), f)
                     }
                 }
             },
@@ -178,8 +376,8 @@ impl WrapperDerives {
                 {
                     #[inline]
                     fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
-                        use #amplify_crate::Wrapper;
-                        ::core::fmt::LowerHex::fmt(Wrapper::as_inner(self), f)
+                        use #amplify_crate::#wrapper;
+                        ::core::fmt::LowerHex::fmt(#wrapper::#as_inner(self), f)
                     }
                 }
             },
@@ -188,8 +386,8 @@ impl WrapperDerives {
                 {
                     #[inline]
                     fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
-                        use #amplify_crate::Wrapper;
-                        ::core::fmt::UpperHex::fmt(Wrapper::as_inner(self), f)
+                        use #amplify_crate::#wrapper;
+                        ::core::fmt::UpperHex::fmt(#wrapper::#as_inner(self), f)
                     }
                 }
             },
@@ -198,8 +396,8 @@ impl WrapperDerives {
                 {
                     #[inline]
                     fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
-                        use #amplify_crate::Wrapper;
-                        ::core::fmt::LowerExp::fmt(Wrapper::as_inner(self), f)
+                        use #amplify_crate::#wrapper;
+                        ::core::fmt::LowerExp::fmt(#wrapper::#as_inner(self), f)
                     }
                 }
             },
@@ -208,121 +406,191 @@ impl WrapperDerives {
                 {
                     #[inline]
                     fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
-                        use #amplify_crate::Wrapper;
-                        ::core::fmt::UpperExp::fmt(Wrapper::as_inner(self), f)
+                        use #amplify_crate::#wrapper;
+                        ::core::fmt::UpperExp::fmt(#wrapper::#as_inner(self), f)
                     }
                 }
             },
+            WrapperDerives::Hex => quote! {
+                impl #impl_generics ::core::fmt::LowerHex for #ident_name #ty_generics #where_clause
+                {
+                    #[inline]
+                    fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                        use #amplify_crate::#wrapper;
+                        ::core::fmt::LowerHex::fmt(#wrapper::#as_inner(self), f)
+                    }
+                }
+
+                impl #impl_generics #amplify_crate::hex::FromHex for #ident_name #ty_generics #where_clause
+                {
+                    #[inline]
+                    fn from_byte_iter<I>(iter: I) -> Result<Self, #amplify_crate::hex::Error>
+                    where
+                        I: Iterator<Item = Result<u8, #amplify_crate::hex::Error>>
+                            + ExactSizeIterator
+                            + DoubleEndedIterator,
+                    {
+                        use #amplify_crate::#wrapper;
+                        <<Self as #wrapper>::#inner as #amplify_crate::hex::FromHex>::from_byte_iter(iter)
+                            .map(#wrapper::#from_inner)
+                    }
+                }
+            },
+            WrapperDerives::Serialize => quote! {
+                impl #impl_generics #amplify_crate::_export::serde::Serialize for #ident_name #ty_generics #where_clause
+                {
+                    #[inline]
+                    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+                    where
+                        S: #amplify_crate::_export::serde::Serializer,
+                    {
+                        use #amplify_crate::#wrapper;
+                        #amplify_crate::_export::serde::Serialize::serialize(#wrapper::#as_inner(self), serializer)
+                    }
+                }
+            },
+            WrapperDerives::Deserialize if validator.is_some() => {
+                let (validate, _) = validator.expect("checked above");
+                let mut generics = generics.clone();
+                generics.params.insert(0, parse_quote! { 'de });
+                let (impl_generics, _, _) = generics.split_for_impl();
+                quote! {
+                    impl #impl_generics #amplify_crate::_export::serde::Deserialize<'de> for #ident_name #ty_generics #where_clause
+                    {
+                        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+                        where
+                            D: #amplify_crate::_export::serde::Deserializer<'de>,
+                        {
+                            use #amplify_crate::_export::serde::de::Error;
+                            use #amplify_crate::#wrapper;
+                            let inner = <<Self as #wrapper>::#inner as #amplify_crate::_export::serde::Deserialize<'de>>::deserialize(deserializer)?;
+                            #validate(&inner).map_err(D::Error::custom)?;
+                            Ok(#wrapper::#from_inner(inner))
+                        }
+                    }
+                }
+            }
+            WrapperDerives::Deserialize => {
+                let mut generics = generics.clone();
+                generics.params.insert(0, parse_quote! { 'de });
+                let (impl_generics, _, _) = generics.split_for_impl();
+                quote! {
+                    impl #impl_generics #amplify_crate::_export::serde::Deserialize<'de> for #ident_name #ty_generics #where_clause
+                    {
+                        #[inline]
+                        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+                        where
+                            D: #amplify_crate::_export::serde::Deserializer<'de>,
+                        {
+                            use #amplify_crate::#wrapper;
+                            <<Self as #wrapper>::#inner as #amplify_crate::_export::serde::Deserialize<'de>>::deserialize(deserializer)
+                                .map(#wrapper::#from_inner)
+                        }
+                    }
+                }
+            }
             WrapperDerives::BorrowSlice => quote! {
                 impl #impl_generics ::core::borrow::Borrow<[u8]> for #ident_name #ty_generics #where_clause
                 {
                     #[inline]
                     fn borrow(&self) -> &[u8] {
-                        use #amplify_crate::Wrapper;
-                        ::core::borrow::Borrow::<[u8]>::borrow(Wrapper::as_inner(self))
+                        use #amplify_crate::#wrapper;
+                        ::core::borrow::Borrow::<[u8]>::borrow(#wrapper::#as_inner(self))
                     }
                 }
             },
             WrapperDerives::Index => {
-                let where_clause = match where_clause {
-                    None => quote! { where },
-                    Some(_) => quote! { #where_clause },
-                };
                 quote! {
-                    impl <#impl_generics_params> ::core::ops::Index<usize> for #ident_name #ty_generics #where_clause
+                    impl #impl_generics ::core::ops::Index<usize> for #ident_name #ty_generics #where_clause
                     {
-                        type Output = <<Self as #amplify_crate::Wrapper>::Inner as ::core::ops::Index<usize>>::Output;
+                        type Output = <<Self as #amplify_crate::#wrapper>::#inner as ::core::ops::Index<usize>>::Output;
 
                         #[inline]
                         fn index(&self, index: usize) -> &Self::Output {
-                            use #amplify_crate::Wrapper;
-                            Wrapper::as_inner(self).index(index)
+                            use #amplify_crate::#wrapper;
+                            #wrapper::#as_inner(self).index(index)
                         }
                     }
                 }
             }
             WrapperDerives::IndexMut => {
-                let where_clause = match where_clause {
-                    None => quote! { where },
-                    Some(_) => quote! { #where_clause },
-                };
                 quote! {
-                    impl <#impl_generics_params> ::core::ops::IndexMut<usize> for #ident_name #ty_generics #where_clause
+                    impl #impl_generics ::core::ops::IndexMut<usize> for #ident_name #ty_generics #where_clause
                     {
                         #[inline]
                         fn index_mut(&mut self, index: usize) -> &mut Self::Output {
-                            use #amplify_crate::Wrapper;
-                            Wrapper::as_inner_mut(self).index_mut(index)
+                            use #amplify_crate::#wrapper_mut;
+                            ::core::ops::IndexMut::index_mut(#wrapper_mut::#as_inner_mut(self), index)
                         }
                     }
                 }
             }
             WrapperDerives::IndexRange => {
                 quote! {
-                    impl <#impl_generics_params> ::core::ops::Index<::core::ops::Range<usize>> for #ident_name #ty_generics #where_clause
+                    impl #impl_generics ::core::ops::Index<::core::ops::Range<usize>> for #ident_name #ty_generics #where_clause
                     {
-                        type Output = <<Self as #amplify_crate::Wrapper>::Inner as ::core::ops::Index<::core::ops::Range<usize>>>::Output;
+                        type Output = <<Self as #amplify_crate::#wrapper>::#inner as ::core::ops::Index<::core::ops::Range<usize>>>::Output;
 
                         #[inline]
                         fn index(&self, index: ::core::ops::Range<usize>) -> &Self::Output {
-                            use #amplify_crate::Wrapper;
-                            Wrapper::as_inner(self).index(index)
+                            use #amplify_crate::#wrapper;
+                            #wrapper::#as_inner(self).index(index)
                         }
                     }
                 }
             }
             WrapperDerives::IndexFrom => {
                 quote! {
-                    impl <#impl_generics_params> ::core::ops::Index<::core::ops::RangeFrom<usize>> for #ident_name #ty_generics #where_clause
+                    impl #impl_generics ::core::ops::Index<::core::ops::RangeFrom<usize>> for #ident_name #ty_generics #where_clause
                     {
-                        type Output = <<Self as #amplify_crate::Wrapper>::Inner as ::core::ops::Index<::core::ops::RangeFrom<usize>>>::Output;
+                        type Output = <<Self as #amplify_crate::#wrapper>::#inner as ::core::ops::Index<::core::ops::RangeFrom<usize>>>::Output;
 
                         #[inline]
                         fn index(&self, index: ::core::ops::RangeFrom<usize>) -> &Self::Output {
-                            use #amplify_crate::Wrapper;
-                            Wrapper::as_inner(self).index(index)
+                            use #amplify_crate::#wrapper;
+                            #wrapper::#as_inner(self).index(index)
                         }
                     }
                 }
             }
             WrapperDerives::IndexTo => {
                 quote! {
-                    impl <#impl_generics_params> ::core::ops::Index<::core::ops::RangeTo<usize>> for #ident_name #ty_generics #where_clause
+                    impl #impl_generics ::core::ops::Index<::core::ops::RangeTo<usize>> for #ident_name #ty_generics #where_clause
                     {
-                        type Output = <<Self as #amplify_crate::Wrapper>::Inner as ::core::ops::Index<::core::ops::RangeTo<usize>>>::Output;
+                        type Output = <<Self as #amplify_crate::#wrapper>::#inner as ::core::ops::Index<::core::ops::RangeTo<usize>>>::Output;
 
                         #[inline]
                         fn index(&self, index: ::core::ops::RangeTo<usize>) -> &Self::Output {
-                            use #amplify_crate::Wrapper;
-                            Wrapper::as_inner(self).index(index)
+                            use #amplify_crate::#wrapper;
+                            #wrapper::#as_inner(self).index(index)
                         }
                     }
                 }
             }
             WrapperDerives::IndexInclusive => {
                 quote! {
-                    impl <#impl_generics_params> ::core::ops::Index<::core::ops::RangeInclusive<usize>> for #ident_name #ty_generics #where_clause
+                    impl #impl_generics ::core::ops::Index<::core::ops::RangeInclusive<usize>> for #ident_name #ty_generics #where_clause
                     {
-                        type Output = <<Self as #amplify_crate::Wrapper>::Inner as ::core::ops::Index<::core::ops::RangeInclusive<usize>>>::Output;
+                        type Output = <<Self as #amplify_crate::#wrapper>::#inner as ::core::ops::Index<::core::ops::RangeInclusive<usize>>>::Output;
 
                         #[inline]
                         fn index(&self, index: ::core::ops::RangeInclusive<usize>) -> &Self::Output {
-                            use #amplify_crate::Wrapper;
-                            Wrapper::as_inner(self).index(index)
+                            use #amplify_crate::#wrapper;
+                            #wrapper::#as_inner(self).index(index)
                         }
                     }
                 }
             }
             WrapperDerives::IndexFull => {
                 quote! {
-                    impl <#impl_generics_params> ::core::ops::Index<::core::ops::RangeFull> for #ident_name #ty_generics #where_clause
+                    impl #impl_generics ::core::ops::Index<::core::ops::RangeFull> for #ident_name #ty_generics #where_clause
                     {
-                        type Output = <<Self as #amplify_crate::Wrapper>::Inner as ::core::ops::Index<::core::ops::RangeFull>>::Output;
+                        type Output = <<Self as #amplify_crate::#wrapper>::#inner as ::core::ops::Index<::core::ops::RangeFull>>::Output;
 
                         #[inline]
                         fn index(&self, index: ::core::ops::RangeFull) -> &Self::Output {
-                            use #amplify_crate::Wrapper;
-                            Wrapper::as_inner(self).index(index)
+                            use #amplify_crate::#wrapper;
+                            #wrapper::#as_inner(self).index(index)
                         }
                     }
                 }
@@ -334,8 +602,8 @@ impl WrapperDerives {
 
                     #[inline]
                     fn neg(self) -> Self {
-                        use #amplify_crate::Wrapper;
-                        Wrapper::from_inner(::core::ops::Neg::neg(Wrapper::into_inner(self)))
+                        use #amplify_crate::#wrapper;
+                        #wrapper::#from_inner(::core::ops::Neg::neg(#wrapper::#into_inner(self)))
                     }
                 }
             },
@@ -346,8 +614,8 @@ impl WrapperDerives {
 
                     #[inline]
                     fn not(self) -> Self {
-                        use #amplify_crate::Wrapper;
-                        Wrapper::from_inner(::core::ops::Not::not(Wrapper::into_inner(self)))
+                        use #amplify_crate::#wrapper;
+                        #wrapper::#from_inner(::core::ops::Not::not(#wrapper::#into_inner(self)))
                     }
                 }
             },
@@ -358,8 +626,8 @@ impl WrapperDerives {
 
                     #[inline]
                     fn add(self, rhs: Self) -> Self {
-                        use #amplify_crate::Wrapper;
-                        Wrapper::from_inner(::core::ops::Add::add(Wrapper::into_inner(self), rhs.into_inner()))
+                        use #amplify_crate::#wrapper;
+                        #wrapper::#from_inner(::core::ops::Add::add(#wrapper::#into_inner(self), #wrapper::#into_inner(rhs)))
                     }
                 }
             },
@@ -370,8 +638,8 @@ impl WrapperDerives {
 
                     #[inline]
                     fn sub(self, rhs: Self) -> Self {
-                        use #amplify_crate::Wrapper;
-                        Wrapper::from_inner(::core::ops::Sub::sub(Wrapper::into_inner(self), rhs.into_inner()))
+                        use #amplify_crate::#wrapper;
+                        #wrapper::#from_inner(::core::ops::Sub::sub(#wrapper::#into_inner(self), #wrapper::#into_inner(rhs)))
                     }
                 }
             },
@@ -382,8 +650,8 @@ impl WrapperDerives {
 
                     #[inline]
                     fn mul(self, rhs: Self) -> Self {
-                        use #amplify_crate::Wrapper;
-                        Wrapper::from_inner(::core::ops::Mul::mul(Wrapper::into_inner(self), rhs.into_inner()))
+                        use #amplify_crate::#wrapper;
+                        #wrapper::#from_inner(::core::ops::Mul::mul(#wrapper::#into_inner(self), #wrapper::#into_inner(rhs)))
                     }
                 }
             },
@@ -394,8 +662,8 @@ impl WrapperDerives {
 
                     #[inline]
                     fn div(self, rhs: Self) -> Self {
-                        use #amplify_crate::Wrapper;
-                        Wrapper::from_inner(::core::ops::Div::div(Wrapper::into_inner(self), rhs.into_inner()))
+                        use #amplify_crate::#wrapper;
+                        #wrapper::#from_inner(::core::ops::Div::div(#wrapper::#into_inner(self), #wrapper::#into_inner(rhs)))
                     }
                 }
             },
@@ -406,8 +674,8 @@ impl WrapperDerives {
 
                     #[inline]
                     fn rem(self, rhs: Self) -> Self {
-                        use #amplify_crate::Wrapper;
-                        Wrapper::from_inner(::core::ops::Rem::rem(Wrapper::into_inner(self), rhs.into_inner()))
+                        use #amplify_crate::#wrapper;
+                        #wrapper::#from_inner(::core::ops::Rem::rem(#wrapper::#into_inner(self), #wrapper::#into_inner(rhs)))
                     }
                 }
             },
@@ -418,8 +686,8 @@ impl WrapperDerives {
 
                     #[inline]
                     fn shl(self, rhs: Self) -> Self {
-                        use #amplify_crate::Wrapper;
-                        Wrapper::from_inner(::core::ops::Shl::shl(Wrapper::into_inner(self), rhs.into_inner()))
+                        use #amplify_crate::#wrapper;
+                        #wrapper::#from_inner(::core::ops::Shl::shl(#wrapper::#into_inner(self), #wrapper::#into_inner(rhs)))
                     }
                 }
             },
@@ -430,8 +698,8 @@ impl WrapperDerives {
 
                     #[inline]
                     fn shr(self, rhs: Self) -> Self {
-                        use #amplify_crate::Wrapper;
-                        Wrapper::from_inner(::core::ops::Shr::shr(Wrapper::into_inner(self), rhs.into_inner()))
+                        use #amplify_crate::#wrapper;
+                        #wrapper::#from_inner(::core::ops::Shr::shr(#wrapper::#into_inner(self), #wrapper::#into_inner(rhs)))
                     }
                 }
             },
@@ -442,8 +710,8 @@ impl WrapperDerives {
 
                     #[inline]
                     fn bitand(self, rhs: Self) -> Self {
-                        use #amplify_crate::Wrapper;
-                        Wrapper::from_inner(::core::ops::BitAnd::bitand(Wrapper::into_inner(self), rhs.into_inner()))
+                        use #amplify_crate::#wrapper;
+                        #wrapper::#from_inner(::core::ops::BitAnd::bitand(#wrapper::#into_inner(self), #wrapper::#into_inner(rhs)))
                     }
                 }
             },
@@ -454,8 +722,8 @@ impl WrapperDerives {
 
                     #[inline]
                     fn bitor(self, rhs: Self) -> Self {
-                        use #amplify_crate::Wrapper;
-                        Wrapper::from_inner(::core::ops::BitOr::bitor(Wrapper::into_inner(self), rhs.into_inner()))
+                        use #amplify_crate::#wrapper;
+                        #wrapper::#from_inner(::core::ops::BitOr::bitor(#wrapper::#into_inner(self), #wrapper::#into_inner(rhs)))
                     }
                 }
             },
@@ -466,8 +734,8 @@ impl WrapperDerives {
 
                     #[inline]
                     fn bitxor(self, rhs: Self) -> Self {
-                        use #amplify_crate::Wrapper;
-                        Wrapper::from_inner(::core::ops::BitXor::bitxor(Wrapper::into_inner(self), rhs.into_inner()))
+                        use #amplify_crate::#wrapper;
+                        #wrapper::#from_inner(::core::ops::BitXor::bitxor(#wrapper::#into_inner(self), #wrapper::#into_inner(rhs)))
                     }
                 }
             },
@@ -476,8 +744,8 @@ impl WrapperDerives {
                 {
                     #[inline]
                     fn add_assign(&mut self, rhs: Self) {
-                        use #amplify_crate::Wrapper;
-                        ::core::ops::AddAssign::add_assign(Wrapper::as_inner_mut(self), rhs.into_inner())
+                        use #amplify_crate::{#wrapper, #wrapper_mut};
+                        ::core::ops::AddAssign::add_assign(#wrapper_mut::#as_inner_mut(self), #wrapper::#into_inner(rhs))
                     }
                 }
             },
@@ -486,8 +754,8 @@ impl WrapperDerives {
                 {
                     #[inline]
                     fn sub_assign(&mut self, rhs: Self) {
-                        use #amplify_crate::Wrapper;
-                        ::core::ops::SubAssign::sub_assign(Wrapper::as_inner_mut(self), rhs.into_inner())
+                        use #amplify_crate::{#wrapper, #wrapper_mut};
+                        ::core::ops::SubAssign::sub_assign(#wrapper_mut::#as_inner_mut(self), #wrapper::#into_inner(rhs))
                     }
                 }
             },
@@ -496,8 +764,8 @@ impl WrapperDerives {
                 {
                     #[inline]
                     fn mul_assign(&mut self, rhs: Self) {
-                        use #amplify_crate::Wrapper;
-                        ::core::ops::MulAssign::mul_assign(Wrapper::as_inner_mut(self), rhs.into_inner())
+                        use #amplify_crate::{#wrapper, #wrapper_mut};
+                        ::core::ops::MulAssign::mul_assign(#wrapper_mut::#as_inner_mut(self), #wrapper::#into_inner(rhs))
                     }
                 }
             },
@@ -506,18 +774,18 @@ impl WrapperDerives {
                 {
                     #[inline]
                     fn div_assign(&mut self, rhs: Self) {
-                        use #amplify_crate::Wrapper;
-                        ::core::ops::DivAssign::div_assign(Wrapper::as_inner_mut(self), rhs.into_inner())
+                        use #amplify_crate::{#wrapper, #wrapper_mut};
+                        ::core::ops::DivAssign::div_assign(#wrapper_mut::#as_inner_mut(self), #wrapper::#into_inner(rhs))
                     }
                 }
             },
-            WrapperDerives::RemAssign => quote! {4
+            WrapperDerives::RemAssign => quote! {
                 impl #impl_generics ::core::ops::RemAssign for #ident_name #ty_generics #where_clause
                 {
                     #[inline]
                     fn rem_assign(&mut self, rhs: Self) {
-                        use #amplify_crate::Wrapper;
-                        ::core::ops::RemAssign::rem_assign(Wrapper::as_inner_mut(self), rhs.into_inner())
+                        use #amplify_crate::{#wrapper, #wrapper_mut};
+                        ::core::ops::RemAssign::rem_assign(#wrapper_mut::#as_inner_mut(self), #wrapper::#into_inner(rhs))
                     }
                 }
             },
@@ -526,8 +794,8 @@ impl WrapperDerives {
                 {
                     #[inline]
                     fn shl_assign(&mut self, rhs: Self) {
-                        use #amplify_crate::Wrapper;
-                        ::core::ops::ShlAssign::shl_assign(Wrapper::as_inner_mut(self), rhs.into_inner())
+                        use #amplify_crate::{#wrapper, #wrapper_mut};
+                        ::core::ops::ShlAssign::shl_assign(#wrapper_mut::#as_inner_mut(self), #wrapper::#into_inner(rhs))
                     }
                 }
             },
@@ -536,8 +804,8 @@ impl WrapperDerives {
                 {
                     #[inline]
                     fn shr_assign(&mut self, rhs: Self) {
-                        use #amplify_crate::Wrapper;
-                        ::core::ops::ShrAssign::shr_assign(Wrapper::as_inner_mut(self), rhs.into_inner())
+                        use #amplify_crate::{#wrapper, #wrapper_mut};
+                        ::core::ops::ShrAssign::shr_assign(#wrapper_mut::#as_inner_mut(self), #wrapper::#into_inner(rhs))
                     }
                 }
             },
@@ -546,8 +814,8 @@ impl WrapperDerives {
                 {
                     #[inline]
                     fn bitand_assign(&mut self, rhs: Self) {
-                        use #amplify_crate::Wrapper;
-                        ::core::ops::BitAndAssign::bitand_assign(Wrapper::as_inner_mut(self), rhs.into_inner())
+                        use #amplify_crate::{#wrapper, #wrapper_mut};
+                        ::core::ops::BitAndAssign::bitand_assign(#wrapper_mut::#as_inner_mut(self), #wrapper::#into_inner(rhs))
                     }
                 }
             },
@@ -556,8 +824,8 @@ impl WrapperDerives {
                 {
                     #[inline]
                     fn bitor_assign(&mut self, rhs: Self) {
-                        use #amplify_crate::Wrapper;
-                        ::core::ops::BitOrAssign::bitor_assign(Wrapper::as_inner_mut(self), rhs.into_inner())
+                        use #amplify_crate::{#wrapper, #wrapper_mut};
+                        ::core::ops::BitOrAssign::bitor_assign(#wrapper_mut::#as_inner_mut(self), #wrapper::#into_inner(rhs))
                     }
                 }
             },
@@ -566,8 +834,141 @@ impl WrapperDerives {
                 {
                     #[inline]
                     fn bitxor_assign(&mut self, rhs: Self) {
-                        use #amplify_crate::Wrapper;
-                        ::core::ops::BitXorAssign::bitxor_assign(Wrapper::as_inner_mut(self), rhs.into_inner())
+                        use #amplify_crate::{#wrapper, #wrapper_mut};
+                        ::core::ops::BitXorAssign::bitxor_assign(#wrapper_mut::#as_inner_mut(self), #wrapper::#into_inner(rhs))
+                    }
+                }
+            },
+            WrapperDerives::Binary => quote! {
+                impl #impl_generics ::core::fmt::Binary for #ident_name #ty_generics #where_clause
+                {
+                    #[inline]
+                    fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                        use #amplify_crate::#wrapper;
+                        ::core::fmt::Binary::fmt(#wrapper::#as_inner(self), f)
+                    }
+                }
+            },
+            WrapperDerives::IntoIterator => quote! {
+                impl #impl_generics ::core::iter::IntoIterator for #ident_name #ty_generics #where_clause
+                {
+                    type Item = <<Self as #amplify_crate::#wrapper>::#inner as ::core::iter::IntoIterator>::Item;
+                    type IntoIter = <<Self as #amplify_crate::#wrapper>::#inner as ::core::iter::IntoIterator>::IntoIter;
+
+                    #[inline]
+                    fn into_iter(self) -> Self::IntoIter {
+                        use #amplify_crate::#wrapper;
+                        ::core::iter::IntoIterator::into_iter(#wrapper::#into_inner(self))
+                    }
+                }
+            },
+            WrapperDerives::IntoIteratorRef => {
+                let mut generics = generics.clone();
+                generics.params.insert(0, parse_quote! { '__a });
+                generics.make_where_clause().predicates.push(parse_quote! {
+                    &'__a <#ident_name #ty_generics as #amplify_crate::#wrapper>::#inner: ::core::iter::IntoIterator
+                });
+                let (impl_generics, _, where_clause) = generics.split_for_impl();
+                quote! {
+                    impl #impl_generics ::core::iter::IntoIterator for &'__a #ident_name #ty_generics #where_clause
+                    {
+                        type Item = <&'__a <#ident_name #ty_generics as #amplify_crate::#wrapper>::#inner as ::core::iter::IntoIterator>::Item;
+                        type IntoIter = <&'__a <#ident_name #ty_generics as #amplify_crate::#wrapper>::#inner as ::core::iter::IntoIterator>::IntoIter;
+
+                        #[inline]
+                        fn into_iter(self) -> Self::IntoIter {
+                            use #amplify_crate::#wrapper;
+                            ::core::iter::IntoIterator::into_iter(#wrapper::#as_inner(self))
+                        }
+                    }
+                }
+            }
+            WrapperDerives::FromIterator => {
+                let mut generics = generics.clone();
+                generics.params.push(parse_quote! { __Item });
+                generics.make_where_clause().predicates.push(parse_quote! {
+                    <#ident_name #ty_generics as #amplify_crate::#wrapper>::#inner: ::core::iter::FromIterator<__Item>
+                });
+                let (impl_generics, _, where_clause) = generics.split_for_impl();
+                quote! {
+                    impl #impl_generics ::core::iter::FromIterator<__Item> for #ident_name #ty_generics #where_clause
+                    {
+                        #[inline]
+                        fn from_iter<I: ::core::iter::IntoIterator<Item = __Item>>(iter: I) -> Self {
+                            use #amplify_crate::#wrapper;
+                            #wrapper::#from_inner(::core::iter::FromIterator::from_iter(iter))
+                        }
+                    }
+                }
+            }
+            WrapperDerives::Extend => {
+                let mut generics = generics.clone();
+                generics.params.push(parse_quote! { __Item });
+                generics.make_where_clause().predicates.push(parse_quote! {
+                    <#ident_name #ty_generics as #amplify_crate::#wrapper>::#inner: ::core::iter::Extend<__Item>
+                });
+                let (impl_generics, _, where_clause) = generics.split_for_impl();
+                quote! {
+                    impl #impl_generics ::core::iter::Extend<__Item> for #ident_name #ty_generics #where_clause
+                    {
+                        #[inline]
+                        fn extend<I: ::core::iter::IntoIterator<Item = __Item>>(&mut self, iter: I) {
+                            use #amplify_crate::#wrapper_mut;
+                            ::core::iter::Extend::extend(#wrapper_mut::#as_inner_mut(self), iter)
+                        }
+                    }
+                }
+            }
+            WrapperDerives::Sum => quote! {
+                impl #impl_generics ::core::iter::Sum for #ident_name #ty_generics #where_clause
+                {
+                    #[inline]
+                    fn sum<I: ::core::iter::Iterator<Item = Self>>(iter: I) -> Self {
+                        use #amplify_crate::#wrapper;
+                        #wrapper::#from_inner(iter.map(#wrapper::#into_inner).sum())
+                    }
+                }
+            },
+            WrapperDerives::Product => quote! {
+                impl #impl_generics ::core::iter::Product for #ident_name #ty_generics #where_clause
+                {
+                    #[inline]
+                    fn product<I: ::core::iter::Iterator<Item = Self>>(iter: I) -> Self {
+                        use #amplify_crate::#wrapper;
+                        #wrapper::#from_inner(iter.map(#wrapper::#into_inner).product())
+                    }
+                }
+            },
+            WrapperDerives::PartialEqInner => quote! {
+                impl #impl_generics ::core::cmp::PartialEq<<#ident_name #ty_generics as #amplify_crate::#wrapper>::#inner> for #ident_name #ty_generics #where_clause
+                {
+                    #[inline]
+                    fn eq(&self, other: &<Self as #amplify_crate::#wrapper>::#inner) -> bool {
+                        use #amplify_crate::#wrapper;
+                        ::core::cmp::PartialEq::eq(#wrapper::#as_inner(self), other)
+                    }
+                }
+            },
+            WrapperDerives::PartialOrdInner => quote! {
+                impl #impl_generics ::core::cmp::PartialOrd<<#ident_name #ty_generics as #amplify_crate::#wrapper>::#inner> for #ident_name #ty_generics #where_clause
+                {
+                    #[inline]
+                    fn partial_cmp(
+                        &self,
+                        other: &<Self as #amplify_crate::#wrapper>::#inner,
+                    ) -> ::core::option::Option<::core::cmp::Ordering> {
+                        use #amplify_crate::#wrapper;
+                        ::core::cmp::PartialOrd::partial_cmp(#wrapper::#as_inner(self), other)
+                    }
+                }
+            },
+            WrapperDerives::Hash => quote! {
+                impl #impl_generics ::core::hash::Hash for #ident_name #ty_generics #where_clause
+                {
+                    #[inline]
+                    fn hash<H: ::core::hash::Hasher>(&self, state: &mut H) {
+                        use #amplify_crate::#wrapper;
+                        ::core::hash::Hash::hash(#wrapper::#as_inner(self), state)
                     }
                 }
             },
@@ -575,55 +976,160 @@ impl WrapperDerives {
     }
 }
 
-pub(crate) fn inner(input: DeriveInput) -> Result<TokenStream2> {
-    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
-    let ident_name = &input.ident;
-    let amplify_crate = get_amplify_crate(&input);
+/// Arguments of `#[wrapper(...)]` and `#[wrapper_mut(...)]` attributes
+#[derive(Default)]
+struct WrapperAttrs {
+    /// Implementations to derive
+    derives: Vec<WrapperDerives>,
+    /// Bounds given with `bound = "..."` argument
+    bounds: Option<Vec<WherePredicate>>,
+    /// Function checking the inner data, given with `validate = path`
+    validate: Option<Path>,
+    /// Error type of the validator, given with `error = Type`
+    error: Option<Type>,
+    /// Whether the implementations must be delegated to the innermost
+    /// wrapped type, given with `deep`
+    deep: bool,
+    /// Whether the wrapped type is the innermost one for the deep wrappers,
+    /// given with `deep_root`
+    deep_root: bool,
+}
+
+/// Single argument of the wrapper attribute: either a meta item or
+/// `validate` and `error` arguments, which values are not literals
+enum WrapperArg {
+    Meta(NestedMeta),
+    Validate(Path),
+    Error(Type),
+}
+
+impl Parse for WrapperArg {
+    fn parse(input: ParseStream) -> Result<Self> {
+        if input.peek(Ident) && input.peek2(Token![=]) {
+            let fork = input.fork();
+            let name: Ident = fork.parse()?;
+            if name == "validate" || name == "error" {
+                input.parse::<Ident>()?;
+                input.parse::<Token![=]>()?;
+                return if name == "validate" {
+                    input.parse().map(WrapperArg::Validate)
+                } else {
+                    input.parse().map(WrapperArg::Error)
+                };
+            }
+        }
+        input.parse().map(WrapperArg::Meta)
+    }
+}
 
+/// Parses `#[wrapper(...)]` or `#[wrapper_mut(...)]` attributes, returning
+/// the requested implementations and other arguments
+fn parse_attrs(input: &DeriveInput, name: &str, example: &str) -> Result<WrapperAttrs> {
+    let mut attrs = WrapperAttrs::default();
+    const WRAPPER_DERIVE_ERR: &str = "Wrapper attributes must be in a form of type list";
+    for attr in input.attrs.iter().filter(|attr| attr.path.is_ident(name)) {
+        let args = attr
+            .parse_args_with(Punctuated::<WrapperArg, Token![,]>::parse_terminated)
+            .map_err(|_| attr_err!(attr, name, WRAPPER_DERIVE_ERR, example))?;
+        for arg in args {
+            let meta = match arg {
+                WrapperArg::Validate(path) if name == NAME => {
+                    if attrs.validate.is_some() {
+                        return Err(attr_err!(
+                            path,
+                            name,
+                            "repeated `validate` argument",
+                            example
+                        ));
+                    }
+                    attrs.validate = Some(path);
+                    continue;
+                }
+                WrapperArg::Error(ty) if name == NAME => {
+                    if attrs.error.is_some() {
+                        return Err(attr_err!(ty, name, "repeated `error` argument", example));
+                    }
+                    attrs.error = Some(ty);
+                    continue;
+                }
+                WrapperArg::Validate(_) | WrapperArg::Error(_) => {
+                    return Err(attr_err!(
+                        attr,
+                        name,
+                        "validator must be given in `#[wrapper(validate = ...)]` attribute",
+                        example
+                    ))
+                }
+                WrapperArg::Meta(meta) => meta,
+            };
+            if let Some(bound) = parse_bound(&meta) {
+                if attrs.bounds.is_some() {
+                    return Err(attr_err!(meta, name, "repeated `bound` argument", example));
+                }
+                attrs.bounds = Some(bound?);
+                continue;
+            }
+            match meta {
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("deep") && name == NAME => {
+                    attrs.deep = true;
+                }
+                NestedMeta::Meta(Meta::Path(path))
+                    if path.is_ident("deep_root") && name == NAME =>
+                {
+                    attrs.deep_root = true;
+                }
+                NestedMeta::Meta(Meta::Path(path))
+                    if ["DerefMut", "AsMut", "BorrowMut"]
+                        .iter()
+                        .any(|ident| path.is_ident(ident)) =>
+                {
+                    let msg = format!(
+                        "`{}` is always implemented by `#[derive(WrapperMut)]` and must not \
+                         be listed in the attribute",
+                        path.to_token_stream()
+                    );
+                    return Err(attr_err!(path, name, msg, example));
+                }
+                NestedMeta::Meta(Meta::Path(path)) => {
+                    attrs
+                        .derives
+                        .push(WrapperDerives::from_path(&path)?.ok_or_else(|| {
+                            attr_err!(path, name, "Unrecognized wrapper parameter", example)
+                        })?);
+                }
+                _ => return Err(attr_err!(meta, name, WRAPPER_DERIVE_ERR, example)),
+            }
+        }
+    }
+    if attrs.deep && attrs.deep_root {
+        return Err(attr_err!(
+            name,
+            "`deep` and `deep_root` arguments are mutually exclusive: `deep_root` marks the \
+             lowest level of deep wrapping",
+            example
+        ));
+    }
+    Ok(attrs)
+}
+
+/// Detects the wrapped field, returning its member and type
+fn wrapped_field(input: &DeriveInput) -> Result<(TokenStream2, Type)> {
     let data = match input.data {
         Data::Struct(ref data) => data,
         Data::Enum(_) => {
             return Err(Error::new_spanned(
-                &input,
+                input,
                 "Deriving wrapper is not supported in enums",
             ))
         }
-        //strict_encode_inner_enum(&input, &data),
         Data::Union(_) => {
             return Err(Error::new_spanned(
-                &input,
+                input,
                 "Deriving wrapper is not supported in unions",
             ))
         }
     };
 
-    let mut wrappers = vec![];
-    const WRAPPER_DERIVE_ERR: &str = "Wrapper attributes must be in a form of type list";
-    for attr in input
-        .attrs
-        .iter()
-        .filter(|attr| attr.path.is_ident("wrapper"))
-    {
-        match attr
-            .parse_meta()
-            .map_err(|_| attr_err!(attr, WRAPPER_DERIVE_ERR))?
-        {
-            Meta::List(MetaList { nested, .. }) => {
-                for meta in nested {
-                    match meta {
-                        NestedMeta::Meta(Meta::Path(path)) => {
-                            wrappers.push(WrapperDerives::from_path(&path)?.ok_or_else(|| {
-                                attr_err!(path, "Unrecognized wrapper parameter")
-                            })?);
-                        }
-                        _ => return Err(attr_err!(meta, WRAPPER_DERIVE_ERR)),
-                    }
-                }
-            }
-            _ => return Err(attr_err!(attr, WRAPPER_DERIVE_ERR)),
-        }
-    }
-
     let field;
     let mut from;
     match data.fields {
@@ -684,13 +1190,251 @@ pub(crate) fn inner(input: DeriveInput) -> Result<TokenStream2> {
         }
         Fields::Unit => {
             return Err(Error::new_spanned(
-                &input,
+                input,
                 "Deriving wrapper is meaningless for unit structs",
             ))
         }
     };
+    Ok((field, from))
+}
+
+/// Generates expression constructing the wrapper directly from the `inner`
+/// value, initializing other fields with their defaults
+fn construct(input: &DeriveInput, field: &TokenStream2) -> TokenStream2 {
+    let fields = match input.data {
+        Data::Struct(ref data) => &data.fields,
+        _ => unreachable!("wrapped field is detected only in structs"),
+    };
+    let wrapped: Member = syn::parse2(field.clone()).expect("wrapped field is a valid member");
+    let values = fields.iter().enumerate().map(|(index, f)| {
+        let member = f
+            .ident
+            .clone()
+            .map_or_else(|| Member::from(index), Member::Named);
+        let value = if member == wrapped {
+            quote! { inner }
+        } else {
+            quote! { ::core::default::Default::default() }
+        };
+        quote! { #member: #value }
+    });
+    quote! { Self { #( #values ),* } }
+}
+
+pub(crate) fn inner(input: DeriveInput) -> Result<TokenStream2> {
+    let ident_name = &input.ident;
+    let amplify_crate = get_amplify_crate(&input);
+
+    let (field, from) = wrapped_field(&input)?;
+    let WrapperAttrs {
+        derives: mut wrappers,
+        bounds: custom_bounds,
+        validate,
+        error,
+        deep,
+        deep_root,
+    } = parse_attrs(&input, NAME, EXAMPLE)?;
+
+    // `Wrapper` already implements conversion into the inner type
+    if let Some(attr) = input.attrs.iter().find(|attr| from::is_into_attr(attr)) {
+        return Err(attr_err!(
+            attr,
+            "from",
+            "`#[derive(Wrapper)]` already implements conversion into the inner type, so \
+             `#[from(into)]` must not be used with it",
+            "#[from]"
+        ));
+    }
+
+    if let Some(w) = wrappers.iter().find(|w| w.is_mut()) {
+        let msg = format!(
+            "`{:?}` requires mutable access to the wrapped data; use \
+             `#[derive(WrapperMut)]` with `#[wrapper_mut({:?})]` attribute",
+            w, w
+        );
+        return Err(attr_err!(NAME, msg, EXAMPLE));
+    }
+
+    let validator = match (validate, error) {
+        (Some(validate), Some(error)) => Some((validate, error)),
+        (None, None) => None,
+        (Some(_), None) => {
+            return Err(attr_err!(
+                NAME,
+                "`validate` requires `error = Type` argument with the error type returned by \
+                 the validator",
+                EXAMPLE_VALIDATE
+            ))
+        }
+        (None, Some(_)) => {
+            return Err(attr_err!(
+                NAME,
+                "`error` must be used together with `validate` argument",
+                EXAMPLE_VALIDATE
+            ))
+        }
+    };
+    if validator.is_some() && (deep || deep_root) {
+        return Err(attr_err!(
+            NAME,
+            "`deep` and `deep_root` wrappers can't have a validator, since they are constructed \
+             from the innermost data without validation",
+            EXAMPLE_VALIDATE
+        ));
+    }
+    if validator.is_some() {
+        if let Some(w) = wrappers.iter().find(|w| w.constructs()) {
+            let msg = format!(
+                "`{:?}` constructs the wrapper without running the validator and can't be used \
+                 with `validate`",
+                w
+            );
+            return Err(attr_err!(NAME, msg, EXAMPLE_VALIDATE));
+        }
+    }
+
+    // `Hex` already includes `LowerHex` implementation
+    if wrappers.contains(&WrapperDerives::Hex) {
+        wrappers.retain(|w| *w != WrapperDerives::LowerHex);
+    }
+
+    // All implementations use `Wrapper` trait, which requires the inner type
+    // to be `Clone`
+    let mut bounds = Bounds::with(&input.generics, custom_bounds);
+    bounds.add(&from, quote! { ::core::clone::Clone });
+    let generics = bounds.apply(&input.generics);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    // Deep wrappers require the inner type to be a deep wrapper itself, and
+    // delegate the implementations to its innermost type
+    let mut deep_bounds = bounds.clone();
+    let (deep_ty, deep_impl): (Type, _) = if deep {
+        deep_bounds.add(&from, quote! { #amplify_crate::DeepWrapper });
+        (
+            parse_quote! { <#from as #amplify_crate::DeepWrapper>::Deep },
+            Some(quote! {
+                #[inline]
+                fn from_deep(deep: Self::Deep) -> Self {
+                    use #amplify_crate::{DeepWrapper, Wrapper};
+                    Wrapper::from_inner(DeepWrapper::from_deep(deep))
+                }
+
+                #[inline]
+                fn as_deep(&self) -> &Self::Deep {
+                    use #amplify_crate::{DeepWrapper, Wrapper};
+                    DeepWrapper::as_deep(Wrapper::as_inner(self))
+                }
+
+                #[inline]
+                fn into_deep(self) -> Self::Deep {
+                    use #amplify_crate::{DeepWrapper, Wrapper};
+                    DeepWrapper::into_deep(Wrapper::into_inner(self))
+                }
+            }),
+        )
+    } else if deep_root {
+        (
+            from.clone(),
+            Some(quote! {
+                #[inline]
+                fn from_deep(deep: Self::Deep) -> Self {
+                    use #amplify_crate::Wrapper;
+                    Wrapper::from_inner(deep)
+                }
+
+                #[inline]
+                fn as_deep(&self) -> &Self::Deep {
+                    use #amplify_crate::Wrapper;
+                    Wrapper::as_inner(self)
+                }
+
+                #[inline]
+                fn into_deep(self) -> Self::Deep {
+                    use #amplify_crate::Wrapper;
+                    Wrapper::into_inner(self)
+                }
+            }),
+        )
+    } else {
+        (from.clone(), None)
+    };
+    let deep_generics = deep_bounds.apply(&input.generics);
+    let deep_where_clause = &deep_generics.where_clause;
+    let deep_wrapper = deep_impl.map(|deep_impl| {
+        quote! {
+            impl #impl_generics #amplify_crate::DeepWrapper for #ident_name #ty_generics #deep_where_clause {
+                type Deep = #deep_ty;
+
+                #deep_impl
+            }
+        }
+    });
+
+    let wrapper_derive = wrappers
+        .iter()
+        .map(|w| {
+            let mut bounds = deep_bounds.clone();
+            if let Some(bound) = w.bound(&deep_ty, &amplify_crate) {
+                bounds.add(&deep_ty, bound);
+            }
+            if let Some((_, error)) = &validator {
+                match w {
+                    WrapperDerives::FromStr => bounds.add(
+                        error,
+                        quote! { ::core::convert::From<<#from as ::core::str::FromStr>::Err> },
+                    ),
+                    WrapperDerives::Deserialize => {
+                        bounds.add(error, quote! { ::core::fmt::Display })
+                    }
+                    _ => {}
+                }
+            }
+            w.into_token_stream2(
+                &input,
+                &bounds.apply(&input.generics),
+                validator.as_ref(),
+                deep,
+            )
+        })
+        .collect::<Vec<_>>();
+
+    // Validated wrappers can't be constructed with `From`, since it would
+    // conflict with `TryFrom`; the latter can't be implemented for a bare type
+    // parameter, since it conflicts with the blanket implementation
+    let (from_inner, try_from) = match validator {
+        Some(_) if bounds.is_param(&from) => (construct(&input, &field), quote! {}),
+        Some((validate, error)) => (
+            construct(&input, &field),
+            quote! {
+                impl #impl_generics ::core::convert::TryFrom<#from> for #ident_name #ty_generics #where_clause {
+                    type Error = #error;
+
+                    #[inline]
+                    fn try_from(inner: #from) -> ::core::result::Result<Self, Self::Error> {
+                        use #amplify_crate::Wrapper;
+                        #validate(&inner)?;
+                        Ok(Wrapper::from_inner(inner))
+                    }
+                }
+            },
+        ),
+        None => (quote! { Self::from(inner) }, quote! {}),
+    };
 
-    let wrapper_derive = wrappers.iter().map(|w| w.into_token_stream2(&input));
+    // Conversion into a bare type parameter is prohibited by the orphan rules
+    let from_wrapper = if bounds.is_param(&from) {
+        quote! {}
+    } else {
+        quote! {
+            impl #impl_generics ::core::convert::From<#ident_name #ty_generics> for #from #where_clause {
+                #[inline]
+                fn from(wrapped: #ident_name #ty_generics) -> Self {
+                    use #amplify_crate::Wrapper;
+                    Wrapper::into_inner(wrapped)
+                }
+            }
+        }
+    };
 
     Ok(quote! {
         impl #impl_generics #amplify_crate::Wrapper for #ident_name #ty_generics #where_clause {
@@ -698,7 +1442,7 @@ pub(crate) fn inner(input: DeriveInput) -> Result<TokenStream2> {
 
             #[inline]
             fn from_inner(inner: Self::Inner) -> Self {
-                Self::from(inner)
+                #from_inner
             }
 
             #[inline]
@@ -706,74 +1450,161 @@ pub(crate) fn inner(input: DeriveInput) -> Result<TokenStream2> {
                 &self.#field
             }
 
-            #[inline]
-            fn as_inner_mut(&mut self) -> &mut Self::Inner {
-                &mut self.#field
-            }
-
             #[inline]
             fn into_inner(self) -> Self::Inner {
                 self.#field
             }
         }
 
-        impl #impl_generics ::core::convert::From<#ident_name #ty_generics> for #from #where_clause {
+        #deep_wrapper
+
+        #from_wrapper
+
+        #try_from
+
+        impl #impl_generics ::core::convert::AsRef<<#ident_name #ty_generics as #amplify_crate::Wrapper>::Inner> for #ident_name #ty_generics #where_clause {
             #[inline]
-            fn from(wrapped: #ident_name #ty_generics) -> Self {
+            fn as_ref(&self) -> &<Self as #amplify_crate::Wrapper>::Inner {
                 use #amplify_crate::Wrapper;
-                Wrapper::into_inner(wrapped)
+                Wrapper::as_inner(self)
             }
         }
 
-        impl #impl_generics ::core::convert::AsRef<<#ident_name #impl_generics as #amplify_crate::Wrapper>::Inner> for #ident_name #ty_generics #where_clause {
+        impl #impl_generics ::core::borrow::Borrow<<#ident_name #ty_generics as #amplify_crate::Wrapper>::Inner> for #ident_name #ty_generics #where_clause {
             #[inline]
-            fn as_ref(&self) -> &<Self as #amplify_crate::Wrapper>::Inner {
+            fn borrow(&self) -> &<Self as #amplify_crate::Wrapper>::Inner {
                 use #amplify_crate::Wrapper;
                 Wrapper::as_inner(self)
             }
         }
 
-        impl #impl_generics ::core::convert::AsMut<<#ident_name #impl_generics as #amplify_crate::Wrapper>::Inner> for #ident_name #ty_generics #where_clause {
+        impl #impl_generics ::core::ops::Deref for #ident_name #ty_generics #where_clause {
+            type Target = <Self as #amplify_crate::Wrapper>::Inner;
             #[inline]
-            fn as_mut(&mut self) -> &mut <Self as #amplify_crate::Wrapper>::Inner {
+            fn deref(&self) -> &Self::Target {
                 use #amplify_crate::Wrapper;
-                Wrapper::as_inner_mut(self)
+                Wrapper::as_inner(self)
+            }
+        }
+
+        #( #wrapper_derive )*
+    })
+}
+
+pub(crate) fn inner_mut(input: DeriveInput) -> Result<TokenStream2> {
+    let ident_name = &input.ident;
+    let amplify_crate = get_amplify_crate(&input);
+
+    let (field, from) = wrapped_field(&input)?;
+    let WrapperAttrs {
+        derives: wrappers,
+        bounds: custom_bounds,
+        ..
+    } = parse_attrs(&input, NAME_MUT, EXAMPLE_MUT)?;
+    if let Some(w) = wrappers.iter().find(|w| !w.is_mut()) {
+        let msg = format!(
+            "`{:?}` does not require mutable access to the wrapped data and must be \
+             put into `#[wrapper({:?})]` attribute",
+            w, w
+        );
+        return Err(attr_err!(NAME_MUT, msg, EXAMPLE_MUT));
+    }
+    // `WrapperMut` implementation must satisfy bounds of `Wrapper`, so if
+    // they were customized we use them unless other bounds are given
+    let WrapperAttrs {
+        bounds: wrapper_bounds,
+        deep,
+        deep_root,
+        ..
+    } = parse_attrs(&input, NAME, EXAMPLE)?;
+    let custom_bounds = custom_bounds.or(wrapper_bounds);
+
+    let mut bounds = Bounds::with(&input.generics, custom_bounds);
+    bounds.add(&from, quote! { ::core::clone::Clone });
+    let generics = bounds.apply(&input.generics);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    // Deep wrappers require mutable access to the innermost data of the
+    // wrapped type, and delegate the operations to it
+    let mut deep_bounds = bounds.clone();
+    let (deep_ty, as_deep_mut): (Type, _) = if deep {
+        deep_bounds.add(&from, quote! { #amplify_crate::DeepWrapperMut });
+        (
+            parse_quote! { <#from as #amplify_crate::DeepWrapper>::Deep },
+            Some(quote! {
+                use #amplify_crate::{DeepWrapperMut, WrapperMut};
+                DeepWrapperMut::as_deep_mut(WrapperMut::as_inner_mut(self))
+            }),
+        )
+    } else if deep_root {
+        (
+            from.clone(),
+            Some(quote! {
+                use #amplify_crate::WrapperMut;
+                WrapperMut::as_inner_mut(self)
+            }),
+        )
+    } else {
+        (from.clone(), None)
+    };
+    let deep_generics = deep_bounds.apply(&input.generics);
+    let deep_where_clause = &deep_generics.where_clause;
+    let deep_wrapper_mut = as_deep_mut.map(|as_deep_mut| {
+        quote! {
+            impl #impl_generics #amplify_crate::DeepWrapperMut for #ident_name #ty_generics #deep_where_clause {
+                #[inline]
+                fn as_deep_mut(&mut self) -> &mut <Self as #amplify_crate::DeepWrapper>::Deep {
+                    #as_deep_mut
+                }
             }
         }
+    });
 
-        impl #impl_generics ::core::borrow::Borrow<<#ident_name #impl_generics as #amplify_crate::Wrapper>::Inner> for #ident_name #ty_generics #where_clause {
+    let wrapper_derive = wrappers
+        .iter()
+        .map(|w| {
+            let mut bounds = deep_bounds.clone();
+            if let Some(bound) = w.bound(&deep_ty, &amplify_crate) {
+                bounds.add(&deep_ty, bound);
+            }
+            w.into_token_stream2(&input, &bounds.apply(&input.generics), None, deep)
+        })
+        .collect::<Vec<_>>();
+
+    Ok(quote! {
+        impl #impl_generics #amplify_crate::WrapperMut for #ident_name #ty_generics #where_clause {
             #[inline]
-            fn borrow(&self) -> &<Self as #amplify_crate::Wrapper>::Inner {
-                use #amplify_crate::Wrapper;
-                Wrapper::as_inner(self)
+            fn as_inner_mut(&mut self) -> &mut <Self as #amplify_crate::Wrapper>::Inner {
+                &mut self.#field
             }
         }
 
-        impl #impl_generics ::core::borrow::BorrowMut<<#ident_name #impl_generics as #amplify_crate::Wrapper>::Inner> for #ident_name #ty_generics #where_clause {
+        impl #impl_generics ::core::convert::AsMut<<#ident_name #ty_generics as #amplify_crate::Wrapper>::Inner> for #ident_name #ty_generics #where_clause {
             #[inline]
-            fn borrow_mut(&mut self) -> &mut <Self as #amplify_crate::Wrapper>::Inner {
-                use #amplify_crate::Wrapper;
-                Wrapper::as_inner_mut(self)
+            fn as_mut(&mut self) -> &mut <Self as #amplify_crate::Wrapper>::Inner {
+                use #amplify_crate::WrapperMut;
+                WrapperMut::as_inner_mut(self)
             }
         }
 
-        impl #impl_generics ::core::ops::Deref for #ident_name #ty_generics #where_clause {
-            type Target = <Self as #amplify_crate::Wrapper>::Inner;
+        impl #impl_generics ::core::borrow::BorrowMut<<#ident_name #ty_generics as #amplify_crate::Wrapper>::Inner> for #ident_name #ty_generics #where_clause {
             #[inline]
-            fn deref(&self) -> &Self::Target {
-                use #amplify_crate::Wrapper;
-                Wrapper::as_inner(self)
+            fn borrow_mut(&mut self) -> &mut <Self as #amplify_crate::Wrapper>::Inner {
+                use #amplify_crate::WrapperMut;
+                WrapperMut::as_inner_mut(self)
             }
         }
 
         impl #impl_generics ::core::ops::DerefMut for #ident_name #ty_generics #where_clause {
             #[inline]
             fn deref_mut(&mut self) -> &mut Self::Target {
-                use #amplify_crate::Wrapper;
-                Wrapper::as_inner_mut(self)
+                use #amplify_crate::WrapperMut;
+                WrapperMut::as_inner_mut(self)
             }
         }
 
+        #deep_wrapper_mut
+
         #( #wrapper_derive )*
     })
 }
@@ -1346,6 +1346,26 @@ macro_rules! construct_bigint {
                         $name::from_be_slice(bytes)
                             .map_err(|_| de::Error::invalid_length(bytes.len(), &self))
                     }
+
+                    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+                    where
+                        A: de::SeqAccess<'de>,
+                    {
+                        let mut bytes = [0u8; $n_words * 8];
+                        for (index, byte) in bytes.iter_mut().enumerate() {
+                            *byte = seq
+                                .next_element()?
+                                .ok_or_else(|| de::Error::invalid_length(index, &self))?;
+                        }
+                        let mut len = bytes.len();
+                        while seq.next_element::<de::IgnoredAny>()?.is_some() {
+                            len += 1;
+                        }
+                        if len != bytes.len() {
+                            return Err(de::Error::invalid_length(len, &self));
+                        }
+                        Ok($name::from_be_bytes(bytes))
+                    }
                 }
 
                 if deserializer.is_human_readable() {
@@ -2104,6 +2124,17 @@ mod tests {
             let bin_encoded = ::bincode::serialize(&uint).unwrap();
             let bin_decoded: u256 = ::bincode::deserialize(&bin_encoded).unwrap();
             assert_eq!(bin_decoded, uint);
+
+            // Byte sequences, used by some self-describing formats, are accepted
+            use serde::de::value::{Error, SeqAccessDeserializer, SeqDeserializer};
+            use serde::Deserialize;
+            let bytes = uint.to_be_bytes();
+            let seq = SeqDeserializer::<_, Error>::new(bytes.iter().copied());
+            assert_eq!(u256::deserialize(seq).unwrap(), uint);
+
+            // Sequences with trailing elements are rejected by the visitor
+            let seq = SeqDeserializer::<_, Error>::new(bytes.iter().copied().chain(Some(0)));
+            assert!(u256::deserialize(SeqAccessDeserializer::new(seq)).is_err());
         };
 
         check(
@@ -0,0 +1,682 @@
+// Rust language amplification library providing multiple generic trait
+// implementations, type wrappers, derive macros and other language enhancements
+//
+// Written in 2020-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the MIT License
+// along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+#[cfg(all(feature = "hex", any(feature = "std", feature = "alloc")))]
+use core::any::Any;
+use core::fmt::{self, Debug, Formatter};
+#[cfg(all(feature = "hex", any(feature = "std", feature = "alloc")))]
+use core::fmt::{Display, LowerHex, UpperHex};
+#[cfg(all(feature = "hex", any(feature = "std", feature = "alloc")))]
+use core::str::FromStr;
+use core::ops::{BitXor, Index, IndexMut, RangeFull};
+use core::borrow::{Borrow, BorrowMut};
+use core::convert::TryFrom;
+use core::hash::{Hash, Hasher};
+use core::{ptr, slice};
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+#[cfg(all(feature = "hex", any(feature = "std", feature = "alloc")))]
+use crate::hex::{Error, FromHex, ToHex};
+use crate::num::error::ParseLengthError;
+use crate::{DeepWrapper, DeepWrapperMut, Wrapper, WrapperMut};
+
+/// Wrapper type for all array-based 160-bit types implementing many important
+/// traits, so types based on it can simply derive their implementations.
+///
+/// Type keeps data in the byte order they were provided and displays them in
+/// the same order (like bitcoin RIPEMD160 and HASH160 hash types).
+pub type Bytes20 = Array<u8, 20>;
+
+/// Wrapper type for all array-based 256-bit types implementing many important
+/// traits, so types based on it can simply derive their implementations.
+///
+/// Type keeps data in little-endian byte order and displays them in the same
+/// order (like bitcoin SHA256 single hash type).
+pub type Bytes32 = Array<u8, 32>;
+
+/// Wrapper type for all array-based 512-bit types implementing many important
+/// traits, so types based on it can simply derive their implementations.
+///
+/// Type keeps data in the byte order they were provided and displays them in
+/// the same order (like SHA512 hashes or 64-byte compact signatures).
+pub type Bytes64 = Array<u8, 64>;
+
+/// Wrapper type for all slice-based 256-bit types implementing many important
+/// traits, so types based on it can simply derive their implementations.
+///
+/// Type alias kept for compatibility with the code written before the
+/// introduction of generic [`Array`]; use [`Bytes32`] in the new code.
+pub type Slice32 = Bytes32;
+
+/// Wrapper type for all array-based 256-bit types which string representation
+/// uses reversed byte order (like bitcoin SHA256d hash types, i.e. transaction
+/// ids). Type keeps data in little-endian byte order.
+pub type Bytes32StrRev = Array<u8, 32, true>;
+
+/// Wrapper type for all fixed arrays implementing many important
+/// traits, so types based on it can simply derive their implementations.
+///
+/// Byte arrays (`Array<u8, LEN>`) keep data in little-endian byte order and
+/// display them in the same order (like bitcoin SHA256 single hash type). If
+/// `REVERSE_STR` generic parameter is set to `true`, hex representation used
+/// by [`Display`], [`FromStr`] and serde uses reversed byte order (like bitcoin
+/// SHA256d hash types, i.e. transaction ids).
+///
+/// Equality comparison does not short-circuit on the first mismatching
+/// element: the intermediate result is accessed with volatile reads, which
+/// prevents the compiler from introducing an early exit. Thus, for byte
+/// arrays the comparison runs in a time independent from the array content.
+#[derive(Clone, Copy, Eq, PartialOrd, Ord)]
+pub struct Array<T, const LEN: usize, const REVERSE_STR: bool = false>([T; LEN]);
+
+impl<T, const LEN: usize, const REVERSE_STR: bool> Array<T, LEN, REVERSE_STR> {
+    /// Constructs array from a provided slice. If the slice length is not
+    /// equal to `LEN`, returns `None`
+    pub fn from_slice(slice: impl AsRef<[T]>) -> Option<Self>
+    where
+        T: Copy,
+    {
+        <[T; LEN]>::try_from(slice.as_ref()).ok().map(Self)
+    }
+
+    /// Copies data from the provided slice into the array. Errors if the slice
+    /// length is not equal to `LEN`, leaving the array unmodified.
+    pub fn copy_from_slice(&mut self, slice: impl AsRef<[T]>) -> Result<(), ParseLengthError>
+    where
+        T: Copy,
+    {
+        let slice = slice.as_ref();
+        if slice.len() != LEN {
+            return Err(ParseLengthError {
+                actual: slice.len(),
+                expected: LEN,
+            });
+        }
+        self.0.copy_from_slice(slice);
+        Ok(())
+    }
+
+    /// Returns slice representation of the array.
+    #[inline]
+    pub fn as_slice(&self) -> &[T] {
+        self.as_ref()
+    }
+
+    /// Returns mutable slice representation of the array.
+    #[inline]
+    pub fn as_slice_mut(&mut self) -> &mut [T] {
+        self.as_mut()
+    }
+
+    /// Returns an iterator over the array items.
+    #[inline]
+    pub fn iter(&self) -> slice::Iter<'_, T> {
+        self.0.iter()
+    }
+
+    /// Returns vector representing internal array data
+    #[allow(clippy::wrong_self_convention)]
+    #[cfg(any(test, feature = "std", feature = "alloc"))]
+    pub fn to_vec(&self) -> Vec<T>
+    where
+        T: Clone,
+    {
+        self.0.to_vec()
+    }
+}
+
+impl<const LEN: usize, const REVERSE_STR: bool> Array<u8, LEN, REVERSE_STR> {
+    #[cfg(feature = "rand")]
+    /// Generates byte array from `rand::thread_rng` random number generator
+    pub fn random() -> Self {
+        use rand::RngCore;
+        let mut entropy = [0u8; LEN];
+        rand::thread_rng().fill_bytes(&mut entropy);
+        Array::from_inner(entropy)
+    }
+}
+
+impl<T, const LEN: usize, const REVERSE_STR: bool> PartialEq for Array<T, LEN, REVERSE_STR>
+where
+    T: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        let mut eq = true;
+        for (a, b) in self.0.iter().zip(other.0.iter()) {
+            // Safety: reading a valid initialized local variable
+            eq = unsafe { ptr::read_volatile(&(eq & (a == b))) };
+        }
+        eq
+    }
+}
+
+impl<T, const LEN: usize, const REVERSE_STR: bool> Hash for Array<T, LEN, REVERSE_STR>
+where
+    T: Hash,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state)
+    }
+}
+
+impl<T, const LEN: usize, const REVERSE_STR: bool> Default for Array<T, LEN, REVERSE_STR>
+where
+    T: Default + Copy,
+{
+    fn default() -> Self {
+        Self([T::default(); LEN])
+    }
+}
+
+impl<T, const LEN: usize, const REVERSE_STR: bool> AsRef<[T]> for Array<T, LEN, REVERSE_STR> {
+    #[inline]
+    fn as_ref(&self) -> &[T] {
+        self.0.as_ref()
+    }
+}
+
+impl<T, const LEN: usize, const REVERSE_STR: bool> AsMut<[T]> for Array<T, LEN, REVERSE_STR> {
+    #[inline]
+    fn as_mut(&mut self) -> &mut [T] {
+        self.0.as_mut()
+    }
+}
+
+impl<T, const LEN: usize, const REVERSE_STR: bool> Borrow<[T]> for Array<T, LEN, REVERSE_STR> {
+    #[inline]
+    fn borrow(&self) -> &[T] {
+        self.0.borrow()
+    }
+}
+
+impl<T, const LEN: usize, const REVERSE_STR: bool> BorrowMut<[T]> for Array<T, LEN, REVERSE_STR> {
+    #[inline]
+    fn borrow_mut(&mut self) -> &mut [T] {
+        self.0.borrow_mut()
+    }
+}
+
+impl<T, const LEN: usize, const REVERSE_STR: bool> Index<usize> for Array<T, LEN, REVERSE_STR> {
+    type Output = T;
+    #[inline]
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.0[index]
+    }
+}
+
+impl<T, const LEN: usize, const REVERSE_STR: bool> Index<u8> for Array<T, LEN, REVERSE_STR> {
+    type Output = T;
+    #[inline]
+    fn index(&self, index: u8) -> &Self::Output {
+        &self.0[index as usize]
+    }
+}
+
+impl<T, const LEN: usize, const REVERSE_STR: bool> Index<RangeFull> for Array<T, LEN, REVERSE_STR> {
+    type Output = [T];
+    #[inline]
+    fn index(&self, index: RangeFull) -> &Self::Output {
+        &self.0[index]
+    }
+}
+
+impl<T, const LEN: usize, const REVERSE_STR: bool> IndexMut<usize> for Array<T, LEN, REVERSE_STR> {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        &mut self.0[index]
+    }
+}
+
+impl<T, const LEN: usize, const REVERSE_STR: bool> IndexMut<u8> for Array<T, LEN, REVERSE_STR> {
+    fn index_mut(&mut self, index: u8) -> &mut Self::Output {
+        &mut self.0[index as usize]
+    }
+}
+
+impl<const LEN: usize, const REVERSE_STR: bool> BitXor for Array<u8, LEN, REVERSE_STR> {
+    type Output = Self;
+
+    fn bitxor(mut self, rhs: Self) -> Self::Output {
+        for (a, b) in self.0.iter_mut().zip(rhs.0.iter()) {
+            *a ^= b;
+        }
+        self
+    }
+}
+
+impl<T, I, const LEN: usize, const REVERSE_STR: bool> From<I> for Array<T, LEN, REVERSE_STR>
+where
+    I: Into<[T; LEN]>,
+{
+    fn from(array: I) -> Self {
+        Self(array.into())
+    }
+}
+
+impl<T, const LEN: usize, const REVERSE_STR: bool> Wrapper for Array<T, LEN, REVERSE_STR>
+where
+    T: Clone,
+{
+    type Inner = [T; LEN];
+
+    #[inline]
+    fn from_inner(inner: Self::Inner) -> Self {
+        Self(inner)
+    }
+
+    #[inline]
+    fn as_inner(&self) -> &Self::Inner {
+        &self.0
+    }
+
+    #[inline]
+    fn into_inner(self) -> Self::Inner {
+        self.0
+    }
+}
+
+impl<T, const LEN: usize, const REVERSE_STR: bool> WrapperMut for Array<T, LEN, REVERSE_STR>
+where
+    T: Clone,
+{
+    #[inline]
+    fn as_inner_mut(&mut self) -> &mut Self::Inner {
+        &mut self.0
+    }
+}
+
+impl<T, const LEN: usize, const REVERSE_STR: bool> DeepWrapper for Array<T, LEN, REVERSE_STR>
+where
+    T: Clone,
+{
+    type Deep = Self::Inner;
+
+    #[inline]
+    fn from_deep(deep: Self::Deep) -> Self {
+        Self(deep)
+    }
+
+    #[inline]
+    fn as_deep(&self) -> &Self::Deep {
+        &self.0
+    }
+
+    #[inline]
+    fn into_deep(self) -> Self::Deep {
+        self.0
+    }
+}
+
+impl<T, const LEN: usize, const REVERSE_STR: bool> DeepWrapperMut for Array<T, LEN, REVERSE_STR>
+where
+    T: Clone,
+{
+    #[inline]
+    fn as_deep_mut(&mut self) -> &mut Self::Deep {
+        &mut self.0
+    }
+}
+
+#[cfg(all(feature = "hex", any(feature = "std", feature = "alloc")))]
+impl<const LEN: usize, const REVERSE_STR: bool> Display for Array<u8, LEN, REVERSE_STR> {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        LowerHex::fmt(self, f)
+    }
+}
+
+impl<const LEN: usize, const REVERSE_STR: bool> Array<u8, LEN, REVERSE_STR> {
+    /// Writes name of the byte array type used in debug output. 32-byte arrays
+    /// are named `Slice32`, keeping the output compatible with the one used
+    /// before the introduction of the generic array.
+    pub(crate) fn fmt_debug_name(f: &mut Formatter<'_>) -> fmt::Result {
+        if LEN == 32 && !REVERSE_STR {
+            f.write_str("Slice32")
+        } else {
+            write!(f, "Array<{}>", LEN)
+        }
+    }
+}
+
+/// Byte arrays are formatted as hex strings (if `hex` feature is enabled),
+/// other arrays as lists of their items.
+impl<T, const LEN: usize, const REVERSE_STR: bool> Debug for Array<T, LEN, REVERSE_STR>
+where
+    T: Debug + 'static,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        #[cfg(all(feature = "hex", any(feature = "std", feature = "alloc")))]
+        if let Some(bytes) = (self as &dyn Any).downcast_ref::<Array<u8, LEN, REVERSE_STR>>() {
+            Array::<u8, LEN, REVERSE_STR>::fmt_debug_name(f)?;
+            return write!(f, "({})", bytes.to_hex());
+        }
+        write!(f, "Array<{}>({:?})", LEN, self.0)
+    }
+}
+
+#[cfg(all(feature = "hex", any(feature = "std", feature = "alloc")))]
+impl<const LEN: usize, const REVERSE_STR: bool> FromStr for Array<u8, LEN, REVERSE_STR> {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_hex(s)
+    }
+}
+
+#[cfg(all(feature = "hex", any(feature = "std", feature = "alloc")))]
+impl<const LEN: usize, const REVERSE_STR: bool> FromHex for Array<u8, LEN, REVERSE_STR> {
+    fn from_byte_iter<I>(iter: I) -> Result<Self, Error>
+    where
+        I: Iterator<Item = Result<u8, Error>> + ExactSizeIterator + DoubleEndedIterator,
+    {
+        let vec = Vec::<u8>::from_byte_iter(iter)?;
+        if vec.len() != LEN {
+            return Err(Error::InvalidLength(LEN, vec.len()));
+        }
+        let mut id = [0u8; LEN];
+        id.copy_from_slice(&vec);
+        if REVERSE_STR {
+            id.reverse();
+        }
+        Ok(Array(id))
+    }
+}
+
+#[cfg(all(feature = "hex", any(feature = "std", feature = "alloc")))]
+impl<const LEN: usize, const REVERSE_STR: bool> Array<u8, LEN, REVERSE_STR> {
+    /// Returns array bytes in the order used for its string representation
+    fn str_bytes(&self) -> [u8; LEN] {
+        let mut bytes = self.0;
+        if REVERSE_STR {
+            bytes.reverse();
+        }
+        bytes
+    }
+}
+
+#[cfg(all(feature = "hex", any(feature = "std", feature = "alloc")))]
+impl<const LEN: usize, const REVERSE_STR: bool> LowerHex for Array<u8, LEN, REVERSE_STR> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let bytes = self.str_bytes();
+        if f.alternate() && LEN > 8 {
+            write!(
+                f,
+                "{}..{}",
+                bytes[..4].to_hex(),
+                bytes[(LEN - 4)..].to_hex()
+            )
+        } else {
+            f.write_str(&bytes.to_hex())
+        }
+    }
+}
+
+#[cfg(all(feature = "hex", any(feature = "std", feature = "alloc")))]
+impl<const LEN: usize, const REVERSE_STR: bool> UpperHex for Array<u8, LEN, REVERSE_STR> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let bytes = self.str_bytes();
+        if f.alternate() && LEN > 8 {
+            write!(
+                f,
+                "{}..{}",
+                bytes[..4].to_hex().to_ascii_uppercase(),
+                bytes[(LEN - 4)..].to_hex().to_ascii_uppercase()
+            )
+        } else {
+            f.write_str(&bytes.to_hex().to_ascii_uppercase())
+        }
+    }
+}
+
+#[cfg(all(feature = "serde", feature = "hex"))]
+pub(crate) mod serde_helpers {
+    //! Serde serialization helpers
+
+    use core::fmt;
+    use serde::de::{self, SeqAccess, Unexpected};
+    use serde::ser::SerializeTuple;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::Array;
+    use crate::hex::{FromHex, ToHex};
+
+    impl<const LEN: usize, const REVERSE_STR: bool> Serialize for Array<u8, LEN, REVERSE_STR> {
+        /// Serializes byte array to a lowercase hex string for human-readable
+        /// formats and to a fixed-size tuple of bytes for binary formats.
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            if serializer.is_human_readable() {
+                serializer.serialize_str(&self.to_hex())
+            } else {
+                let mut tuple = serializer.serialize_tuple(LEN)?;
+                for byte in self.iter() {
+                    tuple.serialize_element(byte)?;
+                }
+                tuple.end()
+            }
+        }
+    }
+
+    impl<'de, const LEN: usize, const REVERSE_STR: bool> Deserialize<'de>
+        for Array<u8, LEN, REVERSE_STR>
+    {
+        /// Deserializes a lowercase hex string (for human-readable formats) or
+        /// a sequence of bytes (for binary formats) to a byte array.
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            struct Visitor<const LEN: usize, const REVERSE_STR: bool>;
+            impl<'de, const LEN: usize, const REVERSE_STR: bool> de::Visitor<'de>
+                for Visitor<LEN, REVERSE_STR>
+            {
+                type Value = Array<u8, LEN, REVERSE_STR>;
+
+                fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                    write!(
+                        f,
+                        "{} bytes or a hex string with {} characters",
+                        LEN,
+                        LEN * 2
+                    )
+                }
+
+                fn visit_str<E>(self, s: &str) -> Result<Self::Value, E>
+                where
+                    E: de::Error,
+                {
+                    Array::from_hex(s).map_err(|err| match err {
+                        crate::hex::Error::InvalidLength(..) => E::invalid_length(s.len(), &self),
+                        _ => E::invalid_value(Unexpected::Str(s), &self),
+                    })
+                }
+
+                fn visit_bytes<E>(self, bytes: &[u8]) -> Result<Self::Value, E>
+                where
+                    E: de::Error,
+                {
+                    Array::from_slice(bytes).ok_or_else(|| E::invalid_length(bytes.len(), &self))
+                }
+
+                fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+                where
+                    A: SeqAccess<'de>,
+                {
+                    let mut array = [0u8; LEN];
+                    for (index, byte) in array.iter_mut().enumerate() {
+                        *byte = seq
+                            .next_element()?
+                            .ok_or_else(|| de::Error::invalid_length(index, &self))?;
+                    }
+                    let mut len = LEN;
+                    while seq.next_element::<de::IgnoredAny>()?.is_some() {
+                        len += 1;
+                    }
+                    if len != LEN {
+                        return Err(de::Error::invalid_length(len, &self));
+                    }
+                    Ok(Array(array))
+                }
+            }
+
+            if deserializer.is_human_readable() {
+                deserializer.deserialize_str(Visitor)
+            } else {
+                deserializer.deserialize_tuple(LEN, Visitor)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Array, Bytes20, Bytes32StrRev, Bytes64, Error, Slice32};
+    use crate::Wrapper;
+    use crate::hex::FromHex;
+    use crate::num::error::ParseLengthError;
+    use core::str::FromStr;
+
+    #[test]
+    fn test_slice32_str() {
+        let s = "a3401bcceb26201b55978ff705fecf7d8a0a03598ebeccf2a947030b91a0ff53";
+        let slice32 = Slice32::from_hex(s).unwrap();
+        assert_eq!(Slice32::from_str(s), Ok(slice32));
+
+        assert_eq!(Slice32::from_hex(&s.to_uppercase()), Ok(slice32));
+        assert_eq!(
+            Slice32::from_str(&s[..30]),
+            Err(Error::InvalidLength(32, 15))
+        );
+
+        assert_eq!(&slice32.to_string(), s);
+        assert_eq!(format!("{:x}", slice32), s);
+        assert_eq!(format!("{:X}", slice32), s.to_uppercase());
+        assert_eq!(format!("{:?}", slice32), format!("Slice32({})", s));
+    }
+
+    #[test]
+    #[allow(unknown_lints, clippy::needless_borrows_for_generic_args)]
+    fn test_encoding() {
+        let s = "a3401bcceb26201b55978ff705fecf7d8a0a03598ebeccf2a947030b91a0ff53";
+        let slice32 = Slice32::from_hex(s).unwrap();
+
+        let data = [
+            0xa3, 0x40, 0x1b, 0xcc, 0xeb, 0x26, 0x20, 0x1b, 0x55, 0x97, 0x8f, 0xf7, 0x05, 0xfe,
+            0xcf, 0x7d, 0x8a, 0x0a, 0x03, 0x59, 0x8e, 0xbe, 0xcc, 0xf2, 0xa9, 0x47, 0x03, 0x0b,
+            0x91, 0xa0, 0xff, 0x53,
+        ];
+
+        assert_eq!(Slice32::from_slice(&data), Some(slice32));
+        assert_eq!(Slice32::from_slice(&data[..30]), None);
+        assert_eq!(&slice32.to_vec(), &data);
+        assert_eq!(&slice32.as_inner()[..], &data);
+        assert_eq!(slice32.to_inner(), data);
+        assert_eq!(slice32.into_inner(), data);
+    }
+
+    #[test]
+    fn test_array_sizes() {
+        let s = "a3401bcceb26201b55978ff705fecf7d8a0a0359";
+        let bytes20 = Bytes20::from_str(s).unwrap();
+        assert_eq!(bytes20.to_string(), s);
+        assert_eq!(format!("{:#x}", bytes20), "a3401bcc..8a0a0359");
+        assert_eq!(Bytes64::from_str(s), Err(Error::InvalidLength(64, 20)));
+
+        let mut bytes64 = Bytes64::default();
+        assert_eq!(
+            bytes64.copy_from_slice(bytes20),
+            Err(ParseLengthError {
+                actual: 20,
+                expected: 64
+            })
+        );
+        assert_eq!(bytes64, Bytes64::default());
+        bytes64.copy_from_slice([0xFFu8; 64]).unwrap();
+        assert!(bytes64.iter().all(|byte| *byte == 0xFF));
+    }
+
+    #[test]
+    fn test_generic_array() {
+        let mut array = Array::<u16, 3>::from_inner([1, 2, 3]);
+        assert_eq!(array[1usize], 2);
+        array[2u8] = 4;
+        assert_eq!(array.as_slice(), &[1, 2, 4]);
+        assert_eq!(array.iter().sum::<u16>(), 7);
+        assert_eq!(Array::<u16, 3>::from_slice([1u16, 2, 4]), Some(array));
+        assert_ne!(array, Array::default());
+        assert_eq!(format!("{:?}", array), "Array<3>([1, 2, 4])");
+    }
+
+    #[test]
+    fn test_xor() {
+        let a = Bytes20::from_inner([0x0F; 20]);
+        let b = Bytes20::from_inner([0xFF; 20]);
+        assert_eq!(a ^ b, Bytes20::from_inner([0xF0; 20]));
+        assert_eq!(a ^ a, Bytes20::default());
+    }
+
+    #[test]
+    fn test_reverse_str() {
+        let s = "a3401bcceb26201b55978ff705fecf7d8a0a03598ebeccf2a947030b91a0ff53";
+        let forward = Slice32::from_str(s).unwrap();
+        let reversed = Bytes32StrRev::from_str(s).unwrap();
+        let mut data = forward.into_inner();
+        data.reverse();
+        assert_eq!(reversed.into_inner(), data);
+        assert_eq!(reversed.to_string(), s);
+        assert_eq!(format!("{:X}", reversed), s.to_uppercase());
+        assert_eq!(format!("{:#x}", reversed), "a3401bcc..91a0ff53");
+        assert_eq!(Bytes32StrRev::from_inner(data).to_string(), s);
+        assert_eq!(Bytes32StrRev::from_slice(data), Some(reversed));
+    }
+
+    #[cfg(all(feature = "serde", feature = "hex"))]
+    #[test]
+    fn test_serde() {
+        let s = "a3401bcceb26201b55978ff705fecf7d8a0a03598ebeccf2a947030b91a0ff53";
+        let slice32 = Slice32::from_str(s).unwrap();
+        let reversed = Bytes32StrRev::from_str(s).unwrap();
+
+        let json = format!("\"{}\"", s);
+        assert_eq!(serde_json::to_string(&slice32).unwrap(), json);
+        assert_eq!(serde_json::to_string(&reversed).unwrap(), json);
+        assert_eq!(serde_json::from_str::<Slice32>(&json).unwrap(), slice32);
+        assert_eq!(
+            serde_json::from_str::<Bytes32StrRev>(&json).unwrap(),
+            reversed
+        );
+        assert!(serde_json::from_str::<Bytes20>(&json).is_err());
+        assert!(serde_json::from_str::<Slice32>("\"a3401bcc\"").is_err());
+
+        let bin = bincode::serialize(&slice32).unwrap();
+        assert_eq!(bin, slice32.to_vec());
+        assert_eq!(bincode::deserialize::<Slice32>(&bin).unwrap(), slice32);
+        assert_eq!(bincode::serialize(&reversed).unwrap(), reversed.to_vec());
+        assert!(bincode::deserialize::<Bytes64>(&bin).is_err());
+
+        use serde::de::value::{Error, SeqAccessDeserializer, SeqDeserializer};
+        use serde::Deserialize;
+        let seq = |len: usize| {
+            SeqAccessDeserializer::new(SeqDeserializer::<_, Error>::new(
+                slice32.iter().copied().cycle().take(len),
+            ))
+        };
+        assert_eq!(Slice32::deserialize(seq(32)).unwrap(), slice32);
+        assert!(Slice32::deserialize(seq(31)).is_err());
+        assert!(Slice32::deserialize(seq(33)).is_err());
+    }
+}
@@ -0,0 +1,56 @@
+// Rust language amplification library providing multiple generic trait
+// implementations, type wrappers, derive macros and other language enhancements
+//
+// Written in 2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the MIT License
+// along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+use core::fmt::{self, Display, Formatter};
+
+/// Error returned by `build()` method of the builders produced with
+/// `#[derive(Builder)]` when a required field was not set
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct MissingFieldError {
+    /// Name of the type constructed by the builder
+    pub ty: &'static str,
+    /// Name of the field which was not set
+    pub field: &'static str,
+}
+
+impl Display for MissingFieldError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "required field `{}` of `{}` was not set",
+            self.field, self.ty
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for MissingFieldError {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn display() {
+        let err = MissingFieldError {
+            ty: "Config",
+            field: "name",
+        };
+        assert_eq!(
+            err.to_string(),
+            "required field `name` of `Config` was not set"
+        );
+    }
+}
@@ -0,0 +1,57 @@
+// Rust language amplification library providing multiple generic trait
+// implementations, type wrappers, derive macros and other language enhancements
+//
+// Written in 2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the MIT License
+// along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+use core::fmt::{self, Display, Formatter};
+
+/// Error returned by `TryFrom<u8>` implementations produced with
+/// `#[derive(EnumMeta)]` when the value does not match any of the enum
+/// discriminants
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct UnknownDiscriminantError {
+    /// Name of the enum type
+    pub ty: &'static str,
+    /// Value which failed to convert
+    pub value: u8,
+}
+
+impl Display for UnknownDiscriminantError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "value {} does not match any variant of `{}`",
+            self.value, self.ty
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for UnknownDiscriminantError {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn display() {
+        let err = UnknownDiscriminantError {
+            ty: "Network",
+            value: 7,
+        };
+        assert_eq!(
+            err.to_string(),
+            "value 7 does not match any variant of `Network`"
+        );
+    }
+}
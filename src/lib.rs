@@ -17,7 +17,7 @@
 //! Amplifying Rust language capabilities: multiple generic trait
 //! implementations, type wrappers, derive macros.
 //!
-//! Minimum supported rust compiler version (MSRV): 1.46 (stable channel)
+//! Minimum supported rust compiler version (MSRV): 1.60 (stable channel)
 
 #![recursion_limit = "256"]
 #![deny(
@@ -36,15 +36,25 @@ extern crate alloc;
 extern crate core;
 
 #[cfg(feature = "derive")]
-#[macro_use]
+#[cfg_attr(feature = "std", macro_use)]
 extern crate amplify_derive;
 #[cfg(feature = "derive")]
-pub use amplify_derive::{Wrapper, Display, AsAny, From, Getters, Error};
+pub use amplify_derive::{
+    Wrapper, WrapperMut, Display, FromStr, AsAny, From, Getters, Setters, Builder, EnumMeta,
+    StrictEncode, StrictDecode, Error,
+};
 
 #[cfg(feature = "serde")]
-#[macro_use]
 extern crate serde_crate as serde;
 
+/// Re-exports used by the code generated with `amplify_derive` macros; not
+/// a part of the public API.
+#[doc(hidden)]
+pub mod _export {
+    #[cfg(feature = "serde")]
+    pub use serde_crate as serde;
+}
+
 extern crate amplify_num;
 #[cfg(any(test, feature = "hex"))]
 pub use num::hex;
@@ -62,20 +72,25 @@ mod macros;
 #[macro_use]
 mod wrapper;
 
+mod array;
 mod as_any;
 mod bipolar;
+mod builder;
 mod dumb_default;
+mod enum_meta;
 #[cfg(all(feature = "std", feature = "derive"))]
 mod io_error;
 #[cfg(feature = "c_raw")]
 mod raw;
-mod slice32;
+mod secret;
 pub mod strategy;
 #[cfg(feature = "serde")]
 mod to_serde_string;
 
 #[cfg(feature = "std")]
 pub mod flags;
+#[cfg(feature = "std")]
+mod strict_encoding;
 
 pub mod num {
     //! Custom-sized numeric types
@@ -97,10 +112,15 @@ pub mod num {
 
 pub use crate::as_any::AsAny;
 pub use crate::bipolar::Bipolar;
+pub use crate::builder::MissingFieldError;
+pub use crate::enum_meta::UnknownDiscriminantError;
+#[cfg(feature = "std")]
+pub use crate::strict_encoding::{StrictDecode, StrictEncode, StrictEncodingError};
 pub use crate::strategy::Holder;
-pub use crate::wrapper::Wrapper;
-pub use crate::slice32::Slice32;
+pub use crate::wrapper::{DeepWrapper, DeepWrapperMut, Wrapper, WrapperMut};
+pub use crate::array::{Array, Bytes20, Bytes32, Bytes32StrRev, Bytes64, Slice32};
 pub use crate::dumb_default::DumbDefault;
+pub use crate::secret::{Secret, SecretData, SecretSlice32};
 #[cfg(feature = "serde")]
 pub use crate::to_serde_string::{ToYamlString, ToJsonString, ToTomlString};
 #[cfg(all(feature = "std", feature = "derive"))]
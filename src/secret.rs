@@ -0,0 +1,371 @@
+// Rust language amplification library providing multiple generic trait
+// implementations, type wrappers, derive macros and other language enhancements
+//
+// Written in 2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the MIT License
+// along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+use core::fmt::{self, Debug, Display, Formatter};
+use core::ptr;
+use core::sync::atomic::{compiler_fence, Ordering};
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+use crate::{Array, Slice32};
+
+/// Secret 256-bit byte array, zeroized on drop.
+pub type SecretSlice32 = Secret<Slice32>;
+
+/// Byte data which can be kept in [`Secret`] container.
+///
+/// Newtypes wrapping secret data (implementing [`crate::WrapperMut`], for
+/// instance with `#[derive(Wrapper, WrapperMut)]`) get the implementation
+/// with [`impl_secret_data!`] macro.
+pub trait SecretData {
+    /// Returns bytes of the secret data; used for the comparison.
+    fn as_bytes(&self) -> &[u8];
+
+    /// Writes name of the data type used in the redacted [`Debug`] output of
+    /// the [`Secret`] container.
+    fn fmt_type_name(f: &mut Formatter<'_>) -> fmt::Result;
+
+    /// Overwrites all memory owned by the data with zeros.
+    fn zeroize(&mut self);
+}
+
+/// Overwrites bytes with zeros in a way which is not elided by the compiler
+fn zeroize_bytes(bytes: &mut [u8]) {
+    for byte in bytes {
+        // Volatile writes are not elided by the compiler even if the memory
+        // is not read after
+        unsafe { ptr::write_volatile(byte, 0) };
+    }
+    compiler_fence(Ordering::SeqCst);
+}
+
+impl<const LEN: usize> SecretData for [u8; LEN] {
+    fn as_bytes(&self) -> &[u8] {
+        self
+    }
+
+    fn fmt_type_name(f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "[u8; {}]", LEN)
+    }
+
+    fn zeroize(&mut self) {
+        zeroize_bytes(self)
+    }
+}
+
+impl<const LEN: usize, const REVERSE_STR: bool> SecretData for Array<u8, LEN, REVERSE_STR> {
+    fn as_bytes(&self) -> &[u8] {
+        self.as_slice()
+    }
+
+    fn fmt_type_name(f: &mut Formatter<'_>) -> fmt::Result {
+        Self::fmt_debug_name(f)
+    }
+
+    fn zeroize(&mut self) {
+        zeroize_bytes(self.as_slice_mut())
+    }
+}
+
+/// Zeroizes the whole allocated buffer of the vector, including its spare
+/// capacity. Buffers left by the reallocations which happened before the
+/// vector was put into the [`Secret`] container can't be zeroized, so the
+/// vector should be allocated with the final capacity.
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl SecretData for Vec<u8> {
+    fn as_bytes(&self) -> &[u8] {
+        self
+    }
+
+    fn fmt_type_name(f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("Vec<u8>")
+    }
+
+    fn zeroize(&mut self) {
+        let ptr = self.as_mut_ptr();
+        for index in 0..self.capacity() {
+            // Safety: the pointer stays within the buffer allocated by the
+            // vector, and any byte value is valid for `u8`
+            unsafe { ptr::write_volatile(ptr.add(index), 0) };
+        }
+        compiler_fence(Ordering::SeqCst);
+    }
+}
+
+/// Container for secret byte data (private keys, seeds etc).
+///
+/// The container:
+/// - zeroizes memory holding the data when dropped;
+/// - redacts the data from [`Debug`] (like `Slice32(***)`) and [`Display`]
+///   output;
+/// - compares data in constant time;
+/// - reveals data only through explicit [`Secret::expose_secret`] call;
+/// - can't be cloned or copied, so the data are never duplicated implicitly.
+///
+/// Newtypes around secret data may be kept in the container as well, once
+/// they implement [`SecretData`] with [`impl_secret_data!`] macro, which
+/// accesses the wrapped data in place with [`crate::Wrapper`] and
+/// [`crate::WrapperMut`] traits. The container itself does not implement the
+/// wrapper traits: they would expose or copy the secret data implicitly.
+///
+/// Serde support is opt-in: the type implements `Deserialize`, while
+/// serialization of the secret data has to be explicitly requested with
+/// `#[serde(serialize_with = "Secret::serialize_exposed")]` field attribute.
+#[derive(Default)]
+pub struct Secret<T>(T)
+where
+    T: SecretData;
+
+impl<T> Secret<T>
+where
+    T: SecretData,
+{
+    /// Constructs secret container taking ownership of the secret data
+    #[inline]
+    pub fn new(secret: T) -> Self {
+        Self(secret)
+    }
+
+    /// Returns reference to the secret data
+    #[inline]
+    pub fn expose_secret(&self) -> &T {
+        &self.0
+    }
+
+    /// Overwrites the secret data with zeros. The same is done when the
+    /// container is dropped.
+    #[inline]
+    pub fn zeroize(&mut self) {
+        self.0.zeroize()
+    }
+
+    /// Serializes secret data. Used with serde field attribute
+    /// `#[serde(serialize_with = "Secret::serialize_exposed")]` for
+    /// explicit opt-in into serialization of the secret data.
+    #[cfg(feature = "serde")]
+    pub fn serialize_exposed<S>(secret: &Self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+        T: serde::Serialize,
+    {
+        secret.0.serialize(serializer)
+    }
+}
+
+/// Implements [`SecretData`] for a newtype wrapping secret data, so it can be
+/// kept in [`Secret`] container. The newtype must implement
+/// [`crate::WrapperMut`] with inner type implementing [`SecretData`]; its data
+/// are accessed in place, without cloning.
+///
+/// # Example
+/// ```
+/// # #[macro_use] extern crate amplify;
+/// use amplify::{Secret, Slice32, Wrapper, WrapperMut};
+///
+/// #[derive(Wrapper, WrapperMut, Default, From)]
+/// struct PrivateKey(Slice32);
+///
+/// impl_secret_data!(PrivateKey);
+///
+/// let key = Secret::new(PrivateKey::from_inner(Slice32::from_inner([7u8; 32])));
+/// assert_eq!(format!("{:?}", key), "PrivateKey(***)");
+/// assert_eq!(key.expose_secret().as_inner()[0usize], 7);
+/// ```
+#[macro_export]
+macro_rules! impl_secret_data {
+    ($ty:ty) => {
+        impl $crate::SecretData for $ty {
+            fn as_bytes(&self) -> &[u8] {
+                $crate::SecretData::as_bytes($crate::Wrapper::as_inner(self))
+            }
+
+            fn fmt_type_name(f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                f.write_str(stringify!($ty))
+            }
+
+            fn zeroize(&mut self) {
+                $crate::SecretData::zeroize($crate::WrapperMut::as_inner_mut(self))
+            }
+        }
+    };
+}
+
+#[cfg(feature = "rand")]
+impl<const LEN: usize> Secret<Array<u8, LEN>> {
+    /// Generates secret byte array from `rand::thread_rng` random number
+    /// generator
+    pub fn random() -> Self {
+        Self(Array::random())
+    }
+}
+
+impl<T> Drop for Secret<T>
+where
+    T: SecretData,
+{
+    fn drop(&mut self) {
+        self.0.zeroize()
+    }
+}
+
+impl<T> PartialEq for Secret<T>
+where
+    T: SecretData,
+{
+    fn eq(&self, other: &Self) -> bool {
+        let (a, b) = (self.0.as_bytes(), other.0.as_bytes());
+        if a.len() != b.len() {
+            return false;
+        }
+        let mut acc = 0u8;
+        for (a, b) in a.iter().zip(b) {
+            // Volatile read prevents the compiler from introducing an early
+            // exit; safety: reading a valid initialized local variable
+            acc = unsafe { ptr::read_volatile(&(acc | (a ^ b))) };
+        }
+        acc == 0
+    }
+}
+
+impl<T> Eq for Secret<T> where T: SecretData {}
+
+impl<T> Debug for Secret<T>
+where
+    T: SecretData,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        T::fmt_type_name(f)?;
+        f.write_str("(***)")
+    }
+}
+
+impl<T> Display for Secret<T>
+where
+    T: SecretData,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("***")
+    }
+}
+
+impl<T> From<T> for Secret<T>
+where
+    T: SecretData,
+{
+    #[inline]
+    fn from(secret: T) -> Self {
+        Self(secret)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T> serde::Deserialize<'de> for Secret<T>
+where
+    T: SecretData + serde::Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        T::deserialize(deserializer).map(Self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Bytes20, Wrapper, WrapperMut};
+
+    #[derive(Default)]
+    struct Key(Slice32);
+
+    impl Wrapper for Key {
+        type Inner = Slice32;
+
+        fn from_inner(inner: Self::Inner) -> Self {
+            Self(inner)
+        }
+
+        fn as_inner(&self) -> &Self::Inner {
+            &self.0
+        }
+
+        fn into_inner(self) -> Self::Inner {
+            self.0
+        }
+    }
+
+    impl WrapperMut for Key {
+        fn as_inner_mut(&mut self) -> &mut Self::Inner {
+            &mut self.0
+        }
+    }
+
+    impl_secret_data!(Key);
+
+    #[test]
+    fn test_redaction() {
+        let secret = SecretSlice32::new(Slice32::from_inner([0xA5; 32]));
+        assert_eq!(format!("{:?}", secret), "Slice32(***)");
+        assert_eq!(format!("{}", secret), "***");
+        assert_eq!(secret.expose_secret(), &Slice32::from_inner([0xA5; 32]));
+
+        assert_eq!(
+            format!("{:?}", Secret::new(Bytes20::default())),
+            "Array<20>(***)"
+        );
+        assert_eq!(format!("{:?}", Secret::new([1u8; 4])), "[u8; 4](***)");
+        assert_eq!(format!("{:?}", Secret::new(vec![1u8])), "Vec<u8>(***)");
+    }
+
+    #[test]
+    fn test_eq() {
+        let secret = Secret::new([1u8, 2, 3]);
+        assert_eq!(secret, Secret::from([1u8, 2, 3]));
+        assert_ne!(secret, Secret::from([1u8, 2, 4]));
+        assert_ne!(Secret::new(vec![1u8, 2]), Secret::new(vec![1u8, 2, 3]));
+    }
+
+    #[test]
+    fn test_zeroize() {
+        let mut secret = SecretSlice32::new(Slice32::from_inner([0xFF; 32]));
+        secret.zeroize();
+        assert_eq!(secret.expose_secret(), &Slice32::default());
+    }
+
+    #[test]
+    fn test_wrapper() {
+        let mut secret = Secret::new(Key::from_inner(Slice32::from_inner([0xFF; 32])));
+        assert_eq!(format!("{:?}", secret), "Key(***)");
+        assert_eq!(secret, Secret::new(Key::from_inner([0xFF; 32].into())));
+        assert_ne!(secret, Secret::new(Key::default()));
+        secret.zeroize();
+        assert_eq!(secret.expose_secret().as_inner(), &Slice32::default());
+    }
+
+    #[test]
+    fn test_zeroize_spare_capacity() {
+        let mut data = Vec::with_capacity(8);
+        data.extend_from_slice(&[0xFFu8; 6]);
+        data.truncate(2);
+        let mut secret = Secret::new(data);
+        secret.zeroize();
+        let data = secret.expose_secret();
+        assert_eq!(data, &[0u8; 2]);
+        // Safety: all bytes of the buffer were initialized by `zeroize`
+        let buffer = unsafe { core::slice::from_raw_parts(data.as_ptr(), data.capacity()) };
+        assert_eq!(buffer, &[0u8; 8]);
+    }
+}
@@ -0,0 +1,674 @@
+// Rust language amplification library providing multiple generic trait
+// implementations, type wrappers, derive macros and other language enhancements
+//
+// Written in 2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the MIT License
+// along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::convert::TryFrom;
+use std::fmt::{self, Display, Formatter};
+use std::io;
+use std::str::Utf8Error;
+
+use crate::flags::FlagVec;
+use crate::num::{i1024, i256, i512, u1, u1024, u2, u24, u256, u3, u4, u5, u512, u6, u7};
+use crate::Array;
+
+/// Errors happening during strict encoding and decoding
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum StrictEncodingError {
+    /// I/O error.
+    ///
+    /// Only the kind of the error is kept, like in `IoError` type, so the
+    /// error type remains `Clone` and `Eq` and can be compared in tests;
+    /// strict decoding happens mostly from in-memory buffers, where the only
+    /// possible I/O error is an unexpected end of data.
+    Io(io::ErrorKind),
+
+    /// String data are not a valid UTF-8
+    Utf8(Utf8Error),
+
+    /// Collection with the given number of items exceeds the maximum of
+    /// `u16::MAX` items
+    ExceedMaxItems(usize),
+
+    /// Byte encoding `Option` is neither 0 nor 1
+    WrongOptionalEncoding(u8),
+
+    /// Decoded value is out of range for the type
+    ValueOutOfRange {
+        /// Name of the type
+        ty: &'static str,
+        /// Decoded value
+        value: u64,
+    },
+
+    /// Decoded tag does not match any variant of the enum
+    UnknownTag {
+        /// Name of the enum type
+        ty: &'static str,
+        /// Decoded tag
+        tag: u8,
+    },
+
+    /// Data do not represent a canonical encoding of the type
+    NonCanonical(&'static str),
+
+    /// Data were not entirely consumed during decoding
+    DataNotEntirelyConsumed,
+}
+
+impl Display for StrictEncodingError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            StrictEncodingError::Io(kind) => Display::fmt(&io::Error::from(*kind), f),
+            StrictEncodingError::Utf8(err) => Display::fmt(err, f),
+            StrictEncodingError::ExceedMaxItems(len) => write!(
+                f,
+                "collection with {} items exceeds the maximum of {} items",
+                len,
+                u16::MAX
+            ),
+            StrictEncodingError::WrongOptionalEncoding(byte) => write!(
+                f,
+                "optional value is encoded with byte {} instead of 0 or 1",
+                byte
+            ),
+            StrictEncodingError::ValueOutOfRange { ty, value } => {
+                write!(f, "value {} is out of range for `{}`", value, ty)
+            }
+            StrictEncodingError::UnknownTag { ty, tag } => {
+                write!(f, "tag {} does not match any variant of `{}`", tag, ty)
+            }
+            StrictEncodingError::NonCanonical(ty) => {
+                write!(f, "data are not a canonical encoding of `{}`", ty)
+            }
+            StrictEncodingError::DataNotEntirelyConsumed => {
+                f.write_str("data were not entirely consumed during decoding")
+            }
+        }
+    }
+}
+
+impl std::error::Error for StrictEncodingError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            StrictEncodingError::Utf8(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// Drops the error details, keeping just [`io::ErrorKind`] (see
+/// [`StrictEncodingError::Io`])
+impl From<io::Error> for StrictEncodingError {
+    fn from(err: io::Error) -> Self {
+        StrictEncodingError::Io(err.kind())
+    }
+}
+
+impl From<Utf8Error> for StrictEncodingError {
+    fn from(err: Utf8Error) -> Self {
+        StrictEncodingError::Utf8(err)
+    }
+}
+
+/// Types which can be written with strict binary encoding: deterministic
+/// serialization of the data, such that each value has exactly one valid
+/// binary representation.
+///
+/// The encoding rules are:
+/// - integers, including custom-sized ones from [`crate::num`], are written in
+///   little-endian byte order using their bit dimension (`u24` takes 3 bytes,
+///   `u256` takes 32 bytes); small integers (`u1`-`u7`) and `bool` take a
+///   single byte;
+/// - fixed-size byte arrays ([`Array`], [`crate::Slice32`] etc) are written
+///   as is, without length prefix;
+/// - strings, vectors, sets and maps are prefixed with the number of items as
+///   `u16`, so they can't contain more than `u16::MAX` items; set and map
+///   items are written in ascending order;
+/// - `Option` is written as a `0` byte for `None` and as a `1` byte followed
+///   by the value for `Some`;
+/// - [`FlagVec`] is written as a byte vector without trailing zero bytes;
+/// - structures are written as a sequence of their fields; enums are prefixed
+///   with a `u8` tag of the variant (see `StrictEncode` derive macro).
+///
+/// Decoding fails on any data which can't be produced by the encoder, like
+/// unsorted set items or trailing zero bytes in [`FlagVec`].
+///
+/// The trait can be derived with `#[derive(StrictEncode)]`.
+pub trait StrictEncode {
+    /// Writes the value to the writer, returning the number of bytes written
+    fn strict_encode<W: io::Write>(&self, writer: W) -> Result<usize, StrictEncodingError>;
+
+    /// Serializes the value into a byte vector
+    fn strict_serialize(&self) -> Result<Vec<u8>, StrictEncodingError> {
+        let mut data = vec![];
+        self.strict_encode(&mut data)?;
+        Ok(data)
+    }
+}
+
+/// Types which can be read from strict binary encoding.
+///
+/// The trait can be derived with `#[derive(StrictDecode)]`.
+pub trait StrictDecode: Sized {
+    /// Reads the value from the reader
+    fn strict_decode<R: io::Read>(reader: R) -> Result<Self, StrictEncodingError>;
+
+    /// Deserializes the value from a byte slice, failing if some of the data
+    /// remain unconsumed
+    fn strict_deserialize(data: impl AsRef<[u8]>) -> Result<Self, StrictEncodingError> {
+        let data = data.as_ref();
+        let mut cursor = io::Cursor::new(data);
+        let value = Self::strict_decode(&mut cursor)?;
+        if cursor.position() as usize != data.len() {
+            return Err(StrictEncodingError::DataNotEntirelyConsumed);
+        }
+        Ok(value)
+    }
+}
+
+/// Writes the number of collection items as `u16`
+fn encode_len<W: io::Write>(len: usize, writer: W) -> Result<usize, StrictEncodingError> {
+    if len > u16::MAX as usize {
+        return Err(StrictEncodingError::ExceedMaxItems(len));
+    }
+    (len as u16).strict_encode(writer)
+}
+
+/// Reads the number of collection items written with [`encode_len`]
+fn decode_len<R: io::Read>(reader: R) -> Result<usize, StrictEncodingError> {
+    u16::strict_decode(reader).map(usize::from)
+}
+
+macro_rules! impl_strict_int {
+    ($($ty:ty),+) => {
+        $(
+            impl StrictEncode for $ty {
+                fn strict_encode<W: io::Write>(&self, mut writer: W) -> Result<usize, StrictEncodingError> {
+                    let bytes = self.to_le_bytes();
+                    writer.write_all(&bytes)?;
+                    Ok(bytes.len())
+                }
+            }
+
+            impl StrictDecode for $ty {
+                fn strict_decode<R: io::Read>(mut reader: R) -> Result<Self, StrictEncodingError> {
+                    let mut bytes = [0u8; ::core::mem::size_of::<$ty>()];
+                    reader.read_exact(&mut bytes)?;
+                    Ok(<$ty>::from_le_bytes(bytes))
+                }
+            }
+        )+
+    };
+}
+
+impl_strict_int!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128);
+impl_strict_int!(u256, u512, u1024, i256, i512, i1024);
+
+macro_rules! impl_strict_smallint {
+    ($($ty:ident),+) => {
+        $(
+            impl StrictEncode for $ty {
+                fn strict_encode<W: io::Write>(&self, writer: W) -> Result<usize, StrictEncodingError> {
+                    self.as_u8().strict_encode(writer)
+                }
+            }
+
+            impl StrictDecode for $ty {
+                fn strict_decode<R: io::Read>(reader: R) -> Result<Self, StrictEncodingError> {
+                    let value = u8::strict_decode(reader)?;
+                    $ty::try_from(value).map_err(|_| StrictEncodingError::ValueOutOfRange {
+                        ty: stringify!($ty),
+                        value: value as u64,
+                    })
+                }
+            }
+        )+
+    };
+}
+
+impl_strict_smallint!(u1, u2, u3, u4, u5, u6, u7);
+
+impl StrictEncode for u24 {
+    fn strict_encode<W: io::Write>(&self, mut writer: W) -> Result<usize, StrictEncodingError> {
+        writer.write_all(&self.as_u32().to_le_bytes()[..3])?;
+        Ok(3)
+    }
+}
+
+impl StrictDecode for u24 {
+    fn strict_decode<R: io::Read>(mut reader: R) -> Result<Self, StrictEncodingError> {
+        let mut bytes = [0u8; 4];
+        reader.read_exact(&mut bytes[..3])?;
+        Ok(u24::with(u32::from_le_bytes(bytes)))
+    }
+}
+
+impl StrictEncode for bool {
+    fn strict_encode<W: io::Write>(&self, writer: W) -> Result<usize, StrictEncodingError> {
+        (*self as u8).strict_encode(writer)
+    }
+}
+
+impl StrictDecode for bool {
+    fn strict_decode<R: io::Read>(reader: R) -> Result<Self, StrictEncodingError> {
+        match u8::strict_decode(reader)? {
+            0 => Ok(false),
+            1 => Ok(true),
+            value => Err(StrictEncodingError::ValueOutOfRange {
+                ty: "bool",
+                value: value as u64,
+            }),
+        }
+    }
+}
+
+impl<const LEN: usize> StrictEncode for [u8; LEN] {
+    fn strict_encode<W: io::Write>(&self, mut writer: W) -> Result<usize, StrictEncodingError> {
+        writer.write_all(self)?;
+        Ok(LEN)
+    }
+}
+
+impl<const LEN: usize> StrictDecode for [u8; LEN] {
+    fn strict_decode<R: io::Read>(mut reader: R) -> Result<Self, StrictEncodingError> {
+        let mut bytes = [0u8; LEN];
+        reader.read_exact(&mut bytes)?;
+        Ok(bytes)
+    }
+}
+
+impl<const LEN: usize, const REVERSE_STR: bool> StrictEncode for Array<u8, LEN, REVERSE_STR> {
+    fn strict_encode<W: io::Write>(&self, mut writer: W) -> Result<usize, StrictEncodingError> {
+        writer.write_all(self.as_slice())?;
+        Ok(LEN)
+    }
+}
+
+impl<const LEN: usize, const REVERSE_STR: bool> StrictDecode for Array<u8, LEN, REVERSE_STR> {
+    fn strict_decode<R: io::Read>(reader: R) -> Result<Self, StrictEncodingError> {
+        <[u8; LEN]>::strict_decode(reader).map(Self::from)
+    }
+}
+
+impl StrictEncode for String {
+    fn strict_encode<W: io::Write>(&self, mut writer: W) -> Result<usize, StrictEncodingError> {
+        let len = encode_len(self.len(), &mut writer)?;
+        writer.write_all(self.as_bytes())?;
+        Ok(len + self.len())
+    }
+}
+
+impl StrictDecode for String {
+    fn strict_decode<R: io::Read>(mut reader: R) -> Result<Self, StrictEncodingError> {
+        let mut bytes = vec![0u8; decode_len(&mut reader)?];
+        reader.read_exact(&mut bytes)?;
+        String::from_utf8(bytes).map_err(|err| err.utf8_error().into())
+    }
+}
+
+impl<T> StrictEncode for Vec<T>
+where
+    T: StrictEncode,
+{
+    fn strict_encode<W: io::Write>(&self, mut writer: W) -> Result<usize, StrictEncodingError> {
+        let mut len = encode_len(self.len(), &mut writer)?;
+        for item in self {
+            len += item.strict_encode(&mut writer)?;
+        }
+        Ok(len)
+    }
+}
+
+impl<T> StrictDecode for Vec<T>
+where
+    T: StrictDecode,
+{
+    fn strict_decode<R: io::Read>(mut reader: R) -> Result<Self, StrictEncodingError> {
+        let len = decode_len(&mut reader)?;
+        (0..len).map(|_| T::strict_decode(&mut reader)).collect()
+    }
+}
+
+impl<T> StrictEncode for BTreeSet<T>
+where
+    T: StrictEncode,
+{
+    fn strict_encode<W: io::Write>(&self, mut writer: W) -> Result<usize, StrictEncodingError> {
+        let mut len = encode_len(self.len(), &mut writer)?;
+        for item in self {
+            len += item.strict_encode(&mut writer)?;
+        }
+        Ok(len)
+    }
+}
+
+impl<T> StrictDecode for BTreeSet<T>
+where
+    T: StrictDecode + Ord,
+{
+    fn strict_decode<R: io::Read>(mut reader: R) -> Result<Self, StrictEncodingError> {
+        let len = decode_len(&mut reader)?;
+        let mut set = BTreeSet::new();
+        for _ in 0..len {
+            let item = T::strict_decode(&mut reader)?;
+            if matches!(set.iter().next_back(), Some(last) if *last >= item) {
+                return Err(StrictEncodingError::NonCanonical("BTreeSet"));
+            }
+            set.insert(item);
+        }
+        Ok(set)
+    }
+}
+
+impl<K, V> StrictEncode for BTreeMap<K, V>
+where
+    K: StrictEncode,
+    V: StrictEncode,
+{
+    fn strict_encode<W: io::Write>(&self, mut writer: W) -> Result<usize, StrictEncodingError> {
+        let mut len = encode_len(self.len(), &mut writer)?;
+        for (key, value) in self {
+            len += key.strict_encode(&mut writer)?;
+            len += value.strict_encode(&mut writer)?;
+        }
+        Ok(len)
+    }
+}
+
+impl<K, V> StrictDecode for BTreeMap<K, V>
+where
+    K: StrictDecode + Ord,
+    V: StrictDecode,
+{
+    fn strict_decode<R: io::Read>(mut reader: R) -> Result<Self, StrictEncodingError> {
+        let len = decode_len(&mut reader)?;
+        let mut map = BTreeMap::new();
+        for _ in 0..len {
+            let key = K::strict_decode(&mut reader)?;
+            if matches!(map.keys().next_back(), Some(last) if *last >= key) {
+                return Err(StrictEncodingError::NonCanonical("BTreeMap"));
+            }
+            let value = V::strict_decode(&mut reader)?;
+            map.insert(key, value);
+        }
+        Ok(map)
+    }
+}
+
+impl<T> StrictEncode for Option<T>
+where
+    T: StrictEncode,
+{
+    fn strict_encode<W: io::Write>(&self, mut writer: W) -> Result<usize, StrictEncodingError> {
+        match self {
+            None => 0u8.strict_encode(writer),
+            Some(value) => Ok(1u8.strict_encode(&mut writer)? + value.strict_encode(writer)?),
+        }
+    }
+}
+
+impl<T> StrictDecode for Option<T>
+where
+    T: StrictDecode,
+{
+    fn strict_decode<R: io::Read>(mut reader: R) -> Result<Self, StrictEncodingError> {
+        match u8::strict_decode(&mut reader)? {
+            0 => Ok(None),
+            1 => T::strict_decode(reader).map(Some),
+            byte => Err(StrictEncodingError::WrongOptionalEncoding(byte)),
+        }
+    }
+}
+
+impl<T> StrictEncode for Box<T>
+where
+    T: StrictEncode,
+{
+    fn strict_encode<W: io::Write>(&self, writer: W) -> Result<usize, StrictEncodingError> {
+        self.as_ref().strict_encode(writer)
+    }
+}
+
+impl<T> StrictDecode for Box<T>
+where
+    T: StrictDecode,
+{
+    fn strict_decode<R: io::Read>(reader: R) -> Result<Self, StrictEncodingError> {
+        T::strict_decode(reader).map(Box::new)
+    }
+}
+
+impl<T> StrictEncode for &T
+where
+    T: StrictEncode,
+{
+    fn strict_encode<W: io::Write>(&self, writer: W) -> Result<usize, StrictEncodingError> {
+        (*self).strict_encode(writer)
+    }
+}
+
+macro_rules! impl_strict_tuple {
+    ($($name:ident),+) => {
+        impl<$($name),+> StrictEncode for ($($name,)+)
+        where
+            $($name: StrictEncode),+
+        {
+            #[allow(non_snake_case)]
+            fn strict_encode<W: io::Write>(&self, mut writer: W) -> Result<usize, StrictEncodingError> {
+                let ($($name,)+) = self;
+                let mut len = 0;
+                $( len += $name.strict_encode(&mut writer)?; )+
+                Ok(len)
+            }
+        }
+
+        impl<$($name),+> StrictDecode for ($($name,)+)
+        where
+            $($name: StrictDecode),+
+        {
+            fn strict_decode<R: io::Read>(mut reader: R) -> Result<Self, StrictEncodingError> {
+                Ok(($($name::strict_decode(&mut reader)?,)+))
+            }
+        }
+    };
+}
+
+impl_strict_tuple!(A, B);
+impl_strict_tuple!(A, B, C);
+impl_strict_tuple!(A, B, C, D);
+
+impl StrictEncode for FlagVec {
+    fn strict_encode<W: io::Write>(&self, writer: W) -> Result<usize, StrictEncodingError> {
+        self.shrunk().as_inner().to_vec().strict_encode(writer)
+    }
+}
+
+impl StrictDecode for FlagVec {
+    fn strict_decode<R: io::Read>(reader: R) -> Result<Self, StrictEncodingError> {
+        let bytes = Vec::<u8>::strict_decode(reader)?;
+        if bytes.last() == Some(&0) {
+            return Err(StrictEncodingError::NonCanonical("FlagVec"));
+        }
+        Ok(FlagVec::from_inner(bytes))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::iter::FromIterator;
+
+    use super::*;
+    use crate::Slice32;
+
+    fn roundtrip<T>(value: T, data: &[u8])
+    where
+        T: StrictEncode + StrictDecode + PartialEq + fmt::Debug,
+    {
+        assert_eq!(value.strict_serialize().unwrap(), data);
+        assert_eq!(value.strict_encode(&mut vec![]).unwrap(), data.len());
+        assert_eq!(T::strict_deserialize(data).unwrap(), value);
+    }
+
+    #[test]
+    fn primitives() {
+        roundtrip(0xABu8, &[0xAB]);
+        roundtrip(0x0102u16, &[0x02, 0x01]);
+        roundtrip(-2i32, &[0xFE, 0xFF, 0xFF, 0xFF]);
+        roundtrip(0x0102_0304_0506_0708u64, &[8, 7, 6, 5, 4, 3, 2, 1]);
+        roundtrip(true, &[1]);
+        roundtrip(false, &[0]);
+        assert_eq!(
+            bool::strict_deserialize([2]),
+            Err(StrictEncodingError::ValueOutOfRange {
+                ty: "bool",
+                value: 2
+            })
+        );
+    }
+
+    #[test]
+    fn custom_ints() {
+        roundtrip(u24::with(0x010203), &[3, 2, 1]);
+        roundtrip(u5::with(31), &[31]);
+        assert_eq!(
+            u5::strict_deserialize([32]),
+            Err(StrictEncodingError::ValueOutOfRange {
+                ty: "u5",
+                value: 32
+            })
+        );
+        let mut data = [0u8; 32];
+        data[0] = 1;
+        roundtrip(u256::from(1u64), &data);
+        assert_eq!(i256::from(-1i64).strict_serialize().unwrap(), [0xFF; 32]);
+    }
+
+    #[test]
+    fn arrays() {
+        roundtrip(Slice32::from([7u8; 32]), &[7u8; 32]);
+        roundtrip([1u8, 2, 3], &[1, 2, 3]);
+    }
+
+    #[test]
+    fn collections() {
+        roundtrip(s!("ab"), &[2, 0, b'a', b'b']);
+        roundtrip(vec![1u16, 2], &[2, 0, 1, 0, 2, 0]);
+        roundtrip(BTreeSet::from_iter(vec![2u8, 1]), &[2, 0, 1, 2]);
+        roundtrip(BTreeMap::from_iter(vec![(1u8, true)]), &[1, 0, 1, 1]);
+        roundtrip(Some(5u8), &[1, 5]);
+        roundtrip(None::<u8>, &[0]);
+        roundtrip((1u8, s!("")), &[1, 0, 0]);
+
+        assert_eq!(
+            String::strict_deserialize([1, 0, 0xFF])
+                .unwrap_err()
+                .to_string(),
+            "invalid utf-8 sequence of 1 bytes from index 0"
+        );
+        assert_eq!(
+            BTreeSet::<u8>::strict_deserialize([2, 0, 2, 1]),
+            Err(StrictEncodingError::NonCanonical("BTreeSet"))
+        );
+        assert_eq!(
+            BTreeSet::<u8>::strict_deserialize([2, 0, 1, 1]),
+            Err(StrictEncodingError::NonCanonical("BTreeSet"))
+        );
+        assert_eq!(
+            Option::<u8>::strict_deserialize([2, 5]),
+            Err(StrictEncodingError::WrongOptionalEncoding(2))
+        );
+        assert_eq!(
+            vec![0u8; u16::MAX as usize + 1].strict_serialize(),
+            Err(StrictEncodingError::ExceedMaxItems(u16::MAX as usize + 1))
+        );
+    }
+
+    #[test]
+    fn flag_vec() {
+        let mut flags = FlagVec::with_capacity(64);
+        flags.set(1);
+        flags.set(9);
+        assert_eq!(flags.strict_serialize().unwrap(), [2, 0, 0b10, 0b10]);
+        assert_eq!(
+            FlagVec::strict_deserialize([2, 0, 0b10, 0b10]).unwrap(),
+            flags
+        );
+        assert_eq!(
+            FlagVec::strict_deserialize([2, 0, 0b10, 0]),
+            Err(StrictEncodingError::NonCanonical("FlagVec"))
+        );
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            u32::strict_deserialize([1, 2]),
+            Err(StrictEncodingError::Io(io::ErrorKind::UnexpectedEof))
+        );
+        assert_eq!(
+            u8::strict_deserialize([1, 2]),
+            Err(StrictEncodingError::DataNotEntirelyConsumed)
+        );
+        assert_eq!(
+            StrictEncodingError::UnknownTag { ty: "Kind", tag: 3 }.to_string(),
+            "tag 3 does not match any variant of `Kind`"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "derive")]
+    fn derived() {
+        #[derive(StrictEncode, StrictDecode, PartialEq, Debug)]
+        #[amplify_crate(crate)]
+        struct Pair<A, B> {
+            first: A,
+            second: Option<B>,
+        }
+
+        #[derive(StrictEncode, StrictDecode, PartialEq, Debug)]
+        #[amplify_crate(crate)]
+        enum Kind<T> {
+            Empty,
+            #[strict(tag = 5)]
+            Value(T),
+            Pair(Pair<T, u8>),
+        }
+
+        roundtrip(
+            Pair {
+                first: 1u16,
+                second: Some(s!("a")),
+            },
+            &[1, 0, 1, 1, 0, b'a'],
+        );
+        roundtrip(Kind::<u16>::Empty, &[0]);
+        roundtrip(Kind::Value(2u16), &[5, 2, 0]);
+        roundtrip(
+            Kind::Pair(Pair {
+                first: 3u16,
+                second: None,
+            }),
+            &[6, 3, 0, 0],
+        );
+        assert_eq!(
+            Kind::<u16>::strict_deserialize([1, 0]),
+            Err(StrictEncodingError::UnknownTag { ty: "Kind", tag: 1 })
+        );
+        assert_eq!(
+            Kind::<u16>::strict_deserialize([5, 1]),
+            Err(StrictEncodingError::Io(io::ErrorKind::UnexpectedEof))
+        );
+    }
+}
@@ -26,16 +26,14 @@ pub trait Wrapper {
     /// Inner type wrapped by the current newtype
     type Inner: Clone;
 
-    /// Instantiates wrapper type with the inner data
+    /// Instantiates wrapper type with the inner data. Does not perform any
+    /// checks of the inner data: wrappers with invariants should be
+    /// constructed with `TryFrom` instead.
     fn from_inner(inner: Self::Inner) -> Self;
 
     /// Returns reference to the inner representation for the wrapper type
     fn as_inner(&self) -> &Self::Inner;
 
-    /// Returns a mutable reference to the inner representation for the wrapper
-    /// type
-    fn as_inner_mut(&mut self) -> &mut Self::Inner;
-
     /// Clones inner data of the wrapped type and return them
     #[inline]
     fn to_inner(&self) -> Self::Inner {
@@ -55,6 +53,63 @@ pub trait Wrapper {
     }
 }
 
+/// Trait providing mutable access to the inner data of the wrapped types.
+///
+/// It is separate from [`Wrapper`], so the types maintaining invariants on
+/// their inner data (like sorted vectors or bounded strings) may expose it
+/// read-only.
+///
+/// The trait works well with `#[derive(WrapperMut)]` from `amplify_derive`
+/// crate
+pub trait WrapperMut: Wrapper {
+    /// Returns a mutable reference to the inner representation for the wrapper
+    /// type
+    fn as_inner_mut(&mut self) -> &mut Self::Inner;
+}
+
+/// Trait providing access to the innermost data of multi-level wrappers,
+/// i.e. wrappers of other wrapped types. For the wrappers of non-wrapped
+/// types the innermost data are the same as [`Wrapper::Inner`].
+///
+/// The trait is implemented by `#[derive(Wrapper)]` from `amplify_derive`
+/// crate for wrappers marked with `#[wrapper(deep)]` attribute, which also
+/// makes the derived traits delegate to the innermost type. Such wrappers
+/// require the wrapped type to implement the trait as well, so the lowest
+/// level of wrapping has to be marked with `#[wrapper(deep_root)]` attribute
+/// (or implement the trait manually), defining the innermost type.
+pub trait DeepWrapper: Wrapper {
+    /// Innermost type wrapped through all levels of wrapping
+    type Deep: Clone;
+
+    /// Instantiates wrapper type, and all intermediate wrappers, with the
+    /// innermost data. Does not perform any checks of the data.
+    fn from_deep(deep: Self::Deep) -> Self;
+
+    /// Returns reference to the innermost data
+    fn as_deep(&self) -> &Self::Deep;
+
+    /// Clones innermost data and returns them
+    #[inline]
+    fn to_deep(&self) -> Self::Deep {
+        self.as_deep().clone()
+    }
+
+    /// Unwraps all levels of wrapping, returning the innermost data
+    fn into_deep(self) -> Self::Deep;
+}
+
+/// Trait providing mutable access to the innermost data of multi-level
+/// wrappers.
+///
+/// The trait is implemented by `#[derive(WrapperMut)]` from `amplify_derive`
+/// crate for wrappers marked with `#[wrapper(deep)]` or
+/// `#[wrapper(deep_root)]` attributes; operations listed in
+/// `#[wrapper_mut(...)]` of deep wrappers modify the innermost data.
+pub trait DeepWrapperMut: DeepWrapper + WrapperMut {
+    /// Returns a mutable reference to the innermost data
+    fn as_deep_mut(&mut self) -> &mut Self::Deep;
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -73,15 +128,86 @@ mod test {
             &self.0
         }
 
+        fn into_inner(self) -> Self::Inner {
+            self.0
+        }
+    }
+
+    impl WrapperMut for TestWrapper {
         fn as_inner_mut(&mut self) -> &mut Self::Inner {
             &mut self.0
         }
+    }
+
+    impl DeepWrapper for TestWrapper {
+        type Deep = u8;
+
+        fn from_deep(deep: Self::Deep) -> Self {
+            Self(deep)
+        }
+
+        fn as_deep(&self) -> &Self::Deep {
+            &self.0
+        }
+
+        fn into_deep(self) -> Self::Deep {
+            self.0
+        }
+    }
+
+    impl DeepWrapperMut for TestWrapper {
+        fn as_deep_mut(&mut self) -> &mut Self::Deep {
+            &mut self.0
+        }
+    }
+
+    #[derive(Copy, Clone, Eq, PartialEq, Debug)]
+    struct NestedWrapper(TestWrapper);
+
+    impl Wrapper for NestedWrapper {
+        type Inner = TestWrapper;
+
+        fn from_inner(inner: Self::Inner) -> Self {
+            Self(inner)
+        }
+
+        fn as_inner(&self) -> &Self::Inner {
+            &self.0
+        }
 
         fn into_inner(self) -> Self::Inner {
             self.0
         }
     }
 
+    impl DeepWrapper for NestedWrapper {
+        type Deep = <TestWrapper as DeepWrapper>::Deep;
+
+        fn from_deep(deep: Self::Deep) -> Self {
+            Self::from_inner(TestWrapper::from_deep(deep))
+        }
+
+        fn as_deep(&self) -> &Self::Deep {
+            self.as_inner().as_deep()
+        }
+
+        fn into_deep(self) -> Self::Deep {
+            self.into_inner().into_deep()
+        }
+    }
+
+    impl WrapperMut for NestedWrapper {
+        fn as_inner_mut(&mut self) -> &mut Self::Inner {
+            &mut self.0
+        }
+    }
+
+    impl DeepWrapperMut for NestedWrapper {
+        fn as_deep_mut(&mut self) -> &mut Self::Deep {
+            self.as_inner_mut().as_deep_mut()
+        }
+    }
+
     #[test]
     fn test_copy() {
         let item = TestWrapper::from_inner(5);
@@ -89,4 +215,27 @@ mod test {
         assert_eq!(item, copy);
         assert_eq!(copy.into_inner(), 5)
     }
+
+    #[test]
+    fn test_as_inner_mut() {
+        let mut item = TestWrapper::from_inner(5);
+        *item.as_inner_mut() += 1;
+        assert_eq!(item.into_inner(), 6)
+    }
+
+    #[test]
+    fn test_deep() {
+        let item = NestedWrapper::from_deep(5);
+        assert_eq!(item.into_inner(), TestWrapper(5));
+        assert_eq!(*item.as_deep(), 5);
+        assert_eq!(item.to_deep(), 5);
+        assert_eq!(item.into_deep(), 5)
+    }
+
+    #[test]
+    fn test_deep_mut() {
+        let mut item = NestedWrapper::from_deep(5);
+        *item.as_deep_mut() += 1;
+        assert_eq!(item.into_inner(), TestWrapper(6))
+    }
 }